@@ -0,0 +1,303 @@
+use bevy_ecs::system::Resource;
+use bevy_reflect::Reflect;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// CLI flag that overrides every other config directory source.
+pub const CONFIG_DIR_FLAG: &str = "--config-dir";
+/// Environment variable checked when no `--config-dir` flag was passed.
+pub const CONFIG_HOME_ENV: &str = "KOMOTOOL_CONFIG_HOME";
+/// CLI flag that points at the directory holding komorebi's `komorebi.json`.
+pub const KOMOREBI_CONFIG_DIR_FLAG: &str = "--komorebi-config-dir";
+/// komorebi's own config directory variable, checked when no `--komorebi-config-dir` flag was passed.
+pub const KOMOREBI_CONFIG_HOME_ENV: &str = "KOMOREBI_CONFIG_HOME";
+/// komorebi's static config file name.
+pub const KOMOREBI_CONFIG_FILE: &str = "komorebi.json";
+
+/// The config directory komotool resolved at startup.
+#[derive(Resource, Reflect, Debug, Clone)]
+pub struct KomotoolConfigPath(pub PathBuf);
+
+/// Extracts the value of `--config-dir <path>` or `--config-dir=<path>` from the given arguments.
+pub fn config_dir_from_args<I>(args: I) -> Option<PathBuf>
+where
+    I: IntoIterator<Item = String>,
+{
+    path_flag_from_args(args, CONFIG_DIR_FLAG)
+}
+
+/// Extracts the value of `<flag> <path>` or `<flag>=<path>` from the given arguments.
+pub fn path_flag_from_args<I>(args: I, flag: &str) -> Option<PathBuf>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next().filter(|v| !v.is_empty()).map(PathBuf::from);
+        }
+        if let Some(value) = arg.strip_prefix(flag).and_then(|v| v.strip_prefix('=')) {
+            if !value.is_empty() {
+                return Some(PathBuf::from(value));
+            }
+        }
+    }
+    None
+}
+
+/// Looks up an environment variable, treating an empty value as unset.
+fn non_empty(env: &impl Fn(&str) -> Option<String>, key: &str) -> Option<String> {
+    env(key).filter(|v| !v.is_empty())
+}
+
+fn process_env(key: &str) -> Option<String> {
+    env::var(key).ok()
+}
+
+/// Platform default: `XDG_CONFIG_HOME/komotool`, falling back to `~/.config/komotool`.
+#[cfg(unix)]
+fn platform_config_path(env: &impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    if let Some(xdg) = non_empty(env, "XDG_CONFIG_HOME") {
+        return Some(Path::new(&xdg).join("komotool"));
+    }
+    non_empty(env, "HOME").map(|home| Path::new(&home).join(".config").join("komotool"))
+}
+
+/// Platform default: `USERPROFILE\.config\Komotool`.
+#[cfg(windows)]
+fn platform_config_path(env: &impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    non_empty(env, "USERPROFILE").map(|usr| Path::new(&usr).join(".config").join("Komotool"))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn platform_config_path(_env: &impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    None
+}
+
+/// Resolves the komotool config directory without touching the filesystem.
+///
+/// Sources are checked in order: the `--config-dir` flag, `KOMOTOOL_CONFIG_HOME`,
+/// then the platform default.
+pub fn resolve_komotool_config_path() -> std::io::Result<PathBuf> {
+    komotool_config_path_from(env::args().skip(1), process_env)
+}
+
+/// [`resolve_komotool_config_path`] over the given arguments and environment lookup.
+fn komotool_config_path_from<I>(
+    args: I,
+    env: impl Fn(&str) -> Option<String>,
+) -> std::io::Result<PathBuf>
+where
+    I: IntoIterator<Item = String>,
+{
+    if let Some(path) = config_dir_from_args(args) {
+        return Ok(path);
+    }
+    if let Some(path) = non_empty(&env, CONFIG_HOME_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+    if let Some(path) = platform_config_path(&env) {
+        return Ok(path);
+    }
+
+    let platform_hint = if cfg!(windows) {
+        "USERPROFILE"
+    } else {
+        "XDG_CONFIG_HOME or HOME"
+    };
+    let error = format!(
+        "Could not resolve the Komotool config directory. Pass {} <path>, set {}, or set {}.",
+        CONFIG_DIR_FLAG, CONFIG_HOME_ENV, platform_hint
+    );
+    Err(std::io::Error::new(std::io::ErrorKind::NotFound, error))
+}
+
+/// Function that resolves the Komotool config path and ensures the directory exists.
+pub fn get_or_create_komotool_config_path() -> std::io::Result<PathBuf> {
+    create_config_dir(resolve_komotool_config_path()?)
+}
+
+fn create_config_dir(komotool_path: PathBuf) -> std::io::Result<PathBuf> {
+    if !komotool_path.exists() {
+        fs::create_dir_all(&komotool_path)?;
+        println!("Created directory: {}", komotool_path.display());
+    }
+
+    Ok(komotool_path)
+}
+
+/// Resolves the directory komorebi reads `komorebi.json` from.
+///
+/// Sources are checked in order: the `--komorebi-config-dir` flag, then `KOMOREBI_CONFIG_HOME`.
+/// komorebi's own default, the home directory, isn't used: the directory is watched
+/// recursively as an asset source, and watching all of home is too much.
+pub fn resolve_komorebi_config_path() -> Option<PathBuf> {
+    komorebi_config_path_from(env::args().skip(1), process_env)
+}
+
+/// [`resolve_komorebi_config_path`] over the given arguments and environment lookup.
+fn komorebi_config_path_from<I>(args: I, env: impl Fn(&str) -> Option<String>) -> Option<PathBuf>
+where
+    I: IntoIterator<Item = String>,
+{
+    if let Some(path) = path_flag_from_args(args, KOMOREBI_CONFIG_DIR_FLAG) {
+        return Some(path);
+    }
+    non_empty(&env, KOMOREBI_CONFIG_HOME_ENV).map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    fn env(vars: &[(&str, &Path)]) -> impl Fn(&str) -> Option<String> {
+        let vars: Vec<(String, String)> = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.display().to_string()))
+            .collect();
+        move |key: &str| {
+            vars.iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.clone())
+        }
+    }
+
+    #[cfg(unix)]
+    const HOME: &str = "HOME";
+    #[cfg(windows)]
+    const HOME: &str = "USERPROFILE";
+
+    #[cfg(unix)]
+    fn platform_default(home: &Path) -> PathBuf {
+        home.join(".config").join("komotool")
+    }
+
+    #[cfg(windows)]
+    fn platform_default(home: &Path) -> PathBuf {
+        home.join(".config").join("Komotool")
+    }
+
+    #[test]
+    fn flag_is_read_with_a_space_or_an_equals_sign() {
+        assert_eq!(
+            config_dir_from_args(args(&["--verbose", "--config-dir", "a"])),
+            Some(PathBuf::from("a"))
+        );
+        assert_eq!(
+            config_dir_from_args(args(&["--config-dir=b"])),
+            Some(PathBuf::from("b"))
+        );
+        assert_eq!(config_dir_from_args(args(&["--config-dir"])), None);
+        assert_eq!(config_dir_from_args(args(&["--config-dir="])), None);
+        assert_eq!(config_dir_from_args(args(&["--config-directory=c"])), None);
+    }
+
+    #[test]
+    fn flag_beats_env_beats_platform_default() -> std::io::Result<()> {
+        let (flag, config_home, home) = (tempdir()?, tempdir()?, tempdir()?);
+        let flag_arg = format!("{}={}", CONFIG_DIR_FLAG, flag.path().display());
+        let env = env(&[(CONFIG_HOME_ENV, config_home.path()), (HOME, home.path())]);
+
+        assert_eq!(
+            komotool_config_path_from(args(&[&flag_arg]), &env)?,
+            flag.path()
+        );
+        assert_eq!(
+            komotool_config_path_from(args(&[]), &env)?,
+            config_home.path()
+        );
+        let env_without_config_home = |key: &str| (key != CONFIG_HOME_ENV).then(|| env(key))?;
+        assert_eq!(
+            komotool_config_path_from(args(&[]), env_without_config_home)?,
+            platform_default(home.path())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn empty_env_vars_are_unset() -> std::io::Result<()> {
+        let home = tempdir()?;
+        let env = |key: &str| match key {
+            CONFIG_HOME_ENV => Some(String::new()),
+            HOME => Some(home.path().display().to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            komotool_config_path_from(args(&[]), env)?,
+            platform_default(home.path())
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn xdg_config_home_beats_home() -> std::io::Result<()> {
+        let (xdg, home) = (tempdir()?, tempdir()?);
+        let env = env(&[("XDG_CONFIG_HOME", xdg.path()), ("HOME", home.path())]);
+        assert_eq!(
+            komotool_config_path_from(args(&[]), env)?,
+            xdg.path().join("komotool")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn nothing_to_resolve_from_is_an_error() {
+        let error = komotool_config_path_from(args(&[]), |_| None).err();
+        assert_eq!(error.map(|e| e.kind()), Some(std::io::ErrorKind::NotFound));
+    }
+
+    #[test]
+    fn komorebi_config_dir_never_falls_back_to_home() -> std::io::Result<()> {
+        let (flag, komorebi_home, home) = (tempdir()?, tempdir()?, tempdir()?);
+        let flag_args = args(&[KOMOREBI_CONFIG_DIR_FLAG, &flag.path().display().to_string()]);
+        let env = env(&[
+            (KOMOREBI_CONFIG_HOME_ENV, komorebi_home.path()),
+            (HOME, home.path()),
+        ]);
+
+        assert_eq!(
+            komorebi_config_path_from(flag_args, &env),
+            Some(flag.path().to_path_buf())
+        );
+        assert_eq!(
+            komorebi_config_path_from(args(&[]), &env),
+            Some(komorebi_home.path().to_path_buf())
+        );
+        let home_only = |key: &str| (key == HOME).then(|| home.path().display().to_string());
+        assert_eq!(komorebi_config_path_from(args(&[]), home_only), None);
+        Ok(())
+    }
+
+    #[test]
+    fn resolving_does_not_create_the_directory() -> std::io::Result<()> {
+        let root = tempdir()?;
+        let missing = root.path().join("missing");
+        let env = env(&[(CONFIG_HOME_ENV, &missing)]);
+        assert_eq!(komotool_config_path_from(args(&[]), env)?, missing);
+        assert!(!missing.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn missing_config_dirs_are_created() -> std::io::Result<()> {
+        let root = tempdir()?;
+        let missing = root.path().join("nested").join("komotool");
+        assert_eq!(create_config_dir(missing.clone())?, missing);
+        assert!(missing.is_dir());
+
+        // An existing directory is left as it is
+        let script = missing.join("init.lua");
+        fs::write(&script, "")?;
+        assert_eq!(create_config_dir(missing.clone())?, missing);
+        assert!(script.exists());
+        Ok(())
+    }
+}
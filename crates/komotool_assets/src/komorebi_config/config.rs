@@ -0,0 +1,138 @@
+use crate::config_dir::KOMOREBI_CONFIG_FILE;
+use bevy_asset::io::Reader;
+use bevy_asset::{Asset, AssetEvent, AssetLoader, AssetServer, Assets, Handle, LoadContext};
+use bevy_ecs::event::EventReader;
+use bevy_ecs::system::{Commands, Res, ResMut, Resource};
+use bevy_reflect::{Reflect, TypePath};
+use komorebi_client::StaticConfig;
+use komotool_ecs::resources::KomorebiStaticConfig;
+use komotool_utils::diagnostics::ScriptDiagnostics;
+use komotool_utils::handler::SyntaxError;
+use std::path::Path;
+
+/// Asset source registered for the directory holding `komorebi.json`.
+pub const KOMOREBI_CONFIG_SOURCE: &str = "komorebi_config";
+
+/// komorebi's `komorebi.json`, parsed into its [`StaticConfig`].
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct KomorebiConfigAsset {
+    /// Parsed config, `None` if the file failed to parse
+    pub config: Option<StaticConfig>,
+    pub error: Option<SyntaxError>,
+}
+
+/// Loads `komorebi.json`. Parse errors are kept in the asset so they can be reported as diagnostics.
+#[derive(Default)]
+pub struct KomorebiConfigLoader;
+
+impl AssetLoader for KomorebiConfigLoader {
+    type Asset = KomorebiConfigAsset;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(parse_komorebi_config(&bytes))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        // Picked by asset type, so it doesn't claim .json files from ScriptConfigLoader
+        &[]
+    }
+}
+
+pub fn parse_komorebi_config(bytes: &[u8]) -> KomorebiConfigAsset {
+    match serde_json::from_slice::<StaticConfig>(bytes) {
+        Ok(config) => KomorebiConfigAsset {
+            config: Some(config),
+            error: None,
+        },
+        Err(e) => KomorebiConfigAsset {
+            config: None,
+            error: Some(SyntaxError {
+                line: Some(e.line()),
+                column: Some(e.column()),
+                message: e.to_string(),
+            }),
+        },
+    }
+}
+
+/// Keeps `komorebi.json` loaded so the file watcher hot-reloads it.
+#[derive(Resource, Reflect)]
+pub struct KomorebiConfigHandle(pub Handle<KomorebiConfigAsset>);
+
+/// The resolved komorebi config directory, registered as [`KOMOREBI_CONFIG_SOURCE`].
+#[derive(Resource, Reflect, Debug, Clone)]
+pub struct KomorebiConfigPath(pub std::path::PathBuf);
+
+pub fn load_komorebi_config(
+    asset_server: Res<AssetServer>,
+    config_path: Option<Res<KomorebiConfigPath>>,
+    mut commands: Commands,
+) {
+    let Some(config_path) = config_path else {
+        println!("No komorebi config directory resolved, KomorebiStaticConfig stays empty");
+        return;
+    };
+    if !config_path.0.join(KOMOREBI_CONFIG_FILE).exists() {
+        println!(
+            "No {} found in {}, KomorebiStaticConfig stays empty",
+            KOMOREBI_CONFIG_FILE,
+            config_path.0.display()
+        );
+        return;
+    }
+    let source = bevy_asset::io::AssetSourceId::from(KOMOREBI_CONFIG_SOURCE);
+    let asset_path =
+        bevy_asset::AssetPath::from_path(Path::new(KOMOREBI_CONFIG_FILE)).with_source(source);
+    // Typed load, so the loader is picked by asset type rather than the .json extension
+    let handle: Handle<KomorebiConfigAsset> = asset_server.load(asset_path);
+    commands.insert_resource(KomorebiConfigHandle(handle));
+}
+
+/// Copies `komorebi.json` into [`KomorebiStaticConfig`] whenever it is loaded or edited.
+///
+/// A config that fails to parse is reported to [`ScriptDiagnostics`] and the previous
+/// config stays active.
+pub fn handle_komorebi_config_events(
+    mut events: EventReader<AssetEvent<KomorebiConfigAsset>>,
+    configs: Res<Assets<KomorebiConfigAsset>>,
+    config_path: Option<Res<KomorebiConfigPath>>,
+    mut static_config: ResMut<KomorebiStaticConfig>,
+    mut diagnostics: ResMut<ScriptDiagnostics>,
+) {
+    let file = config_path.map_or_else(
+        || KOMOREBI_CONFIG_FILE.to_string(),
+        |path| path.0.join(KOMOREBI_CONFIG_FILE).display().to_string(),
+    );
+    for event in events.read() {
+        match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                let Some(asset) = configs.get(*id) else {
+                    continue;
+                };
+                if let Some(error) = &asset.error {
+                    let diagnostic = error.clone().into_diagnostic(file.clone());
+                    println!("komorebi config failed to parse: {}", diagnostic);
+                    diagnostics.report(diagnostic);
+                    continue;
+                }
+                diagnostics.clear(&file);
+                static_config.config = asset.config.clone();
+                println!("Loaded komorebi static config from {}", file);
+            }
+            AssetEvent::Removed { .. } => {
+                diagnostics.clear(&file);
+                static_config.config = None;
+            }
+            _ => {}
+        }
+    }
+}
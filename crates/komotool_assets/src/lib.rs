@@ -1,8 +1,18 @@
+pub mod config_dir;
+pub mod komorebi_config;
 pub mod remove_watcher;
+pub mod script_config;
+pub mod validation;
+pub mod window_rules;
 
 pub mod prelude {
     pub use super::*;
+    pub use config_dir::*;
+    pub use komorebi_config::*;
     pub use remove_watcher::*;
+    pub use script_config::*;
+    pub use validation::*;
+    pub use window_rules::*;
 }
 
 use bevy_app::{App, Plugin, PreStartup, PreUpdate, Startup};
@@ -25,16 +35,37 @@ use bevy_reflect::Reflect;
 use bevy_state::app::AppExtStates;
 use bevy_state::condition::in_state;
 use bevy_state::state::{NextState, OnEnter, OnExit, States};
-use komotool_utils::callbacklabels::{OnPostUpdate, OnPreUpdate, OnUpdate};
+use config_dir::{
+    KOMOREBI_CONFIG_DIR_FLAG, KOMOREBI_CONFIG_HOME_ENV, KomotoolConfigPath,
+    get_or_create_komotool_config_path, resolve_komorebi_config_path,
+};
+use komorebi_config::{
+    KOMOREBI_CONFIG_SOURCE, KomorebiConfigAsset, KomorebiConfigLoader, KomorebiConfigPath,
+    handle_komorebi_config_events, load_komorebi_config,
+};
+use komotool_ecs::resources::{
+    GENERATED_STATIC_CONFIG_FILE, SESSIONS_FOLDER, SessionDirectory, StaticConfigOutput,
+    USER_DATA_FILE, UserDataPath,
+};
+use komotool_utils::callbacklabels::{
+    OnConfigChanged, OnContainerMoved, OnFocusChanged, OnLayout, OnMonitorConnected,
+    OnMonitorDisconnected, OnPostUpdate, OnPreUpdate, OnUpdate, OnWindowDespawned, OnWindowSpawned,
+    OnWorkspaceFocused,
+};
+use komotool_utils::diagnostics::{ScriptDiagnostic, ScriptDiagnostics};
 use komotool_utils::handler::{KomoToolScriptStore, KomoToolScriptStoreAll, ScriptFunctionChecker};
 use komotool_utils::loading_systems::{decrement_loading_counter, increment_loading_counter};
+use komotool_utils::script_groups::ScriptGroups;
 use komotool_utils::startup_schedule::PreUpdateStartup;
-use remove_watcher::{check_file_events, setup_file_watcher};
+use remove_watcher::{WatchedScriptHandles, check_file_events, setup_file_watcher};
+use script_config::{ScriptConfig, ScriptConfigLoader, ScriptConfigs, handle_script_config_events};
 use std::{
-    collections::HashMap,
-    env, fs,
+    collections::{HashMap, HashSet},
+    fs,
     path::{Path, PathBuf},
 };
+use validation::{HeldBackScripts, hold_back_invalid_scripts, release_held_back_scripts};
+use window_rules::{handle_window_rule_events, load_window_rules};
 
 #[derive(States, Default, Debug, Clone, Eq, PartialEq, Hash)]
 pub enum ScriptLoadState {
@@ -54,17 +85,49 @@ pub struct ScriptEntityMapping {
     pub handle_to_entity: HashMap<AssetId<ScriptAsset>, Entity>,
 }
 
-/// The KomotoolAssetsPlugin, which registers `.config\Komotool`
+/// The KomotoolAssetsPlugin, which registers the resolved Komotool config directory
 /// as a custom asset source and ensures the `AssetPlugin` is added afterward.
 #[derive(Default)]
 pub struct KomotoolAssetsPlugin;
 
 impl Plugin for KomotoolAssetsPlugin {
     fn build(&self, app: &mut App) {
-        if let Ok(komotool_config_path) = get_or_create_komotool_config_path() {
+        match get_or_create_komotool_config_path() {
+            Ok(komotool_config_path) => {
+                app.register_asset_source(
+                    "komotool_config",
+                    AssetSourceBuilder::platform_default(
+                        &komotool_config_path.to_string_lossy(),
+                        None,
+                    ),
+                );
+                app.insert_resource(StaticConfigOutput {
+                    file: Some(komotool_config_path.join(GENERATED_STATIC_CONFIG_FILE)),
+                });
+                app.insert_resource(SessionDirectory {
+                    dir: Some(komotool_config_path.join(SESSIONS_FOLDER)),
+                });
+                app.insert_resource(UserDataPath {
+                    file: Some(komotool_config_path.join(USER_DATA_FILE)),
+                });
+                app.insert_resource(KomotoolConfigPath(komotool_config_path));
+            }
+            Err(e) => {
+                eprintln!("Komotool startup error: {}", e);
+                eprintln!("No scripts will be loaded until a config directory is available.");
+            }
+        }
+
+        if let Some(komorebi_config_path) = resolve_komorebi_config_path() {
             app.register_asset_source(
-                "komotool_config",
-                AssetSourceBuilder::platform_default(&komotool_config_path.to_string_lossy(), None),
+                KOMOREBI_CONFIG_SOURCE,
+                AssetSourceBuilder::platform_default(&komorebi_config_path.to_string_lossy(), None),
+            );
+            app.insert_resource(KomorebiConfigPath(komorebi_config_path));
+        } else {
+            println!(
+                "komorebi.json isn't loaded. Pass {} <path> or set {} to load it.",
+                KOMOREBI_CONFIG_DIR_FLAG, KOMOREBI_CONFIG_HOME_ENV
             );
         }
 
@@ -74,13 +137,28 @@ impl Plugin for KomotoolAssetsPlugin {
         });
 
         // Add general script loading functionality
-        app.init_state::<ScriptLoadState>()
+        app.init_asset::<ScriptConfig>()
+            .init_asset_loader::<ScriptConfigLoader>()
+            .init_asset::<KomorebiConfigAsset>()
+            .init_asset_loader::<KomorebiConfigLoader>()
+            .init_state::<ScriptLoadState>()
             .init_resource::<ScriptEntityMapping>()
+            .init_resource::<ScriptConfigs>()
+            .init_resource::<HeldBackScripts>()
+            .init_resource::<WatchedScriptHandles>()
             .add_systems(OnEnter(ScriptLoadState::Loading), increment_loading_counter)
             .add_systems(OnExit(ScriptLoadState::Loading), decrement_loading_counter)
             .add_systems(Startup, setup_file_watcher)
             .add_systems(PreUpdate, check_file_events)
-            .add_systems(PreStartup, load_scripts)
+            .add_systems(
+                PreStartup,
+                (
+                    load_scripts,
+                    load_script_profiles,
+                    load_komorebi_config,
+                    load_window_rules,
+                ),
+            )
             .add_systems(
                 PreUpdateStartup,
                 check_scripts_loaded.run_if(in_state(ScriptLoadState::Loading)),
@@ -88,34 +166,37 @@ impl Plugin for KomotoolAssetsPlugin {
             .add_systems(
                 PreUpdate,
                 handle_script_store_updates_all.in_set(ScriptingSystemSet::ScriptCommandDispatch),
+            )
+            .add_systems(
+                PreUpdate,
+                (
+                    hold_back_invalid_scripts
+                        .after(ScriptingSystemSet::ScriptMetadataInsertion)
+                        .before(ScriptingSystemSet::ScriptAssetDispatch),
+                    release_held_back_scripts.after(ScriptingSystemSet::ScriptCommandDispatch),
+                ),
+            )
+            .add_systems(PreUpdate, handle_script_config_events)
+            .add_systems(PreUpdate, handle_komorebi_config_events)
+            .add_systems(PreUpdate, handle_window_rule_events)
+            .add_systems(PreUpdate, handle_script_profile_events)
+            .add_systems(
+                PreUpdate,
+                (
+                    handle_callback_store_updates::<OnWindowSpawned>,
+                    handle_callback_store_updates::<OnWindowDespawned>,
+                    handle_callback_store_updates::<OnContainerMoved>,
+                    handle_callback_store_updates::<OnWorkspaceFocused>,
+                    handle_callback_store_updates::<OnMonitorConnected>,
+                    handle_callback_store_updates::<OnMonitorDisconnected>,
+                    handle_callback_store_updates::<OnFocusChanged>,
+                    handle_callback_store_updates::<OnLayout>,
+                )
+                    .after(handle_script_store_updates_all),
             );
     }
 }
 
-/// Function that retrieves the `.config\Komotool` path and ensures the directory exists.
-pub fn get_or_create_komotool_config_path() -> std::io::Result<PathBuf> {
-    let user_profile = env::var("USERPROFILE");
-    match user_profile {
-        Ok(usr) => {
-            let komotool_path = Path::new(&usr).join(".config").join("Komotool");
-
-            if !komotool_path.exists() {
-                fs::create_dir_all(&komotool_path)?;
-                println!("Created directory: {}", komotool_path.display());
-            }
-
-            Ok(komotool_path)
-        }
-        Err(e) => {
-            let error = format!(
-                "Failed to fetch USERPROFILE environment variable. ValueError: {}",
-                e
-            );
-            Err(std::io::Error::new(std::io::ErrorKind::NotFound, error))
-        }
-    }
-}
-
 /// Function to load all scripts from the "scripts" folder
 pub fn load_scripts(asset_server: Res<AssetServer>, mut commands: Commands) {
     if let Ok(komotool_config_path) = get_or_create_komotool_config_path() {
@@ -137,6 +218,102 @@ pub fn load_scripts(asset_server: Res<AssetServer>, mut commands: Commands) {
     commands.insert_resource(ScriptLoadTracker { handle });
 }
 
+/// Config file mapping a profile name to the script groups (folders below `scripts/`) it
+/// enables: `{ "home": ["borders", "personal"], "office": ["borders", "work"] }`
+pub const PROFILES_FILE: &str = "profiles.json";
+
+/// Whether a config asset path is the profiles file rather than a script config.
+pub fn is_profiles_file(path: &Path) -> bool {
+    path == Path::new(PROFILES_FILE)
+}
+
+/// Keeps `profiles.json` loaded so the file watcher hot-reloads it.
+#[derive(Resource, Reflect)]
+pub struct ScriptProfilesFile(pub Handle<ScriptConfig>);
+
+/// Loads `profiles.json` from the config directory, where the asset watcher hot-reloads edits.
+///
+/// A file that doesn't exist yet at startup is only picked up once it's moved into place, or on
+/// the next start.
+pub fn load_script_profiles(
+    asset_server: Res<AssetServer>,
+    config_path: Option<Res<KomotoolConfigPath>>,
+    mut commands: Commands,
+) {
+    let Some(config_path) = config_path else {
+        return;
+    };
+    if !config_path.0.join(PROFILES_FILE).exists() {
+        return;
+    }
+    let source = bevy_asset::io::AssetSourceId::from("komotool_config");
+    let asset_path = AssetPath::from_path(Path::new(PROFILES_FILE)).with_source(source);
+    commands.insert_resource(ScriptProfilesFile(asset_server.load(asset_path)));
+}
+
+/// Replaces the profiles in [`ScriptGroups`] whenever `profiles.json` is added, edited or removed.
+///
+/// A file that fails to parse is reported to [`ScriptDiagnostics`] and the previous profiles
+/// stay active.
+pub fn handle_script_profile_events(
+    mut events: EventReader<AssetEvent<ScriptConfig>>,
+    configs: Res<Assets<ScriptConfig>>,
+    asset_server: Res<AssetServer>,
+    mut groups: ResMut<ScriptGroups>,
+    mut diagnostics: ResMut<ScriptDiagnostics>,
+) {
+    for event in events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id } | AssetEvent::Removed { id }) =
+            event
+        else {
+            continue;
+        };
+        let Some(path) = asset_server.get_path(*id) else {
+            continue;
+        };
+        if !is_profiles_file(path.path()) {
+            continue;
+        }
+        let file = path.path().to_string_lossy().to_string();
+
+        if let AssetEvent::Removed { .. } = event {
+            diagnostics.clear(&file);
+            groups.set_profiles(HashMap::new());
+            println!("Removed script profiles");
+            continue;
+        }
+        let Some(config) = configs.get(*id) else {
+            continue;
+        };
+        if let Some(error) = &config.error {
+            let diagnostic = error.clone().into_diagnostic(file);
+            println!("Script profiles failed to parse: {}", diagnostic);
+            diagnostics.report(diagnostic);
+            continue;
+        }
+        let Some(value) = &config.value else {
+            continue;
+        };
+        match serde_json::from_value::<HashMap<String, HashSet<String>>>(value.clone()) {
+            Ok(profiles) => {
+                diagnostics.clear(&file);
+                println!("Loaded {} script profiles", profiles.len());
+                groups.set_profiles(profiles);
+            }
+            Err(e) => {
+                let diagnostic = ScriptDiagnostic {
+                    file,
+                    line: None,
+                    column: None,
+                    message: format!("Invalid script profiles: {}", e),
+                };
+                println!("Script profiles failed validation: {}", diagnostic);
+                diagnostics.report(diagnostic);
+            }
+        }
+    }
+}
+
 /// System to check if scripts are loaded and register them
 pub fn check_scripts_loaded(
     asset_server: Res<AssetServer>,
@@ -410,6 +587,11 @@ pub fn is_in_script_folder(file_path: &str, komotool_path: &Path) -> bool {
     file_path.starts_with(&scripts_path)
 }
 
+/// Keeps the `KomoToolScriptStoreAll` stores in sync with the script assets.
+///
+/// A script that failed validation was held back by [`hold_back_invalid_scripts`] and keeps
+/// its previous store entries.
+#[allow(clippy::too_many_arguments)]
 pub fn handle_script_store_updates_all(
     mut events: EventReader<AssetEvent<ScriptAsset>>,
     assets: Res<Assets<ScriptAsset>>,
@@ -418,6 +600,10 @@ pub fn handle_script_store_updates_all(
     mut update: ResMut<KomoToolScriptStoreAll<OnUpdate>>,
     mut preupdate: ResMut<KomoToolScriptStoreAll<OnPreUpdate>>,
     mut postupdate: ResMut<KomoToolScriptStoreAll<OnPostUpdate>>,
+    mut config_changed: ResMut<KomoToolScriptStoreAll<OnConfigChanged>>,
+    held_back: Res<HeldBackScripts>,
+    mut diagnostics: ResMut<ScriptDiagnostics>,
+    mut groups: ResMut<ScriptGroups>,
 ) {
     // Process asset events
     for event in events.read() {
@@ -430,6 +616,17 @@ pub fn handle_script_store_updates_all(
                 };
 
                 if let Some(script_bytes) = assets.get(*id) {
+                    // Convert to ScriptId format (path without source)
+                    let script_id = ScriptId::from(
+                        script_bytes.asset_path.path().to_string_lossy().to_string(),
+                    );
+
+                    // Scripts that don't compile were held back by hold_back_invalid_scripts
+                    if held_back.is_held_back(*id) {
+                        continue;
+                    }
+                    groups.register_script(&script_id);
+
                     // Get all functions in the script once
                     let script_functions = match language {
                         Language::Lua => LuaScriptingPlugin::get_functions(&script_bytes.content),
@@ -438,11 +635,6 @@ pub fn handle_script_store_updates_all(
                         _ => continue,
                     };
 
-                    // Convert to ScriptId format (path without source)
-                    let script_id = ScriptId::from(
-                        script_bytes.asset_path.path().to_string_lossy().to_string(),
-                    );
-
                     // Check and update each store
                     if script_functions.contains(OnUpdate::into_callback_label().as_ref()) {
                         update.scripts.insert(script_id.clone());
@@ -459,6 +651,11 @@ pub fn handle_script_store_updates_all(
                         println!("Added to OnPostUpdate: {}", script_id);
                     }
 
+                    if script_functions.contains(OnConfigChanged::into_callback_label().as_ref()) {
+                        config_changed.scripts.insert(script_id.clone());
+                        println!("Added to OnConfigChanged: {}", script_id);
+                    }
+
                     println!(
                         "Processed new script: {}",
                         script_bytes.asset_path.path().to_string_lossy()
@@ -480,6 +677,11 @@ pub fn handle_script_store_updates_all(
                         script_bytes.asset_path.path().to_string_lossy().to_string(),
                     );
 
+                    // The previous version stays active if hold_back_invalid_scripts held this one back
+                    if held_back.is_held_back(*id) {
+                        continue;
+                    }
+
                     // Get all functions in the script once
                     let script_functions = match language {
                         Language::Lua => LuaScriptingPlugin::get_functions(&script_bytes.content),
@@ -506,6 +708,12 @@ pub fn handle_script_store_updates_all(
                     } else {
                         postupdate.scripts.shift_remove(&script_id);
                     }
+
+                    if script_functions.contains(OnConfigChanged::into_callback_label().as_ref()) {
+                        config_changed.scripts.insert(script_id.clone());
+                    } else {
+                        config_changed.scripts.shift_remove(&script_id);
+                    }
                 }
             }
             AssetEvent::Removed { id } => {
@@ -516,6 +724,9 @@ pub fn handle_script_store_updates_all(
                     update.scripts.shift_remove(&script_id);
                     preupdate.scripts.shift_remove(&script_id);
                     postupdate.scripts.shift_remove(&script_id);
+                    config_changed.scripts.shift_remove(&script_id);
+
+                    diagnostics.clear(&script_id);
 
                     println!("File removed: {}", path.path().to_string_lossy());
                 }
@@ -525,28 +736,138 @@ pub fn handle_script_store_updates_all(
     }
 }
 
-pub fn create_komotool_asset_path(file_path: &str) -> AssetPath<'static> {
-    let path = Path::new(file_path);
-
-    // Find the Komotool directory in the path (case insensitive)
-    let mut components = Vec::new();
-    let mut found_komotool = false;
+/// Keeps the store of an event driven callback in sync with script assets.
+///
+/// Runs after [`handle_script_store_updates_all`], which has already validated the scripts;
+/// a script with a diagnostic keeps its previous membership.
+pub fn handle_callback_store_updates<L>(
+    mut events: EventReader<AssetEvent<ScriptAsset>>,
+    assets: Res<Assets<ScriptAsset>>,
+    asset_server: Res<AssetServer>,
+    metadata_store: Res<ScriptMetadataStore>,
+    diagnostics: Res<ScriptDiagnostics>,
+    mut store: ResMut<KomoToolScriptStoreAll<L>>,
+) where
+    L: IntoCallbackLabel + Send + Sync + 'static + std::default::Default,
+{
+    for event in events.read() {
+        match event {
+            AssetEvent::Added { id }
+            | AssetEvent::LoadedWithDependencies { id }
+            | AssetEvent::Modified { id } => {
+                let Some(script_bytes) = assets.get(*id) else {
+                    continue;
+                };
+                let script_id =
+                    ScriptId::from(script_bytes.asset_path.path().to_string_lossy().to_string());
+                if diagnostics.get(&script_id).is_some() {
+                    continue;
+                }
 
-    for component in path.components() {
-        let component_str = component.as_os_str().to_string_lossy();
+                let language = if let Some(script_metadata) = metadata_store.get(*id) {
+                    script_metadata.language.clone()
+                } else {
+                    Language::Unknown
+                };
+                let script_functions = match language {
+                    Language::Lua => LuaScriptingPlugin::get_functions(&script_bytes.content),
+                    Language::Rhai => RhaiScriptingPlugin::get_functions(&script_bytes.content),
+                    _ => continue,
+                };
 
-        if found_komotool {
-            // After finding Komotool, collect all remaining components
-            components.push(component_str.to_string());
-        } else if component_str.to_lowercase() == "komotool" {
-            // Found the Komotool directory (case insensitive)
-            found_komotool = true;
+                if script_functions.contains(L::into_callback_label().as_ref()) {
+                    store.scripts.insert(script_id);
+                } else {
+                    store.scripts.shift_remove(&script_id);
+                }
+            }
+            AssetEvent::Removed { id } => {
+                if let Some(path) = asset_server.get_path(*id) {
+                    let script_id = ScriptId::from(path.path().to_string_lossy().to_string());
+                    store.scripts.shift_remove(&script_id);
+                }
+            }
+            _ => {}
         }
     }
+}
+
+/// Maps a filesystem path below the config root to a `komotool_config://` asset path.
+///
+/// Returns `None` for paths outside the config root.
+pub fn create_komotool_asset_path(
+    file_path: &Path,
+    config_root: &Path,
+) -> Option<AssetPath<'static>> {
+    let relative = match file_path.strip_prefix(config_root) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => {
+            // notify may report canonical paths while the root was given through a symlink
+            let canonical_root = config_root.canonicalize().ok()?;
+            file_path.strip_prefix(&canonical_root).ok()?.to_path_buf()
+        }
+    };
 
     // Join the components with forward slashes for the asset path
-    let relative_path = components.join("/");
+    let relative_path = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/");
+    if relative_path.is_empty() {
+        return None;
+    }
 
     let source = bevy_asset::io::AssetSourceId::from("komotool_config");
-    AssetPath::from(relative_path).with_source(source)
+    Some(AssetPath::from(relative_path).with_source(source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn komotool_config_path(path: &str) -> AssetPath<'static> {
+        AssetPath::from(path.to_string())
+            .with_source(bevy_asset::io::AssetSourceId::from("komotool_config"))
+    }
+
+    #[test]
+    fn paths_below_the_root_become_komotool_config_paths() -> std::io::Result<()> {
+        let root = tempdir()?;
+        let script = root.path().join("scripts").join("group").join("init.lua");
+        assert_eq!(
+            create_komotool_asset_path(&script, root.path()),
+            Some(komotool_config_path("scripts/group/init.lua"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn the_root_and_paths_outside_it_have_no_asset_path() -> std::io::Result<()> {
+        let (root, other) = (tempdir()?, tempdir()?);
+        assert_eq!(create_komotool_asset_path(root.path(), root.path()), None);
+        assert_eq!(
+            create_komotool_asset_path(&other.path().join("init.lua"), root.path()),
+            None
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn canonical_paths_match_a_symlinked_root() -> std::io::Result<()> {
+        let dir = tempdir()?;
+        let real_root = dir.path().join("real");
+        fs::create_dir(&real_root)?;
+        let linked_root = dir.path().join("linked");
+        std::os::unix::fs::symlink(&real_root, &linked_root)?;
+
+        let script = real_root.canonicalize()?.join("scripts").join("init.lua");
+        assert_eq!(
+            create_komotool_asset_path(&script, &linked_root),
+            Some(komotool_config_path("scripts/init.lua"))
+        );
+        Ok(())
+    }
 }
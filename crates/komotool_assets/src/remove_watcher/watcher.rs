@@ -1,33 +1,101 @@
-use super::super::get_or_create_komotool_config_path;
-use crate::create_komotool_asset_path;
-use bevy_asset::{AssetEvent, AssetServer};
+use crate::config_dir::KomotoolConfigPath;
+use crate::script_config::ScriptConfig;
+use crate::{create_komotool_asset_path, is_profiles_file};
+use bevy_asset::{AssetEvent, AssetPath, AssetServer, Assets, UntypedHandle};
 use bevy_ecs::event::EventWriter;
-use bevy_ecs::system::{Commands, Res, Resource};
+use bevy_ecs::system::{Commands, Res, ResMut, Resource};
 use bevy_mod_scripting::core::asset::{Language, ScriptAsset, ScriptAssetSettings};
 use crossbeam_channel::Receiver;
+use notify::event::{ModifyKind, RenameMode};
 use notify::{Config, Event as NotifyEvent, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A filesystem change the asset server doesn't report on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileWatchEvent {
+    /// A file or folder was deleted or renamed away from this path
+    Removed(PathBuf),
+    /// A file or folder was renamed or moved to this path
+    Added(PathBuf),
+}
 
 // Resource to hold the file watcher and event receiver
 #[derive(Resource)]
 pub struct FileWatcher {
-    rx: Receiver<String>,
+    rx: Receiver<FileWatchEvent>,
     _watcher: RecommendedWatcher, // Keeps the watcher alive
 }
 
-pub fn setup_file_watcher(mut commands: Commands) {
+/// Handles for scripts and script configs loaded because they were renamed or moved into place.
+///
+/// The asset server only loads files it discovers through a folder load, so these
+/// handles keep the renamed files alive until they are removed again.
+#[derive(Resource, Default)]
+pub struct WatchedScriptHandles(pub HashMap<AssetPath<'static>, UntypedHandle>);
+
+/// Splits a notify event into removals and additions.
+///
+/// Renames are reported as a removal of the old path plus an addition of the new one.
+/// When the backend can't tell which side of a rename a path is on, the path's
+/// existence decides.
+pub fn translate_notify_event(event: NotifyEvent) -> Vec<FileWatchEvent> {
+    match event.kind {
+        EventKind::Remove(_) => event
+            .paths
+            .into_iter()
+            .map(FileWatchEvent::Removed)
+            .collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            let mut paths = event.paths.into_iter();
+            let mut translated = Vec::new();
+            if let Some(from) = paths.next() {
+                translated.push(FileWatchEvent::Removed(from));
+            }
+            if let Some(to) = paths.next() {
+                translated.push(FileWatchEvent::Added(to));
+            }
+            translated
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => event
+            .paths
+            .into_iter()
+            .map(FileWatchEvent::Removed)
+            .collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+            event.paths.into_iter().map(FileWatchEvent::Added).collect()
+        }
+        EventKind::Modify(ModifyKind::Name(_)) => event
+            .paths
+            .into_iter()
+            .map(|path| {
+                if path.exists() {
+                    FileWatchEvent::Added(path)
+                } else {
+                    FileWatchEvent::Removed(path)
+                }
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn setup_file_watcher(mut commands: Commands, config_path: Option<Res<KomotoolConfigPath>>) {
+    let Some(config_path) = config_path else {
+        println!("No Komotool config directory, file watcher disabled");
+        return;
+    };
+
     // Use crossbeam channels as requested
     let (tx, rx) = crossbeam_channel::unbounded();
 
-    // Configure the watcher to watch for removal events
+    // Configure the watcher to watch for removal and rename events
     let mut watcher = match RecommendedWatcher::new(
         move |res: Result<NotifyEvent, notify::Error>| {
             if let Ok(event) = res {
-                if let EventKind::Remove(_) = event.kind {
-                    for path in event.paths {
-                        if let Some(path_str) = path.to_str() {
-                            let _ = tx.send(path_str.to_string());
-                        }
-                    }
+                for watch_event in translate_notify_event(event) {
+                    let _ = tx.send(watch_event);
                 }
             }
         },
@@ -41,14 +109,9 @@ pub fn setup_file_watcher(mut commands: Commands) {
     };
 
     // Watch the target directory RECURSIVELY as requested
-    if let Ok(komotool_path) = get_or_create_komotool_config_path() {
-        match watcher.watch(&komotool_path, RecursiveMode::Recursive) {
-            Ok(_) => (),
-            Err(e) => {
-                println!("Failed to watch directory: {}", e);
-                return;
-            }
-        }
+    if let Err(e) = watcher.watch(&config_path.0, RecursiveMode::Recursive) {
+        println!("Failed to watch directory: {}", e);
+        return;
     }
 
     // Insert the resource with the watcher and receiver
@@ -58,33 +121,202 @@ pub fn setup_file_watcher(mut commands: Commands) {
     });
 }
 
+/// Collects every file below `path`, or `path` itself if it is a file.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        let Ok(entries) = fs::read_dir(path) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            collect_files(&entry.path(), files);
+        }
+    } else if path.is_file() {
+        files.push(path.to_path_buf());
+    }
+}
+
+fn is_script_language(settings: &ScriptAssetSettings, asset_path: &AssetPath) -> bool {
+    matches!(
+        settings.select_script_language(asset_path),
+        Language::Lua | Language::Rhai
+    )
+}
+
+fn is_script_config(asset_path: &AssetPath) -> bool {
+    // The last extension, so `foo.schema.json` counts too
+    asset_path
+        .path()
+        .extension()
+        .is_some_and(|extension| extension == "toml" || extension == "json")
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn check_file_events(
     watcher: Option<Res<FileWatcher>>,
+    config_path: Option<Res<KomotoolConfigPath>>,
     mut event: EventWriter<AssetEvent<ScriptAsset>>,
+    mut config_event: EventWriter<AssetEvent<ScriptConfig>>,
     asset_server: Res<AssetServer>,
+    assets: Res<Assets<ScriptAsset>>,
+    configs: Res<Assets<ScriptConfig>>,
     settings: Res<ScriptAssetSettings>,
+    mut handles: ResMut<WatchedScriptHandles>,
 ) {
-    if let Some(watcher) = watcher {
-        // Process all available events without blocking
-        while let Ok(path) = watcher.rx.try_recv() {
-            let asset_path = create_komotool_asset_path(&path);
-
-            if let Some(id) = asset_server.get_path_id(&asset_path) {
-                match settings.select_script_language(&asset_path) {
-                    Language::Lua | Language::Rhai => {
-                        let typed_id = id.try_typed::<ScriptAsset>();
-                        match typed_id {
-                            Ok(typed) => {
-                                event.send(AssetEvent::Removed { id: typed });
-                            }
-                            Err(e) => {
-                                println!("Failed to get typed id: {}", e);
-                            }
-                        }
+    let (Some(watcher), Some(config_path)) = (watcher, config_path) else {
+        return;
+    };
+
+    // Process all available events without blocking
+    while let Ok(watch_event) = watcher.rx.try_recv() {
+        match watch_event {
+            FileWatchEvent::Removed(path) => {
+                let Some(asset_path) = create_komotool_asset_path(&path, &config_path.0) else {
+                    continue;
+                };
+
+                // The path may be a single script or a folder of scripts
+                for (id, script) in assets.iter() {
+                    if script.asset_path.source() != asset_path.source()
+                        || !script.asset_path.path().starts_with(asset_path.path())
+                    {
+                        continue;
+                    }
+                    if !is_script_language(&settings, &script.asset_path) {
+                        continue;
                     }
-                    _ => (),
+                    handles.0.remove(&script.asset_path);
+                    event.send(AssetEvent::Removed { id });
+                }
+
+                for id in configs.ids() {
+                    let Some(script_config_path) = asset_server.get_path(id) else {
+                        continue;
+                    };
+                    if script_config_path.source() != asset_path.source()
+                        || !script_config_path.path().starts_with(asset_path.path())
+                    {
+                        continue;
+                    }
+                    handles.0.remove(&script_config_path.into_owned());
+                    config_event.send(AssetEvent::Removed { id });
                 }
             }
+            FileWatchEvent::Added(path) => {
+                let mut files = Vec::new();
+                collect_files(&path, &mut files);
+
+                for file in files {
+                    let Some(asset_path) = create_komotool_asset_path(&file, &config_path.0) else {
+                        continue;
+                    };
+                    if is_profiles_file(asset_path.path()) {
+                        println!("Loading script profiles: {}", asset_path);
+                        let handle = asset_server.load::<ScriptConfig>(asset_path.clone());
+                        handles.0.insert(asset_path, handle.untyped());
+                        continue;
+                    }
+                    if !asset_path.path().starts_with("scripts") {
+                        continue;
+                    }
+                    let handle = if is_script_language(&settings, &asset_path) {
+                        println!("Loading moved script: {}", asset_path);
+                        asset_server
+                            .load::<ScriptAsset>(asset_path.clone())
+                            .untyped()
+                    } else if is_script_config(&asset_path) {
+                        println!("Loading moved script config: {}", asset_path);
+                        asset_server
+                            .load::<ScriptConfig>(asset_path.clone())
+                            .untyped()
+                    } else {
+                        continue;
+                    };
+                    handles.0.insert(asset_path, handle);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, RemoveKind};
+    use tempfile::tempdir;
+
+    fn rename(mode: RenameMode, paths: &[&Path]) -> NotifyEvent {
+        paths.iter().fold(
+            NotifyEvent::new(EventKind::Modify(ModifyKind::Name(mode))),
+            |event, path| event.add_path(path.to_path_buf()),
+        )
+    }
+
+    #[test]
+    fn removals_remove_every_path() {
+        let event = NotifyEvent::new(EventKind::Remove(RemoveKind::Any))
+            .add_path("scripts/a.lua".into())
+            .add_path("scripts/b".into());
+        assert_eq!(
+            translate_notify_event(event),
+            vec![
+                FileWatchEvent::Removed("scripts/a.lua".into()),
+                FileWatchEvent::Removed("scripts/b".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn both_sided_renames_remove_the_old_path_and_add_the_new_one() {
+        let event = rename(
+            RenameMode::Both,
+            &[Path::new("scripts/old.lua"), Path::new("scripts/new.lua")],
+        );
+        assert_eq!(
+            translate_notify_event(event),
+            vec![
+                FileWatchEvent::Removed("scripts/old.lua".into()),
+                FileWatchEvent::Added("scripts/new.lua".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn one_sided_renames_follow_their_side() {
+        let from = rename(RenameMode::From, &[Path::new("scripts/old.lua")]);
+        assert_eq!(
+            translate_notify_event(from),
+            vec![FileWatchEvent::Removed("scripts/old.lua".into())]
+        );
+        let to = rename(RenameMode::To, &[Path::new("scripts/new.lua")]);
+        assert_eq!(
+            translate_notify_event(to),
+            vec![FileWatchEvent::Added("scripts/new.lua".into())]
+        );
+    }
+
+    #[test]
+    fn unknown_renames_are_decided_by_existence() -> std::io::Result<()> {
+        let dir = tempdir()?;
+        let existing = dir.path().join("new.lua");
+        fs::write(&existing, "")?;
+        let missing = dir.path().join("old.lua");
+
+        for mode in [RenameMode::Any, RenameMode::Other] {
+            assert_eq!(
+                translate_notify_event(rename(mode, &[&missing, &existing])),
+                vec![
+                    FileWatchEvent::Removed(missing.clone()),
+                    FileWatchEvent::Added(existing.clone()),
+                ]
+            );
         }
+        Ok(())
+    }
+
+    #[test]
+    fn other_events_are_ignored() {
+        let event =
+            NotifyEvent::new(EventKind::Create(CreateKind::File)).add_path("scripts/a.lua".into());
+        assert_eq!(translate_notify_event(event), Vec::new());
     }
 }
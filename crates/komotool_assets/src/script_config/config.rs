@@ -0,0 +1,452 @@
+use crate::is_profiles_file;
+use crate::window_rules::is_rule_file;
+use bevy_asset::io::Reader;
+use bevy_asset::{Asset, AssetEvent, AssetLoader, AssetServer, Assets, LoadContext};
+use bevy_ecs::event::{EventReader, EventWriter};
+use bevy_ecs::system::{Res, ResMut, Resource};
+use bevy_mod_scripting::core::asset::ScriptAsset;
+use bevy_mod_scripting::core::bindings::{ScriptValue, ThreadWorldContainer, WorldContainer};
+use bevy_mod_scripting::core::event::{Recipients, ScriptCallbackEvent};
+use bevy_mod_scripting::core::script::ScriptId;
+use bevy_reflect::TypePath;
+use komotool_utils::callbacklabels::OnConfigChanged;
+use komotool_utils::diagnostics::{ScriptDiagnostic, ScriptDiagnostics};
+use komotool_utils::handler::SyntaxError;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A `foo.toml` or `foo.json` next to a script `foo.lua`/`foo.rhai`, or its
+/// `foo.schema.toml`/`foo.schema.json`.
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct ScriptConfig {
+    /// Parsed config, `None` if the file failed to parse
+    pub value: Option<Value>,
+    pub error: Option<SyntaxError>,
+}
+
+impl ScriptConfig {
+    fn parsed(value: Value) -> Self {
+        if value.is_object() {
+            Self {
+                value: Some(value),
+                error: None,
+            }
+        } else {
+            Self::failed(SyntaxError {
+                line: None,
+                column: None,
+                message: "Script config must be a table at the top level".to_string(),
+            })
+        }
+    }
+
+    fn failed(error: SyntaxError) -> Self {
+        Self {
+            value: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Loads script configs and their schemas below `scripts/`, rule files and `profiles.json`.
+/// Parse errors are kept in the asset so they can be reported as diagnostics.
+#[derive(Default)]
+pub struct ScriptConfigLoader;
+
+impl AssetLoader for ScriptConfigLoader {
+    type Asset = ScriptConfig;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        // Only claim the komotool files read through this asset, not any .json/.toml a
+        // source happens to load
+        let path = load_context.path();
+        if !(path.starts_with("scripts") || is_rule_file(path) || is_profiles_file(path)) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "{} isn't a script config, rule or profiles file",
+                    path.display()
+                ),
+            ));
+        }
+
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let is_toml = load_context
+            .path()
+            .extension()
+            .is_some_and(|extension| extension == "toml");
+        Ok(if is_toml {
+            parse_toml_config(&bytes)
+        } else {
+            parse_json_config(&bytes)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["toml", "json"]
+    }
+}
+
+pub fn parse_json_config(bytes: &[u8]) -> ScriptConfig {
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(value) => ScriptConfig::parsed(value),
+        Err(e) => ScriptConfig::failed(SyntaxError {
+            line: Some(e.line()),
+            column: Some(e.column()),
+            message: e.to_string(),
+        }),
+    }
+}
+
+pub fn parse_toml_config(bytes: &[u8]) -> ScriptConfig {
+    let content = match std::str::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(e) => {
+            return ScriptConfig::failed(SyntaxError {
+                line: None,
+                column: None,
+                message: format!("Config is not valid UTF-8: {}", e),
+            });
+        }
+    };
+    match toml::from_str::<Value>(content) {
+        Ok(value) => ScriptConfig::parsed(value),
+        Err(e) => {
+            let (line, column) = match e.span() {
+                Some(span) => {
+                    let (line, column) = line_and_column(content, span.start);
+                    (Some(line), Some(column))
+                }
+                None => (None, None),
+            };
+            ScriptConfig::failed(SyntaxError {
+                line,
+                column,
+                message: e.message().to_string(),
+            })
+        }
+    }
+}
+
+/// 1-based line and column of a byte offset.
+fn line_and_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before.len(), |newline| before.len() - newline - 1)
+        + 1;
+    (line, column)
+}
+
+/// Script configs by script path without extension, e.g. `scripts/borders/colours`.
+///
+/// Context initializers read it through the world bevy_mod_scripting hands them while a
+/// script loads, see [`script_config`].
+#[derive(Resource, Default, Debug)]
+pub struct ScriptConfigs {
+    configs: HashMap<String, Value>,
+    /// Schemas from `foo.schema.json`/`foo.schema.toml`, by the key of the config they describe
+    schemas: HashMap<String, Value>,
+}
+
+impl ScriptConfigs {
+    /// The config of the given script, if it has a sibling config file that parsed and matches
+    /// its schema.
+    pub fn get(&self, script_id: &str) -> Option<&Value> {
+        self.configs.get(&script_config_key(Path::new(script_id)))
+    }
+
+    fn set(&mut self, key: String, value: Option<Value>) {
+        match value {
+            Some(value) => self.configs.insert(key, value),
+            None => self.configs.remove(&key),
+        };
+    }
+}
+
+/// Key shared by a script and its config: the path without its extension.
+pub fn script_config_key(path: &Path) -> String {
+    path.with_extension("")
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The config key a schema file describes: `scripts/foo.schema.json` describes `scripts/foo`.
+pub fn schema_config_key(path: &Path) -> Option<String> {
+    script_config_key(path)
+        .strip_suffix(".schema")
+        .map(str::to_string)
+}
+
+/// The config of the given script while bevy_mod_scripting loads it.
+pub fn script_config(script_id: &str) -> Option<Value> {
+    let world = ThreadWorldContainer.try_get_world().ok()?;
+    world
+        .with_resource(|configs: &ScriptConfigs| configs.get(script_id).cloned())
+        .ok()
+        .flatten()
+}
+
+/// Checks a config against its schema, a table of key to type name: `string`, `integer`,
+/// `number`, `boolean`, `list` or `table`, with a trailing `?` for optional keys.
+///
+/// Keys the schema doesn't mention are rejected, so typos don't go unnoticed.
+pub fn validate_script_config(config: &Value, schema: &Value) -> Result<(), String> {
+    let (Some(config), Some(schema)) = (config.as_object(), schema.as_object()) else {
+        return Err("Config schema must be a table at the top level".to_string());
+    };
+
+    let mut errors = Vec::new();
+    for (key, expected) in schema {
+        let Some(expected) = expected.as_str() else {
+            errors.push(format!("schema type of `{}` must be a string", key));
+            continue;
+        };
+        let (expected, optional) = match expected.strip_suffix('?') {
+            Some(expected) => (expected, true),
+            None => (expected, false),
+        };
+        let matches: fn(&Value) -> bool = match expected {
+            "string" => Value::is_string,
+            "integer" => |value: &Value| value.is_i64() || value.is_u64(),
+            "number" => Value::is_number,
+            "boolean" => Value::is_boolean,
+            "list" => Value::is_array,
+            "table" => Value::is_object,
+            _ => {
+                errors.push(format!("unknown schema type `{}` for `{}`", expected, key));
+                continue;
+            }
+        };
+        match config.get(key) {
+            Some(value) if !matches(value) => {
+                errors.push(format!("`{}` must be of type `{}`", key, expected));
+            }
+            None if !optional => errors.push(format!("`{}` is missing", key)),
+            _ => {}
+        }
+    }
+    for key in config.keys() {
+        if !schema.contains_key(key) {
+            errors.push(format!("`{}` isn't in the schema", key));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join(", "))
+    }
+}
+
+pub fn json_to_script_value(value: &Value) -> ScriptValue {
+    match value {
+        Value::Null => ScriptValue::Unit,
+        Value::Bool(b) => ScriptValue::Bool(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => ScriptValue::Integer(i),
+            None => ScriptValue::Float(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => ScriptValue::String(s.clone().into()),
+        Value::Array(items) => ScriptValue::List(items.iter().map(json_to_script_value).collect()),
+        Value::Object(map) => ScriptValue::Map(
+            map.iter()
+                .map(|(key, value)| (key.clone(), json_to_script_value(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Keeps [`ScriptConfigs`] in sync with config assets and calls `on_config_changed` when a
+/// config is added, edited or removed.
+///
+/// A config that fails to parse or doesn't match its schema is reported to
+/// [`ScriptDiagnostics`] and the previous config stays active. A changed schema re-checks
+/// its config.
+pub fn handle_script_config_events(
+    mut events: EventReader<AssetEvent<ScriptConfig>>,
+    assets: Res<Assets<ScriptConfig>>,
+    scripts: Res<Assets<ScriptAsset>>,
+    asset_server: Res<AssetServer>,
+    mut configs: ResMut<ScriptConfigs>,
+    mut diagnostics: ResMut<ScriptDiagnostics>,
+    mut writer: EventWriter<ScriptCallbackEvent>,
+) {
+    let mut updated = Vec::new();
+    for event in events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id } | AssetEvent::Removed { id }) =
+            event
+        else {
+            continue;
+        };
+        let Some(path) = asset_server.get_path(*id) else {
+            continue;
+        };
+        // Rule and profile files are loaded the same way but aren't tied to a script
+        if is_rule_file(path.path()) || is_profiles_file(path.path()) {
+            continue;
+        }
+        let Some(key) = schema_config_key(path.path()) else {
+            updated.push((*id, matches!(event, AssetEvent::Removed { .. })));
+            continue;
+        };
+
+        let file = path.path().to_string_lossy().to_string();
+        if let AssetEvent::Removed { .. } = event {
+            diagnostics.clear(&file);
+            configs.schemas.remove(&key);
+        } else {
+            let Some(schema) = assets.get(*id) else {
+                continue;
+            };
+            if let Some(error) = &schema.error {
+                let diagnostic = error.clone().into_diagnostic(file);
+                println!("Script config schema failed to parse: {}", diagnostic);
+                diagnostics.report(diagnostic);
+                continue;
+            }
+            diagnostics.clear(&file);
+            if let Some(value) = &schema.value {
+                configs.schemas.insert(key.clone(), value.clone());
+            }
+        }
+        // Re-check the config the schema describes
+        let config = assets.ids().find(|config| {
+            asset_server.get_path(*config).is_some_and(|path| {
+                schema_config_key(path.path()).is_none() && script_config_key(path.path()) == key
+            })
+        });
+        if let Some(config) = config {
+            updated.push((config, false));
+        }
+    }
+
+    for (id, removed) in updated {
+        let Some(path) = asset_server.get_path(id) else {
+            continue;
+        };
+        let file = path.path().to_string_lossy().to_string();
+        let key = script_config_key(path.path());
+
+        let value = if removed {
+            diagnostics.clear(&file);
+            configs.set(key.clone(), None);
+            None
+        } else {
+            let Some(config) = assets.get(id) else {
+                continue;
+            };
+            if let Some(error) = &config.error {
+                let diagnostic = error.clone().into_diagnostic(file);
+                println!("Script config failed validation: {}", diagnostic);
+                diagnostics.report(diagnostic);
+                continue;
+            }
+            let Some(value) = config.value.clone() else {
+                continue;
+            };
+            if let Some(schema) = configs.schemas.get(&key) {
+                if let Err(message) = validate_script_config(&value, schema) {
+                    let diagnostic = ScriptDiagnostic {
+                        file,
+                        line: None,
+                        column: None,
+                        message,
+                    };
+                    println!("Script config failed validation: {}", diagnostic);
+                    diagnostics.report(diagnostic);
+                    continue;
+                }
+            }
+            diagnostics.clear(&file);
+            if configs.configs.get(&key) == Some(&value) {
+                continue;
+            }
+            configs.set(key.clone(), Some(value.clone()));
+            Some(value)
+        };
+
+        let arg = value
+            .as_ref()
+            .map_or(ScriptValue::Unit, json_to_script_value);
+        for (_, script) in scripts.iter() {
+            if script_config_key(script.asset_path.path()) != key {
+                continue;
+            }
+            let script_id = ScriptId::from(script.asset_path.path().to_string_lossy().to_string());
+            println!("Config changed for script: {}", script_id);
+            writer.send(ScriptCallbackEvent::new(
+                OnConfigChanged,
+                vec![arg.clone()],
+                Recipients::Script(script_id),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn schemas_describe_their_sibling_config() {
+        assert_eq!(
+            schema_config_key(Path::new("scripts/borders/colours.schema.toml")),
+            Some("scripts/borders/colours".to_string())
+        );
+        assert_eq!(
+            schema_config_key(Path::new("scripts/borders/colours.toml")),
+            None
+        );
+    }
+
+    #[test]
+    fn configs_matching_their_schema_pass() {
+        let schema = json!({ "padding": "integer", "apps": "list", "colour": "string?" });
+        assert_eq!(
+            validate_script_config(&json!({ "padding": 4, "apps": [] }), &schema),
+            Ok(())
+        );
+        assert_eq!(
+            validate_script_config(
+                &json!({ "padding": 4, "apps": ["firefox"], "colour": "red" }),
+                &schema
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn schema_errors_name_every_key() {
+        let schema = json!({ "padding": "integer", "apps": "list", "colour": "string?" });
+        assert_eq!(
+            validate_script_config(&json!({ "padding": 4.5, "colour": 1, "gap": 2 }), &schema),
+            Err("`apps` is missing, `colour` must be of type `string`, \
+                 `padding` must be of type `integer`, `gap` isn't in the schema"
+                .to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_schemas_are_reported() {
+        assert!(validate_script_config(&json!({}), &json!(["padding"])).is_err());
+        assert_eq!(
+            validate_script_config(&json!({ "padding": 4 }), &json!({ "padding": "int" })),
+            Err("unknown schema type `int` for `padding`".to_string())
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use bevy_asset::{AssetEvent, AssetId, Assets};
+use bevy_ecs::event::EventReader;
+use bevy_ecs::system::{Res, ResMut, Resource};
+use bevy_mod_scripting::core::asset::{Language, ScriptAsset, ScriptMetadata, ScriptMetadataStore};
+use bevy_mod_scripting::core::script::ScriptId;
+use bevy_mod_scripting::lua::LuaScriptingPlugin;
+use bevy_mod_scripting::rhai::RhaiScriptingPlugin;
+use komotool_utils::diagnostics::ScriptDiagnostics;
+use komotool_utils::handler::{ScriptFunctionChecker, SyntaxError};
+
+/// Scripts whose new content failed validation this frame, held back from bevy_mod_scripting
+/// so the version that is already loaded keeps running.
+#[derive(Resource, Default)]
+pub struct HeldBackScripts {
+    /// Metadata taken out of [`ScriptMetadataStore`] while the asset events are dispatched
+    held_back: Vec<(AssetId<ScriptAsset>, ScriptMetadata)>,
+}
+
+impl HeldBackScripts {
+    /// Whether the script's new content failed validation this frame.
+    pub fn is_held_back(&self, id: AssetId<ScriptAsset>) -> bool {
+        self.held_back.iter().any(|(held_back, _)| *held_back == id)
+    }
+}
+
+/// Compiles the script for its language without running it.
+pub fn validate_script(language: &Language, content: &[u8]) -> Option<Result<(), SyntaxError>> {
+    match language {
+        Language::Lua => Some(LuaScriptingPlugin::check_syntax(content)),
+        Language::Rhai => Some(RhaiScriptingPlugin::check_syntax(content)),
+        _ => None,
+    }
+}
+
+/// Validates new and modified scripts before bevy_mod_scripting dispatches their asset events.
+///
+/// bevy_mod_scripting only loads scripts it has metadata for, so a script that doesn't compile
+/// has its metadata taken out of the store until the events are dispatched. The broken
+/// version is never loaded and its init never runs; the failure goes to [`ScriptDiagnostics`],
+/// and a script that compiles clears its entry there. This is the only place scripts are
+/// validated, later systems ask [`HeldBackScripts::is_held_back`].
+/// Runs before `ScriptingSystemSet::ScriptAssetDispatch`.
+pub fn hold_back_invalid_scripts(
+    mut events: EventReader<AssetEvent<ScriptAsset>>,
+    assets: Res<Assets<ScriptAsset>>,
+    mut metadata_store: ResMut<ScriptMetadataStore>,
+    mut diagnostics: ResMut<ScriptDiagnostics>,
+    mut held_back: ResMut<HeldBackScripts>,
+) {
+    for event in events.read() {
+        let (AssetEvent::Added { id }
+        | AssetEvent::LoadedWithDependencies { id }
+        | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        let (Some(script), Some(metadata)) = (assets.get(*id), metadata_store.get(*id)) else {
+            continue;
+        };
+        let script_id = ScriptId::from(script.asset_path.path().to_string_lossy().to_string());
+        let e = match validate_script(&metadata.language, &script.content) {
+            Some(Ok(())) => {
+                diagnostics.clear(&script_id);
+                continue;
+            }
+            Some(Err(e)) => e,
+            None => continue,
+        };
+        let diagnostic = e.into_diagnostic(script_id.to_string());
+        println!("Script failed validation: {}", diagnostic);
+        println!("Keeping last good version of script: {}", script_id);
+        diagnostics.report(diagnostic);
+        if let Some(metadata) = metadata_store.remove(*id) {
+            held_back.held_back.push((*id, metadata));
+        }
+    }
+}
+
+/// Puts back the metadata [`hold_back_invalid_scripts`] took out, so the next edit is picked up.
+///
+/// Runs after `ScriptingSystemSet::ScriptCommandDispatch`.
+pub fn release_held_back_scripts(
+    mut metadata_store: ResMut<ScriptMetadataStore>,
+    mut held_back: ResMut<HeldBackScripts>,
+) {
+    for (id, metadata) in held_back.held_back.drain(..) {
+        metadata_store.insert(id, metadata);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod held_back;
+
+pub use held_back::*;
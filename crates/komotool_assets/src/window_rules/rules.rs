@@ -0,0 +1,118 @@
+use crate::config_dir::KomotoolConfigPath;
+use crate::script_config::ScriptConfig;
+use bevy_asset::{AssetEvent, AssetServer, Assets, Handle, LoadedFolder};
+use bevy_ecs::event::EventReader;
+use bevy_ecs::system::{Commands, Res, ResMut, Resource};
+use bevy_reflect::Reflect;
+use komotool_ecs::rules::{WindowRules, parse_rule_file};
+use komotool_utils::diagnostics::{ScriptDiagnostic, ScriptDiagnostics};
+use std::fs;
+use std::path::Path;
+
+/// Folder below the komotool config directory holding window rule files.
+///
+/// Rule files are `.toml` or `.json` with a top-level `rules` list:
+///
+/// ```toml
+/// [[rules]]
+/// name = "Browsers on web"
+/// exe = "(?i)^firefox\\.exe$"
+/// actions = [{ move_to_workspace = "web" }, "float"]
+/// ```
+pub const RULES_FOLDER: &str = "rules";
+
+/// Whether a config asset path is a rule file rather than a script config.
+pub fn is_rule_file(path: &Path) -> bool {
+    path.starts_with(RULES_FOLDER)
+}
+
+/// Keeps the rules folder loaded so the file watcher hot-reloads it.
+#[derive(Resource, Reflect)]
+pub struct WindowRulesFolder(pub Handle<LoadedFolder>);
+
+pub fn load_window_rules(
+    asset_server: Res<AssetServer>,
+    config_path: Option<Res<KomotoolConfigPath>>,
+    mut commands: Commands,
+) {
+    let Some(config_path) = config_path else {
+        return;
+    };
+    let path = config_path.0.join(RULES_FOLDER);
+    if !path.exists() {
+        match fs::create_dir_all(&path) {
+            Ok(_) => println!("Created directory: {}", path.display()),
+            Err(e) => {
+                println!("Failed to create directory: {}", e);
+                return;
+            }
+        };
+    }
+    let source = bevy_asset::io::AssetSourceId::from("komotool_config");
+    let asset_path = bevy_asset::AssetPath::from_path(Path::new(RULES_FOLDER)).with_source(source);
+    let handle = asset_server.load_folder(asset_path);
+    commands.insert_resource(WindowRulesFolder(handle));
+}
+
+/// Compiles rule files into [`WindowRules`] whenever one is added, edited or removed.
+///
+/// A file that fails to parse or has an invalid pattern is reported to [`ScriptDiagnostics`]
+/// and its previous rules stay active.
+pub fn handle_window_rule_events(
+    mut events: EventReader<AssetEvent<ScriptConfig>>,
+    configs: Res<Assets<ScriptConfig>>,
+    asset_server: Res<AssetServer>,
+    mut rules: ResMut<WindowRules>,
+    mut diagnostics: ResMut<ScriptDiagnostics>,
+) {
+    for event in events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id } | AssetEvent::Removed { id }) =
+            event
+        else {
+            continue;
+        };
+        let Some(path) = asset_server.get_path(*id) else {
+            continue;
+        };
+        if !is_rule_file(path.path()) {
+            continue;
+        }
+        let file = path.path().to_string_lossy().to_string();
+
+        if let AssetEvent::Removed { .. } = event {
+            diagnostics.clear(&file);
+            rules.remove_file(&file);
+            println!("Removed window rules from {}", file);
+            continue;
+        }
+        let Some(config) = configs.get(*id) else {
+            continue;
+        };
+        if let Some(error) = &config.error {
+            let diagnostic = error.clone().into_diagnostic(file);
+            println!("Window rules failed to parse: {}", diagnostic);
+            diagnostics.report(diagnostic);
+            continue;
+        }
+        let Some(value) = &config.value else {
+            continue;
+        };
+        match parse_rule_file(&file, value) {
+            Ok(file_rules) => {
+                diagnostics.clear(&file);
+                println!("Loaded {} window rules from {}", file_rules.len(), file);
+                rules.set_file(file, file_rules);
+            }
+            Err(message) => {
+                let diagnostic = ScriptDiagnostic {
+                    file,
+                    line: None,
+                    column: None,
+                    message,
+                };
+                println!("Window rules failed validation: {}", diagnostic);
+                diagnostics.report(diagnostic);
+            }
+        }
+    }
+}
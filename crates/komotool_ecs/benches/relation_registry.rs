@@ -0,0 +1,125 @@
+use bevy_ecs::entity::Entity;
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use komotool_ecs::relations::{EntityRecord, RelationRegistry};
+use std::hint::black_box;
+
+const MONITORS: usize = 8;
+const WORKSPACES_PER_MONITOR: usize = 4;
+const CONTAINERS_PER_WORKSPACE: usize = 8;
+const WINDOWS: usize = 500;
+
+/// A synthetic hierarchy of 8 monitors with 500 windows spread across their workspaces.
+///
+/// Every fifth window floats, the rest are tiled round-robin into containers.
+fn synthetic_records() -> Vec<EntityRecord> {
+    let mut next_entity = 0u32;
+    let mut entity = || {
+        next_entity += 1;
+        Entity::from_raw(next_entity)
+    };
+    let mut records = Vec::new();
+    let mut window_counts =
+        vec![0usize; MONITORS * WORKSPACES_PER_MONITOR * (CONTAINERS_PER_WORKSPACE + 1)];
+
+    for m in 1..=MONITORS {
+        records.push(EntityRecord::new(entity(), m, 0, 0, 0));
+        for w in 1..=WORKSPACES_PER_MONITOR {
+            records.push(EntityRecord::new(entity(), m, w, 0, 0));
+            for c in 1..=CONTAINERS_PER_WORKSPACE {
+                records.push(EntityRecord::new(entity(), m, w, c, 0));
+            }
+        }
+    }
+
+    for n in 0..WINDOWS {
+        let m = n % MONITORS + 1;
+        let w = (n / MONITORS) % WORKSPACES_PER_MONITOR + 1;
+        let c = if n % 5 == 0 {
+            0
+        } else {
+            (n / (MONITORS * WORKSPACES_PER_MONITOR)) % CONTAINERS_PER_WORKSPACE + 1
+        };
+        let slot =
+            ((m - 1) * WORKSPACES_PER_MONITOR + (w - 1)) * (CONTAINERS_PER_WORKSPACE + 1) + c;
+        window_counts[slot] += 1;
+        records.push(EntityRecord::new(entity(), m, w, c, window_counts[slot]));
+    }
+
+    // Import walks komorebi's state, which is not the registry's sort order
+    records.reverse();
+    records
+}
+
+fn build(c: &mut Criterion) {
+    let records = synthetic_records();
+
+    c.bench_function("registry_insert_each", |b| {
+        b.iter_batched(
+            || records.clone(),
+            |records| {
+                let mut registry = RelationRegistry::default();
+                for record in records {
+                    registry.insert(
+                        record.entity,
+                        record.monitor,
+                        record.workspace,
+                        record.container,
+                        record.window,
+                    );
+                }
+                registry
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    c.bench_function("registry_rebuild", |b| {
+        b.iter_batched(
+            || records.clone(),
+            |records| {
+                let mut registry = RelationRegistry::default();
+                registry.rebuild(records);
+                registry
+            },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+fn lookups(c: &mut Criterion) {
+    let records = synthetic_records();
+    let paths: Vec<_> = records.iter().map(EntityRecord::key).collect();
+    let mut registry = RelationRegistry::default();
+    registry.rebuild(records);
+
+    c.bench_function("registry_entity_at", |b| {
+        b.iter(|| {
+            for path in &paths {
+                black_box(registry.entity_at(*path));
+            }
+        });
+    });
+
+    c.bench_function("registry_containers_of", |b| {
+        b.iter(|| {
+            for m in 1..=MONITORS {
+                for w in 1..=WORKSPACES_PER_MONITOR {
+                    black_box(registry.containers_of(m, w).count());
+                }
+            }
+        });
+    });
+
+    c.bench_function("registry_workspace_subtree", |b| {
+        b.iter(|| {
+            for m in 1..=MONITORS {
+                for w in 1..=WORKSPACES_PER_MONITOR {
+                    black_box(registry.workspace_subtree(m, w).count());
+                }
+            }
+        });
+    });
+}
+
+criterion_group!(benches, build, lookups);
+criterion_main!(benches);
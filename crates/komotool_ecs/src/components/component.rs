@@ -1,20 +1,27 @@
 use bevy_ecs::component::Component;
+use bevy_ecs::reflect::ReflectComponent;
 use bevy_reflect::Reflect;
 
 #[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct MonocleContainer;
 
 #[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct FloatingWindow;
 
 #[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct MaximizedWindow;
 
 #[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Focused;
 
 #[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct LastFocused;
 
 #[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct FocusedGlobal;
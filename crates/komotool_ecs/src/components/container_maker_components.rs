@@ -913,7 +913,7 @@ pub fn insert_container_marker_component(
             commands.entity(entity).insert(Container128);
         }
         n if n > 128 => unsafe {
-            if let Some(component_id) = extended_marker_map.makers.get(&n) {
+            if let Some(component_id) = extended_marker_map.containers.get(&n) {
                 commands
                     .entity(entity)
                     .insert_by_id(*component_id, DynamicComponent::default());
@@ -1315,7 +1315,7 @@ pub fn despawn_container_marker_component(
             commands.entity(entity).remove::<Container128>();
         }
         n if n > 128 => {
-            if let Some(component_id) = extended_marker_map.makers.get(&n) {
+            if let Some(component_id) = extended_marker_map.containers.get(&n) {
                 commands.entity(entity).remove_by_id(*component_id);
             } else {
                 // Optional: Log warning if needed for index {n}
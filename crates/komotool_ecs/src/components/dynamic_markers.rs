@@ -0,0 +1,284 @@
+use crate::resources::{ExtendedMarkerMap, KomorebiState};
+use bevy_ecs::component::ComponentId;
+use bevy_ecs::world::World;
+use bevy_mod_scripting::core::bindings::AppScriptComponentRegistry;
+use bevy_mod_scripting::core::error::InteropError;
+use komorebi_client::State;
+use std::collections::HashMap;
+
+/// The four kinds of numbered marker components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarkerKind {
+    Monitor,
+    Workspace,
+    Container,
+    Window,
+}
+
+impl MarkerKind {
+    pub const ALL: [MarkerKind; 4] = [
+        MarkerKind::Monitor,
+        MarkerKind::Workspace,
+        MarkerKind::Container,
+        MarkerKind::Window,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MarkerKind::Monitor => "Monitor",
+            MarkerKind::Workspace => "Workspace",
+            MarkerKind::Container => "Container",
+            MarkerKind::Window => "Window",
+        }
+    }
+
+    /// The highest index with a generated marker type, e.g. `Monitor32`.
+    pub fn generated_max(self) -> usize {
+        match self {
+            MarkerKind::Monitor => 32,
+            MarkerKind::Workspace => 64,
+            MarkerKind::Container | MarkerKind::Window => 128,
+        }
+    }
+
+    /// The name of the marker for a 1-based index, e.g. `Container57`.
+    pub fn marker_name(self, index: usize) -> String {
+        format!("{}{}", self.name(), index)
+    }
+
+    fn markers(self, map: &ExtendedMarkerMap) -> &HashMap<usize, ComponentId> {
+        match self {
+            MarkerKind::Monitor => &map.monitors,
+            MarkerKind::Workspace => &map.workspaces,
+            MarkerKind::Container => &map.containers,
+            MarkerKind::Window => &map.windows,
+        }
+    }
+
+    fn markers_mut(self, map: &mut ExtendedMarkerMap) -> &mut HashMap<usize, ComponentId> {
+        match self {
+            MarkerKind::Monitor => &mut map.monitors,
+            MarkerKind::Workspace => &mut map.workspaces,
+            MarkerKind::Container => &mut map.containers,
+            MarkerKind::Window => &mut map.windows,
+        }
+    }
+}
+
+/// The highest marker index of each kind a state needs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MarkerCounts {
+    pub monitors: usize,
+    pub workspaces: usize,
+    pub containers: usize,
+    pub windows: usize,
+}
+
+impl MarkerCounts {
+    pub fn get(&self, kind: MarkerKind) -> usize {
+        match kind {
+            MarkerKind::Monitor => self.monitors,
+            MarkerKind::Workspace => self.workspaces,
+            MarkerKind::Container => self.containers,
+            MarkerKind::Window => self.windows,
+        }
+    }
+}
+
+/// Counts the markers `build_relation_registry` will hand out for this state.
+///
+/// Floating windows share the window markers, so they count towards `windows`.
+pub fn required_marker_counts(state: &State) -> MarkerCounts {
+    let mut counts = MarkerCounts {
+        monitors: state.monitors.elements().len(),
+        ..Default::default()
+    };
+    for monitor in state.monitors.elements() {
+        counts.workspaces = counts.workspaces.max(monitor.workspaces().len());
+        for workspace in monitor.workspaces() {
+            counts.containers = counts.containers.max(workspace.containers().len());
+            counts.windows = counts.windows.max(workspace.floating_windows.len());
+            for container in workspace.containers() {
+                counts.windows = counts.windows.max(container.windows().len());
+            }
+        }
+    }
+    counts
+}
+
+/// Registers a runtime marker component for an index beyond the generated range.
+///
+/// The component is named like the generated markers, e.g. `Container57`, and registered in
+/// bevy_mod_scripting's script component registry, so scripts can look it up by name like any
+/// other component. It stores a
+/// [`DynamicComponent`](bevy_mod_scripting::core::bindings::DynamicComponent) and is inserted
+/// with `insert_by_id`.
+pub fn register_dynamic_marker(
+    world: &mut World,
+    kind: MarkerKind,
+    index: usize,
+) -> Result<ComponentId, InteropError> {
+    if let Some(component_id) = world
+        .get_resource::<ExtendedMarkerMap>()
+        .and_then(|map| kind.markers(map).get(&index).copied())
+    {
+        return Ok(component_id);
+    }
+
+    let name = kind.marker_name(index);
+    let registry = world
+        .get_resource_or_insert_with(AppScriptComponentRegistry::default)
+        .clone();
+    let registered = registry
+        .read()
+        .get(&name)
+        .map(|info| info.registration.component_id());
+    let component_id = match registered {
+        Some(component_id) => component_id,
+        None => registry
+            .write()
+            .register_script_component(name, world)?
+            .component_id(),
+    };
+
+    let mut map = world.get_resource_or_insert_with(ExtendedMarkerMap::default);
+    kind.markers_mut(&mut map).insert(index, component_id);
+    Ok(component_id)
+}
+
+/// Registers dynamic markers for every index the current komorebi state needs beyond the
+/// generated ones, so `build_relation_registry` can insert them.
+pub fn register_dynamic_markers(world: &mut World) {
+    let Some(counts) = world
+        .get_resource::<KomorebiState>()
+        .and_then(|state| state.komorebi.as_ref())
+        .map(required_marker_counts)
+    else {
+        return;
+    };
+
+    for kind in MarkerKind::ALL {
+        for index in kind.generated_max() + 1..=counts.get(kind) {
+            if let Err(e) = register_dynamic_marker(world, kind, index) {
+                eprintln!(
+                    "Failed to register marker {}: {}",
+                    kind.marker_name(index),
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{
+        despawn_container_marker_component, insert_container_marker_component,
+    };
+    use crate::test_states::*;
+    use bevy_ecs::change_detection::Mut;
+    use bevy_ecs::reflect::AppTypeRegistry;
+    use bevy_ecs::system::Commands;
+    use bevy_ecs::world::CommandQueue;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<AppScriptComponentRegistry>();
+        world.init_resource::<ExtendedMarkerMap>();
+        world
+    }
+
+    /// One monitor with a workspace of `containers` single-window containers and three
+    /// floating windows.
+    fn wide_state(containers: usize) -> State {
+        let hwnds: Vec<[isize; 1]> = (1..=containers as isize).map(|hwnd| [hwnd]).collect();
+        let hwnds: Vec<&[isize]> = hwnds.iter().map(|hwnd| hwnd.as_slice()).collect();
+        let mut workspace = workspace("a", &hwnds);
+        workspace.floating_windows = (-3..0).map(window).collect();
+        state(vec![monitor("m1", vec![workspace])])
+    }
+
+    #[test]
+    fn counts_cover_containers_past_the_generated_range() {
+        assert_eq!(
+            required_marker_counts(&wide_state(130)),
+            MarkerCounts {
+                monitors: 1,
+                workspaces: 1,
+                containers: 130,
+                windows: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn markers_are_registered_once_by_name() -> Result<(), InteropError> {
+        let mut world = world();
+        let component_id = register_dynamic_marker(&mut world, MarkerKind::Container, 129)?;
+        assert_eq!(
+            register_dynamic_marker(&mut world, MarkerKind::Container, 129)?,
+            component_id
+        );
+
+        let registered = world
+            .resource::<AppScriptComponentRegistry>()
+            .read()
+            .get("Container129")
+            .map(|info| info.registration.component_id());
+        assert_eq!(registered, Some(component_id));
+        assert_eq!(
+            world
+                .components()
+                .get_info(component_id)
+                .map(|info| info.name().to_string()),
+            Some("Container129".to_string())
+        );
+        assert_eq!(
+            world.resource::<ExtendedMarkerMap>().containers.get(&129),
+            Some(&component_id)
+        );
+
+        // Same index, different kind
+        let window_id = register_dynamic_marker(&mut world, MarkerKind::Window, 129)?;
+        assert_ne!(window_id, component_id);
+        Ok(())
+    }
+
+    #[test]
+    fn only_indices_past_the_generated_range_are_registered() {
+        let mut world = world();
+        world.insert_resource(KomorebiState {
+            komorebi: Some(wide_state(130)),
+        });
+        register_dynamic_markers(&mut world);
+
+        let map = world.resource::<ExtendedMarkerMap>();
+        let mut containers: Vec<usize> = map.containers.keys().copied().collect();
+        containers.sort();
+        assert_eq!(containers, vec![129, 130]);
+        assert!(map.monitors.is_empty() && map.workspaces.is_empty() && map.windows.is_empty());
+    }
+
+    #[test]
+    fn dynamic_markers_are_inserted_and_removed() -> Result<(), InteropError> {
+        let mut world = world();
+        let component_id = register_dynamic_marker(&mut world, MarkerKind::Container, 129)?;
+        let entity = world.spawn_empty().id();
+
+        let mut queue = CommandQueue::default();
+        world.resource_scope(|world, map: Mut<ExtendedMarkerMap>| {
+            insert_container_marker_component(129, entity, Commands::new(&mut queue, world), &map);
+        });
+        queue.apply(&mut world);
+        assert!(world.entity(entity).contains_id(component_id));
+
+        world.resource_scope(|world, map: Mut<ExtendedMarkerMap>| {
+            despawn_container_marker_component(129, entity, Commands::new(&mut queue, world), &map);
+        });
+        queue.apply(&mut world);
+        assert!(!world.entity(entity).contains_id(component_id));
+        Ok(())
+    }
+}
@@ -1,10 +1,14 @@
 pub mod component;
 pub mod container_maker_components;
+pub mod dynamic_markers;
 pub mod monitor_maker_components;
+pub mod tags;
 pub mod window_maker_components;
 pub mod workspace_maker_components;
 pub use component::*;
 pub use container_maker_components::*;
+pub use dynamic_markers::*;
 pub use monitor_maker_components::*;
+pub use tags::*;
 pub use window_maker_components::*;
 pub use workspace_maker_components::*;
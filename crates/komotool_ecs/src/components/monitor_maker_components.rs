@@ -241,7 +241,7 @@ pub fn insert_monitor_marker_component(
             commands.entity(entity).insert(Monitor32);
         }
         n if n > 32 => unsafe {
-            if let Some(component_id) = extended_marker_map.makers.get(&n) {
+            if let Some(component_id) = extended_marker_map.monitors.get(&n) {
                 commands
                     .entity(entity)
                     .insert_by_id(*component_id, DynamicComponent::default());
@@ -355,7 +355,7 @@ pub fn despawn_monitor_marker_component(
             commands.entity(entity).remove::<Monitor32>();
         }
         n if n > 32 => {
-            if let Some(component_id) = extended_marker_map.makers.get(&n) {
+            if let Some(component_id) = extended_marker_map.monitors.get(&n) {
                 commands.entity(entity).remove_by_id(*component_id);
             } else {
                 // Optional: Log warning if needed for index {n}
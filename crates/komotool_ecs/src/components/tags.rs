@@ -0,0 +1,41 @@
+use bevy_ecs::component::Component;
+use bevy_ecs::reflect::ReflectComponent;
+use bevy_reflect::Reflect;
+use bevy_reflect::std_traits::ReflectDefault;
+use std::collections::HashMap;
+
+/// Free-form labels scripts attach to a window or workspace, e.g. `"pinned"`.
+///
+/// Kept across imports and restarts by [`UserDataStore`](crate::user_data::UserDataStore).
+#[derive(Component, Reflect, Debug, Clone, Default, PartialEq, Eq)]
+#[reflect(Component, Default)]
+pub struct Tags(pub Vec<String>);
+
+impl Tags {
+    pub fn contains(&self, tag: &str) -> bool {
+        self.0.iter().any(|t| t == tag)
+    }
+
+    /// Adds a tag, returning `false` if it was already there.
+    pub fn insert(&mut self, tag: String) -> bool {
+        if self.contains(&tag) {
+            return false;
+        }
+        self.0.push(tag);
+        true
+    }
+
+    /// Removes a tag, returning `false` if it wasn't there.
+    pub fn remove(&mut self, tag: &str) -> bool {
+        let len = self.0.len();
+        self.0.retain(|t| t != tag);
+        self.0.len() != len
+    }
+}
+
+/// Key/value data scripts attach to a window or workspace, e.g. `project = "foo"`.
+///
+/// Kept across imports and restarts by [`UserDataStore`](crate::user_data::UserDataStore).
+#[derive(Component, Reflect, Debug, Clone, Default, PartialEq, Eq)]
+#[reflect(Component, Default)]
+pub struct Meta(pub HashMap<String, String>);
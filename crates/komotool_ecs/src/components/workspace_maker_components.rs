@@ -465,7 +465,7 @@ pub fn insert_workspace_marker_component(
             commands.entity(entity).insert(Workspace64);
         }
         n if n > 64 => unsafe {
-            if let Some(component_id) = extended_marker_map.makers.get(&n) {
+            if let Some(component_id) = extended_marker_map.workspaces.get(&n) {
                 commands
                     .entity(entity)
                     .insert_by_id(*component_id, DynamicComponent::default());
@@ -675,7 +675,7 @@ pub fn despawn_workspace_marker_component(
             commands.entity(entity).remove::<Workspace64>();
         }
         n if n > 64 => {
-            if let Some(component_id) = extended_marker_map.makers.get(&n) {
+            if let Some(component_id) = extended_marker_map.workspaces.get(&n) {
                 commands.entity(entity).remove_by_id(*component_id);
             } else {
                 // Optional: Log warning if needed for index {n}
@@ -0,0 +1,64 @@
+use bevy_ecs::entity::Entity;
+use bevy_ecs::event::Event;
+
+/// A window komorebi manages got an entity.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct WindowSpawned {
+    pub entity: Entity,
+    pub hwnd: isize,
+}
+
+/// A window's entity was despawned because its process is gone.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct WindowDespawned {
+    pub entity: Entity,
+    pub hwnd: isize,
+}
+
+/// A container moved to another workspace.
+///
+/// Workspaces are identified by their [`WorkspaceToEntityMap`](crate::resources::WorkspaceToEntityMap) key,
+/// which is the workspace name when it has one.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct ContainerMoved {
+    pub entity: Entity,
+    pub container_id: String,
+    pub from_workspace: String,
+    pub to_workspace: String,
+}
+
+/// A workspace became the focused workspace of its monitor.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceFocused {
+    pub entity: Entity,
+    pub workspace: String,
+    pub monitor_serial: String,
+}
+
+/// A monitor appeared in komorebi's state.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct MonitorConnected {
+    pub entity: Entity,
+    pub serial: String,
+}
+
+/// A monitor disappeared from komorebi's state.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct MonitorDisconnected {
+    pub entity: Entity,
+    pub serial: String,
+}
+
+/// The globally focused window changed.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct FocusChanged {
+    pub from: Option<FocusedWindow>,
+    pub to: Option<FocusedWindow>,
+}
+
+/// A window that holds or held global focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FocusedWindow {
+    pub entity: Entity,
+    pub hwnd: isize,
+}
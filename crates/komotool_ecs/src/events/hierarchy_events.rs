@@ -0,0 +1,126 @@
+use crate::events::{ContainerMoved, FocusChanged, FocusedWindow, WorkspaceFocused};
+use crate::resources::{
+    ContainerToEntityMap, KomorebiState, WindowToEntityMap, WorkspaceToEntityMap,
+};
+use crate::systems::workspace_key;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::event::EventWriter;
+use bevy_ecs::system::{Local, Res};
+use komorebi_client::State;
+use std::collections::HashMap;
+
+/// The parts of the hierarchy that hierarchy events are derived from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HierarchySnapshot {
+    /// Workspace key of every tiled container
+    pub container_workspaces: HashMap<String, String>,
+    /// Focused workspace key by monitor serial
+    pub focused_workspaces: HashMap<String, String>,
+    /// hwnd of the globally focused window
+    pub focused_hwnd: Option<isize>,
+}
+
+impl HierarchySnapshot {
+    pub fn from_state(state: &State) -> Self {
+        let mut snapshot = Self::default();
+        let focused_monitor = state.monitors.focused_idx();
+
+        for (monitor_idx, komo_mon) in state.monitors.elements().iter().enumerate() {
+            let focused_workspace = komo_mon.focused_workspace_idx();
+            for (workspace_idx, komo_ws) in komo_mon.workspaces().iter().enumerate() {
+                let key = workspace_key(komo_mon, monitor_idx, workspace_idx, komo_ws);
+                for komo_cont in komo_ws.containers() {
+                    snapshot
+                        .container_workspaces
+                        .insert(komo_cont.id().clone(), key.clone());
+                }
+
+                if workspace_idx != focused_workspace {
+                    continue;
+                }
+                if let Some(serial) = komo_mon.serial_number_id() {
+                    snapshot
+                        .focused_workspaces
+                        .insert(serial.clone(), key.clone());
+                }
+                if monitor_idx == focused_monitor {
+                    snapshot.focused_hwnd = if let Some(max) = komo_ws.maximized_window() {
+                        Some(max.hwnd)
+                    } else if let Some(monocle) = komo_ws.monocle_container() {
+                        monocle.focused_window().map(|w| w.hwnd)
+                    } else {
+                        komo_ws
+                            .focused_container()
+                            .and_then(|c| c.focused_window())
+                            .map(|w| w.hwnd)
+                    };
+                }
+            }
+        }
+        snapshot
+    }
+}
+
+/// Emits [`ContainerMoved`], [`WorkspaceFocused`] and [`FocusChanged`] by comparing the
+/// imported state with the one from the previous import.
+#[allow(clippy::too_many_arguments)]
+pub fn emit_hierarchy_events(
+    mut previous: Local<Option<HierarchySnapshot>>,
+    komorebi_state: Res<KomorebiState>,
+    workspace_map: Res<WorkspaceToEntityMap>,
+    container_map: Res<ContainerToEntityMap>,
+    window_map: Res<WindowToEntityMap>,
+    mut container_moved: EventWriter<ContainerMoved>,
+    mut workspace_focused: EventWriter<WorkspaceFocused>,
+    mut focus_changed: EventWriter<FocusChanged>,
+) {
+    let Some(state) = &komorebi_state.komorebi else {
+        return;
+    };
+    let current = HierarchySnapshot::from_state(state);
+    let previous = previous.replace(current.clone()).unwrap_or_default();
+
+    for (container_id, to_workspace) in &current.container_workspaces {
+        let Some(from_workspace) = previous.container_workspaces.get(container_id) else {
+            continue;
+        };
+        if from_workspace == to_workspace {
+            continue;
+        }
+        let Some(entity) = container_map.0.get(container_id) else {
+            continue;
+        };
+        container_moved.send(ContainerMoved {
+            entity: *entity,
+            container_id: container_id.clone(),
+            from_workspace: from_workspace.clone(),
+            to_workspace: to_workspace.clone(),
+        });
+    }
+
+    for (serial, workspace) in &current.focused_workspaces {
+        if previous.focused_workspaces.get(serial) == Some(workspace) {
+            continue;
+        }
+        let Some(entity) = workspace_map.0.get(workspace) else {
+            continue;
+        };
+        workspace_focused.send(WorkspaceFocused {
+            entity: *entity,
+            workspace: workspace.clone(),
+            monitor_serial: serial.clone(),
+        });
+    }
+
+    if current.focused_hwnd != previous.focused_hwnd {
+        let focused_window = |hwnd: Option<isize>| -> Option<FocusedWindow> {
+            let hwnd = hwnd?;
+            let entity: Entity = *window_map.0.get(&hwnd.to_string())?;
+            Some(FocusedWindow { entity, hwnd })
+        };
+        focus_changed.send(FocusChanged {
+            from: focused_window(previous.focused_hwnd),
+            to: focused_window(current.focused_hwnd),
+        });
+    }
+}
@@ -0,0 +1,7 @@
+pub mod event;
+pub mod hierarchy_events;
+pub mod script_forwarding;
+
+pub use event::*;
+pub use hierarchy_events::*;
+pub use script_forwarding::*;
@@ -0,0 +1,142 @@
+use crate::events::*;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::event::{EventReader, EventWriter};
+use bevy_ecs::system::Res;
+use bevy_mod_scripting::core::bindings::{
+    AppReflectAllocator, ReflectAllocator, ReflectReference, ScriptValue,
+};
+use bevy_mod_scripting::core::event::{Recipients, ScriptCallbackEvent};
+use komotool_utils::callbacklabels::{
+    OnContainerMoved, OnFocusChanged, OnMonitorConnected, OnMonitorDisconnected, OnWindowDespawned,
+    OnWindowSpawned, OnWorkspaceFocused,
+};
+use std::collections::HashMap;
+
+/// Entities are passed to scripts as `Val<Entity>` references, like the namespace functions
+/// return them.
+pub fn entity_value(entity: Entity, allocator: &mut ReflectAllocator) -> ScriptValue {
+    ScriptValue::Reference(ReflectReference::new_allocated(entity, allocator))
+}
+
+fn string_value(value: &str) -> ScriptValue {
+    ScriptValue::String(value.to_string().into())
+}
+
+fn focused_window_value(
+    window: Option<FocusedWindow>,
+    allocator: &mut ReflectAllocator,
+) -> ScriptValue {
+    match window {
+        Some(window) => table([
+            ("entity", entity_value(window.entity, allocator)),
+            ("hwnd", ScriptValue::Integer(window.hwnd as i64)),
+        ]),
+        None => ScriptValue::Unit,
+    }
+}
+
+fn table<const N: usize>(entries: [(&str, ScriptValue); N]) -> ScriptValue {
+    ScriptValue::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect::<HashMap<_, _>>(),
+    )
+}
+
+/// Calls the matching script callback, e.g. `on_window_spawned`, with a table describing
+/// each lifecycle and focus event.
+#[allow(clippy::too_many_arguments)]
+pub fn forward_ecs_events_to_scripts(
+    mut window_spawned: EventReader<WindowSpawned>,
+    mut window_despawned: EventReader<WindowDespawned>,
+    mut container_moved: EventReader<ContainerMoved>,
+    mut workspace_focused: EventReader<WorkspaceFocused>,
+    mut monitor_connected: EventReader<MonitorConnected>,
+    mut monitor_disconnected: EventReader<MonitorDisconnected>,
+    mut focus_changed: EventReader<FocusChanged>,
+    mut writer: EventWriter<ScriptCallbackEvent>,
+    allocator: Res<AppReflectAllocator>,
+) {
+    let mut allocator = allocator.write();
+    for event in window_spawned.read() {
+        let arg = table([
+            ("entity", entity_value(event.entity, &mut allocator)),
+            ("hwnd", ScriptValue::Integer(event.hwnd as i64)),
+        ]);
+        writer.send(ScriptCallbackEvent::new(
+            OnWindowSpawned,
+            vec![arg],
+            Recipients::All,
+        ));
+    }
+    for event in window_despawned.read() {
+        let arg = table([
+            ("entity", entity_value(event.entity, &mut allocator)),
+            ("hwnd", ScriptValue::Integer(event.hwnd as i64)),
+        ]);
+        writer.send(ScriptCallbackEvent::new(
+            OnWindowDespawned,
+            vec![arg],
+            Recipients::All,
+        ));
+    }
+    for event in container_moved.read() {
+        let arg = table([
+            ("entity", entity_value(event.entity, &mut allocator)),
+            ("container_id", string_value(&event.container_id)),
+            ("from_workspace", string_value(&event.from_workspace)),
+            ("to_workspace", string_value(&event.to_workspace)),
+        ]);
+        writer.send(ScriptCallbackEvent::new(
+            OnContainerMoved,
+            vec![arg],
+            Recipients::All,
+        ));
+    }
+    for event in workspace_focused.read() {
+        let arg = table([
+            ("entity", entity_value(event.entity, &mut allocator)),
+            ("workspace", string_value(&event.workspace)),
+            ("monitor_serial", string_value(&event.monitor_serial)),
+        ]);
+        writer.send(ScriptCallbackEvent::new(
+            OnWorkspaceFocused,
+            vec![arg],
+            Recipients::All,
+        ));
+    }
+    for event in monitor_connected.read() {
+        let arg = table([
+            ("entity", entity_value(event.entity, &mut allocator)),
+            ("serial", string_value(&event.serial)),
+        ]);
+        writer.send(ScriptCallbackEvent::new(
+            OnMonitorConnected,
+            vec![arg],
+            Recipients::All,
+        ));
+    }
+    for event in monitor_disconnected.read() {
+        let arg = table([
+            ("entity", entity_value(event.entity, &mut allocator)),
+            ("serial", string_value(&event.serial)),
+        ]);
+        writer.send(ScriptCallbackEvent::new(
+            OnMonitorDisconnected,
+            vec![arg],
+            Recipients::All,
+        ));
+    }
+    for event in focus_changed.read() {
+        let arg = table([
+            ("from", focused_window_value(event.from, &mut allocator)),
+            ("to", focused_window_value(event.to, &mut allocator)),
+        ]);
+        writer.send(ScriptCallbackEvent::new(
+            OnFocusChanged,
+            vec![arg],
+            Recipients::All,
+        ));
+    }
+}
@@ -0,0 +1,291 @@
+use crate::components::Focused;
+use crate::events::entity_value;
+use crate::layouts::layout::{CustomLayout, LayoutEngines, PendingLayouts};
+use crate::relations::{ChildOfMonitor, RelationIndex, WorkspaceContainers};
+use crate::transport::KomorebiTransport;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::event::EventWriter;
+use bevy_ecs::query::Has;
+use bevy_ecs::removal_detection::RemovedComponents;
+use bevy_ecs::system::{Local, Query, Res, ResMut};
+use bevy_mod_scripting::core::bindings::{AppReflectAllocator, ScriptValue};
+use bevy_mod_scripting::core::event::{Recipients, ScriptCallbackEvent};
+use komorebi_client::{Container, Monitor, Rect, SocketMessage, Workspace};
+use komotool_utils::callbacklabels::OnLayout;
+use std::collections::{HashMap, HashSet};
+
+/// Shrinks a rect by `padding` on every side.
+pub fn pad_rect(rect: Rect, padding: i32) -> Rect {
+    Rect {
+        left: rect.left + padding,
+        top: rect.top + padding,
+        right: (rect.right - padding * 2).max(0),
+        bottom: (rect.bottom - padding * 2).max(0),
+    }
+}
+
+/// Everything a layout depends on; the layout is recomputed whenever it changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutInput {
+    pub name: String,
+    pub area: Rect,
+    pub containers: Vec<Entity>,
+}
+
+pub fn rect_value(rect: Rect) -> ScriptValue {
+    ScriptValue::Map(
+        [
+            ("left", rect.left),
+            ("top", rect.top),
+            ("right", rect.right),
+            ("bottom", rect.bottom),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), ScriptValue::Integer(value as i64)))
+        .collect(),
+    )
+}
+
+/// Computes [`CustomLayout`] workspaces and moves their containers' windows into place.
+///
+/// Only the focused workspace of each monitor is laid out, when its containers or work area
+/// change. Layouts without a [`LayoutEngine`](super::LayoutEngine) are sent to scripts as
+/// `on_layout(name, workspace, area, containers)`; scripts answer with `Komotool.apply_layout`.
+/// komorebi's own tiling is switched off for the workspace while a custom layout is set.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn apply_custom_layouts(
+    engines: Res<LayoutEngines>,
+    transport: Res<KomorebiTransport>,
+    mut pending: ResMut<PendingLayouts>,
+    workspaces: Query<(
+        Entity,
+        &CustomLayout,
+        &Workspace,
+        &ChildOfMonitor,
+        Option<&WorkspaceContainers>,
+        Option<&RelationIndex>,
+        Has<Focused>,
+    )>,
+    relation_indices: Query<(&Workspace, &RelationIndex)>,
+    monitors: Query<&Monitor>,
+    containers: Query<&Container>,
+    mut removed: RemovedComponents<CustomLayout>,
+    mut applied: Local<HashMap<Entity, LayoutInput>>,
+    mut untiled: Local<HashSet<Entity>>,
+    mut writer: EventWriter<ScriptCallbackEvent>,
+    allocator: Res<AppReflectAllocator>,
+) {
+    // Hand workspaces without a custom layout back to komorebi
+    for entity in removed.read() {
+        applied.remove(&entity);
+        untiled.remove(&entity);
+        if let Ok((workspace, index)) = relation_indices.get(entity) {
+            if !workspace.tile() {
+                set_workspace_tiling(&transport, index, true);
+            }
+        }
+    }
+
+    for (entity, rects) in pending.0.drain() {
+        let Ok((_, _, workspace, _, Some(workspace_containers), _, _)) = workspaces.get(entity)
+        else {
+            continue;
+        };
+        position_containers(
+            &transport,
+            &containers,
+            &workspace_containers.0,
+            &rects,
+            workspace.container_padding().unwrap_or_default(),
+        );
+    }
+
+    for (entity, layout, workspace, monitor, workspace_containers, index, focused) in
+        workspaces.iter()
+    {
+        // Sent once, until komorebi's state catches up
+        if !workspace.tile() {
+            untiled.remove(&entity);
+        } else if let Some(index) = index {
+            if untiled.insert(entity) {
+                set_workspace_tiling(&transport, index, false);
+            }
+        }
+        if !focused {
+            continue;
+        }
+        let Ok(monitor) = monitors.get(monitor.0) else {
+            continue;
+        };
+        let input = LayoutInput {
+            name: layout.name.clone(),
+            area: pad_rect(
+                monitor.work_area_size(),
+                workspace.workspace_padding().unwrap_or_default(),
+            ),
+            containers: workspace_containers
+                .map(|containers| containers.0.clone())
+                .unwrap_or_default(),
+        };
+        if applied.get(&entity) == Some(&input) {
+            continue;
+        }
+
+        match engines.get(&input.name) {
+            Some(engine) => {
+                let rects = engine.arrange(input.area, &input.containers);
+                position_containers(
+                    &transport,
+                    &containers,
+                    &input.containers,
+                    &rects,
+                    workspace.container_padding().unwrap_or_default(),
+                );
+            }
+            None => {
+                let mut allocator = allocator.write();
+                let container_values = input
+                    .containers
+                    .iter()
+                    .map(|container| entity_value(*container, &mut allocator))
+                    .collect();
+                writer.send(ScriptCallbackEvent::new(
+                    OnLayout,
+                    vec![
+                        ScriptValue::String(input.name.clone().into()),
+                        entity_value(entity, &mut allocator),
+                        rect_value(input.area),
+                        ScriptValue::List(container_values),
+                    ],
+                    Recipients::All,
+                ));
+            }
+        }
+        applied.insert(entity, input);
+    }
+}
+
+fn set_workspace_tiling(transport: &KomorebiTransport, index: &RelationIndex, tile: bool) {
+    if index.monitor == 0 || index.workspace == 0 {
+        return;
+    }
+    let message = SocketMessage::WorkspaceTiling(index.monitor - 1, index.workspace - 1, tile);
+    if let Err(e) = transport.send_all(&[message]) {
+        eprintln!("Failed to set workspace tiling to {}: {}", tile, e);
+    }
+}
+
+/// Moves the focused window of each container into its rect, through the transport so dry-run
+/// and shadow mode leave windows where they are.
+fn position_containers(
+    transport: &KomorebiTransport,
+    containers: &Query<&Container>,
+    container_entities: &[Entity],
+    rects: &[Rect],
+    container_padding: i32,
+) {
+    for (entity, rect) in container_entities.iter().zip(rects) {
+        let Some(window) = containers
+            .get(*entity)
+            .ok()
+            .and_then(|container| container.focused_window().copied())
+        else {
+            continue;
+        };
+        if let Err(e) = transport.position_window(&window, &pad_rect(*rect, container_padding)) {
+            eprintln!("Failed to position window {}: {}", window.hwnd, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_states::{container, monitor, workspace};
+    use crate::transport::MockTransport;
+    use bevy_ecs::event::Events;
+    use bevy_ecs::system::RunSystemOnce;
+    use bevy_ecs::world::World;
+    use bevy_mod_scripting::core::event::IntoCallbackLabel;
+
+    #[test]
+    fn padding_shrinks_every_side() {
+        let rect = Rect {
+            left: 100,
+            top: 50,
+            right: 800,
+            bottom: 600,
+        };
+        assert_eq!(
+            pad_rect(rect, 10),
+            Rect {
+                left: 110,
+                top: 60,
+                right: 780,
+                bottom: 580,
+            }
+        );
+        assert_eq!(pad_rect(rect, 0), rect);
+    }
+
+    #[test]
+    fn padding_never_turns_a_rect_inside_out() {
+        let rect = Rect {
+            left: 0,
+            top: 0,
+            right: 30,
+            bottom: 10,
+        };
+        let padded = pad_rect(rect, 20);
+        assert_eq!((padded.right, padded.bottom), (0, 0));
+    }
+
+    #[test]
+    fn script_layouts_send_on_layout_with_every_container() {
+        let mut world = World::new();
+        world.init_resource::<LayoutEngines>();
+        world.init_resource::<PendingLayouts>();
+        world.init_resource::<AppReflectAllocator>();
+        world.init_resource::<Events<ScriptCallbackEvent>>();
+        world.insert_resource(KomorebiTransport(Box::new(MockTransport::default())));
+
+        let monitor = world.spawn(monitor("m1", vec![])).id();
+        let containers = vec![
+            world.spawn(container(&[1])).id(),
+            world.spawn(container(&[2])).id(),
+        ];
+        world.spawn((
+            CustomLayout {
+                name: "from-script".to_string(),
+            },
+            workspace("a", &[&[1], &[2]]),
+            ChildOfMonitor(monitor),
+            WorkspaceContainers(containers),
+            Focused,
+        ));
+
+        assert!(world.run_system_once(apply_custom_layouts).is_ok());
+
+        let events: Vec<_> = world
+            .resource_mut::<Events<ScriptCallbackEvent>>()
+            .drain()
+            .collect();
+        assert_eq!(events.len(), 1);
+        let Some(event) = events.first() else {
+            return;
+        };
+        assert!(event.label == OnLayout::into_callback_label());
+        assert_eq!(event.args.len(), 4);
+        assert!(matches!(event.args.get(1), Some(ScriptValue::Reference(_))));
+        let container_values = match event.args.get(3) {
+            Some(ScriptValue::List(values)) => values.as_slice(),
+            _ => &[],
+        };
+        assert_eq!(container_values.len(), 2);
+        assert!(
+            container_values
+                .iter()
+                .all(|value| matches!(value, ScriptValue::Reference(_)))
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use crate::layouts::layout::LayoutEngine;
+use bevy_ecs::entity::Entity;
+use komorebi_client::Rect;
+
+/// Splits `area` into a left part `ratio` of its width wide and the remaining right part.
+pub fn split_vertical(area: Rect, ratio: f32) -> (Rect, Rect) {
+    let left_width = (area.right as f32 * ratio).round() as i32;
+    (
+        Rect {
+            left: area.left,
+            top: area.top,
+            right: left_width,
+            bottom: area.bottom,
+        },
+        Rect {
+            left: area.left + left_width,
+            top: area.top,
+            right: area.right - left_width,
+            bottom: area.bottom,
+        },
+    )
+}
+
+/// Splits `area` into a top part `ratio` of its height tall and the remaining bottom part.
+pub fn split_horizontal(area: Rect, ratio: f32) -> (Rect, Rect) {
+    let top_height = (area.bottom as f32 * ratio).round() as i32;
+    (
+        Rect {
+            left: area.left,
+            top: area.top,
+            right: area.right,
+            bottom: top_height,
+        },
+        Rect {
+            left: area.left,
+            top: area.top + top_height,
+            right: area.right,
+            bottom: area.bottom - top_height,
+        },
+    )
+}
+
+/// Stacks `count` equally tall rows in `area`; the last row takes the rounding remainder.
+pub fn stack_rows(area: Rect, count: usize) -> Vec<Rect> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let height = area.bottom / count as i32;
+    (0..count)
+        .map(|idx| {
+            let top = area.top + height * idx as i32;
+            let bottom = if idx + 1 == count {
+                area.top + area.bottom - top
+            } else {
+                height
+            };
+            Rect {
+                left: area.left,
+                top,
+                right: area.right,
+                bottom,
+            }
+        })
+        .collect()
+}
+
+/// Halves the remaining space for each container, turning clockwise: left, top, right, bottom.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Spiral;
+
+impl LayoutEngine for Spiral {
+    fn arrange(&self, area: Rect, containers: &[Entity]) -> Vec<Rect> {
+        let mut rects = Vec::with_capacity(containers.len());
+        let mut remaining = area;
+        for idx in 0..containers.len() {
+            if idx + 1 == containers.len() {
+                rects.push(remaining);
+                break;
+            }
+            let (taken, rest) = match idx % 4 {
+                0 => split_vertical(remaining, 0.5),
+                1 => split_horizontal(remaining, 0.5),
+                2 => {
+                    let (rest, taken) = split_vertical(remaining, 0.5);
+                    (taken, rest)
+                }
+                _ => {
+                    let (rest, taken) = split_horizontal(remaining, 0.5);
+                    (taken, rest)
+                }
+            };
+            rects.push(taken);
+            remaining = rest;
+        }
+        rects
+    }
+}
+
+/// Splits the remaining space at the golden ratio for each container, alternating vertical
+/// and horizontal splits, so every container is larger than all of the ones after it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GoldenRatio;
+
+const GOLDEN_RATIO: f32 = 0.618_034;
+
+impl LayoutEngine for GoldenRatio {
+    fn arrange(&self, area: Rect, containers: &[Entity]) -> Vec<Rect> {
+        let mut rects = Vec::with_capacity(containers.len());
+        let mut remaining = area;
+        for idx in 0..containers.len() {
+            if idx + 1 == containers.len() {
+                rects.push(remaining);
+                break;
+            }
+            let (taken, rest) = if idx % 2 == 0 {
+                split_vertical(remaining, GOLDEN_RATIO)
+            } else {
+                split_horizontal(remaining, GOLDEN_RATIO)
+            };
+            rects.push(taken);
+            remaining = rest;
+        }
+        rects
+    }
+}
+
+/// The first container in a centred column, the others stacked alternately to its right and left.
+#[derive(Debug, Clone, Copy)]
+pub struct CenteredMaster {
+    /// Width of the master column as a fraction of the work area
+    pub master_ratio: f32,
+}
+
+impl Default for CenteredMaster {
+    fn default() -> Self {
+        Self { master_ratio: 0.5 }
+    }
+}
+
+impl LayoutEngine for CenteredMaster {
+    fn arrange(&self, area: Rect, containers: &[Entity]) -> Vec<Rect> {
+        match containers.len() {
+            0 => Vec::new(),
+            1 => vec![area],
+            2 => {
+                let (master, stack) = split_vertical(area, self.master_ratio);
+                vec![master, stack]
+            }
+            count => {
+                let side_ratio = (1.0 - self.master_ratio) / 2.0;
+                let (left, rest) = split_vertical(area, side_ratio);
+                let master_share = self.master_ratio / (1.0 - side_ratio);
+                let (master, right) = split_vertical(rest, master_share);
+
+                let stacked = count - 1;
+                let right_count = stacked.div_ceil(2);
+                let mut right_rows = stack_rows(right, right_count).into_iter();
+                let mut left_rows = stack_rows(left, stacked - right_count).into_iter();
+
+                let mut rects = vec![master];
+                for idx in 0..stacked {
+                    let row = if idx % 2 == 0 {
+                        right_rows.next()
+                    } else {
+                        left_rows.next()
+                    };
+                    rects.extend(row);
+                }
+                rects
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect {
+        left: 100,
+        top: 50,
+        right: 1200,
+        bottom: 900,
+    };
+
+    fn containers(count: usize) -> Vec<Entity> {
+        (0..count as u32).map(Entity::from_raw).collect()
+    }
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> Rect {
+        Rect {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    fn engines() -> Vec<(&'static str, Box<dyn LayoutEngine>)> {
+        vec![
+            ("spiral", Box::new(Spiral)),
+            ("golden-ratio", Box::new(GoldenRatio)),
+            ("centered-master", Box::new(CenteredMaster::default())),
+        ]
+    }
+
+    fn overlaps(a: &Rect, b: &Rect) -> bool {
+        a.left < b.left + b.right
+            && b.left < a.left + a.right
+            && a.top < b.top + b.bottom
+            && b.top < a.top + a.bottom
+    }
+
+    #[test]
+    fn every_engine_tiles_the_whole_area() {
+        for (name, engine) in engines() {
+            for count in 1..=9 {
+                let rects = engine.arrange(AREA, &containers(count));
+                assert_eq!(rects.len(), count, "{} with {} containers", name, count);
+                for rect in &rects {
+                    assert!(
+                        rect.left >= AREA.left
+                            && rect.top >= AREA.top
+                            && rect.left + rect.right <= AREA.left + AREA.right
+                            && rect.top + rect.bottom <= AREA.top + AREA.bottom,
+                        "{} with {} containers: {:?} outside the area",
+                        name,
+                        count,
+                        rect
+                    );
+                }
+                for (idx, a) in rects.iter().enumerate() {
+                    for b in &rects[idx + 1..] {
+                        assert!(!overlaps(a, b), "{}: {:?} overlaps {:?}", name, a, b);
+                    }
+                }
+                let covered: i32 = rects.iter().map(|rect| rect.right * rect.bottom).sum();
+                assert_eq!(covered, AREA.right * AREA.bottom, "{} with {}", name, count);
+            }
+        }
+    }
+
+    #[test]
+    fn a_single_container_fills_the_area() {
+        for (name, engine) in engines() {
+            assert_eq!(engine.arrange(AREA, &containers(1)), vec![AREA], "{}", name);
+            assert!(engine.arrange(AREA, &[]).is_empty(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn spiral_turns_clockwise() {
+        assert_eq!(
+            Spiral.arrange(AREA, &containers(4)),
+            vec![
+                rect(100, 50, 600, 900),
+                rect(700, 50, 600, 450),
+                rect(1000, 500, 300, 450),
+                rect(700, 500, 300, 450),
+            ]
+        );
+    }
+
+    #[test]
+    fn centered_master_alternates_sides_for_odd_stacks() {
+        assert_eq!(
+            CenteredMaster::default().arrange(AREA, &containers(4)),
+            vec![
+                rect(400, 50, 600, 900),
+                rect(1000, 50, 300, 450),
+                rect(100, 50, 300, 900),
+                rect(1000, 500, 300, 450),
+            ]
+        );
+    }
+
+    #[test]
+    fn stack_rows_gives_the_remainder_to_the_last_row() {
+        assert_eq!(
+            stack_rows(rect(0, 10, 100, 100), 3),
+            vec![
+                rect(0, 10, 100, 33),
+                rect(0, 43, 100, 33),
+                rect(0, 76, 100, 34)
+            ]
+        );
+        assert!(stack_rows(AREA, 0).is_empty());
+    }
+}
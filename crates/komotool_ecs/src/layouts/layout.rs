@@ -0,0 +1,65 @@
+use crate::layouts::builtin::{CenteredMaster, GoldenRatio, Spiral};
+use bevy_ecs::component::Component;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::reflect::ReflectComponent;
+use bevy_ecs::system::Resource;
+use bevy_reflect::Reflect;
+use komorebi_client::Rect;
+use std::collections::HashMap;
+
+/// Arranges a workspace's containers inside its work area.
+///
+/// Rects use komorebi's convention: `right` and `bottom` are the width and height.
+/// Implementations are pure, so they can be tested without a window manager.
+pub trait LayoutEngine: Send + Sync + 'static {
+    /// One rect per container, in the same order. Extra rects are ignored and missing ones
+    /// leave their containers where they are.
+    fn arrange(&self, area: Rect, containers: &[Entity]) -> Vec<Rect>;
+}
+
+/// Lays out a workspace with the named engine instead of komorebi's own layout.
+///
+/// A name with no registered [`LayoutEngine`] is computed by scripts through `on_layout`.
+#[derive(Component, Reflect, Debug, Clone, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct CustomLayout {
+    pub name: String,
+}
+
+/// The Rust layout engines, by name.
+#[derive(Resource)]
+pub struct LayoutEngines {
+    engines: HashMap<String, Box<dyn LayoutEngine>>,
+}
+
+impl Default for LayoutEngines {
+    fn default() -> Self {
+        let mut engines = Self {
+            engines: HashMap::new(),
+        };
+        engines.register("spiral", Spiral);
+        engines.register("centered-master", CenteredMaster::default());
+        engines.register("golden-ratio", GoldenRatio);
+        engines
+    }
+}
+
+impl LayoutEngines {
+    pub fn register(&mut self, name: impl Into<String>, engine: impl LayoutEngine) {
+        self.engines.insert(name.into(), Box::new(engine));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn LayoutEngine> {
+        self.engines.get(name).map(|engine| engine.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.engines.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+/// Rects computed by scripts in response to `on_layout`, waiting to be applied.
+#[derive(Resource, Default)]
+pub struct PendingLayouts(pub HashMap<Entity, Vec<Rect>>);
@@ -0,0 +1,7 @@
+pub mod apply;
+pub mod builtin;
+pub mod layout;
+
+pub use apply::*;
+pub use builtin::*;
+pub use layout::*;
@@ -1,27 +1,68 @@
 pub mod components;
+pub mod events;
+pub mod layouts;
 pub mod register_komorebi_types;
 pub mod relations;
 pub mod resources;
+pub mod rules;
+pub mod script_functions;
+pub mod sessions;
+pub mod shadow;
 pub mod systems;
+pub mod transport;
+pub mod undo;
+pub mod user_data;
+pub mod validation;
+
+#[cfg(test)]
+mod test_states;
 
 pub mod prelude {
     pub use super::*;
     pub use components::*;
+    pub use events::*;
+    pub use layouts::*;
     pub use register_komorebi_types::*;
     pub use relations::*;
     pub use resources::*;
+    pub use rules::*;
+    pub use script_functions::*;
+    pub use sessions::*;
+    pub use shadow::*;
     pub use systems::*;
+    pub use transport::*;
+    pub use undo::*;
+    pub use user_data::*;
+    pub use validation::*;
 }
 
-use bevy_app::{App, First, Last, Plugin};
+use bevy_app::{App, First, Last, Plugin, Startup};
 use bevy_ecs::prelude::resource_changed;
 use bevy_ecs::schedule::IntoSystemConfigs;
 use components::*;
+use events::*;
 use komorebi_client::{Container, Monitor, Window, Workspace};
+use komotool_utils::komorebi_commands::{KomorebiCommandCounter, KomorebiOutgoing};
+use layouts::*;
 use register_komorebi_types::register_komorebi_types;
 use relations::*;
 use resources::*;
+use rules::*;
+use script_functions::{
+    register_layout_functions, register_outgoing_mode_functions, register_query_functions,
+    register_session_functions, register_state_validation_functions,
+    register_static_config_functions, register_undo_functions, register_user_data_functions,
+    register_write_back_functions,
+};
+use sessions::handle_session_commands;
+use shadow::{
+    ShadowResync, apply_shadowed_messages, finish_shadow_resync, handle_outgoing_mode_commands,
+};
 use systems::*;
+use transport::KomorebiTransport;
+use undo::*;
+use user_data::*;
+use validation::StateValidation;
 
 #[derive(Default)]
 pub struct KomoToolEcsPlugin;
@@ -32,16 +73,37 @@ impl Plugin for KomoToolEcsPlugin {
             .init_resource::<RelationRegistry>()
             .init_resource::<ExtendedMarkerMap>()
             .init_resource::<KomorebiState>()
-            .init_resource::<KomotoolState>()
+            .init_resource::<WriteBackState>()
+            .init_resource::<KomorebiCommandCounter>()
+            .init_resource::<KomorebiOutgoing>()
+            .init_resource::<KomorebiTransport>()
+            .init_resource::<FocusHistory>()
+            .init_resource::<LayoutEngines>()
+            .init_resource::<PendingLayouts>()
+            .init_resource::<WindowRules>()
             .init_resource::<MonitorToEntityMap>()
             .init_resource::<WorkspaceToEntityMap>()
             .init_resource::<ContainerToEntityMap>()
             .init_resource::<WindowToEntityMap>()
             .init_resource::<KomotoolStaticConfig>()
             .init_resource::<KomorebiStaticConfig>()
+            .init_resource::<StaticConfigOutput>()
+            .init_resource::<SessionDirectory>()
+            .init_resource::<UndoHistory>()
+            .init_resource::<UserDataPath>()
+            .init_resource::<UserDataStore>()
+            .init_resource::<StateValidation>()
+            .init_resource::<ShadowResync>()
             .init_resource::<KeepAliveMonitors>()
             .init_resource::<KeepAliveWorkspaces>()
             .init_resource::<KeepAliveContainers>()
+            .add_event::<WindowSpawned>()
+            .add_event::<WindowDespawned>()
+            .add_event::<ContainerMoved>()
+            .add_event::<WorkspaceFocused>()
+            .add_event::<MonitorConnected>()
+            .add_event::<MonitorDisconnected>()
+            .add_event::<FocusChanged>()
             .register_type::<Monitor>()
             .register_type::<Window>()
             .register_type::<Container>()
@@ -52,12 +114,35 @@ impl Plugin for KomoToolEcsPlugin {
             .register_type::<FocusedGlobal>()
             .register_type::<MaximizedWindow>()
             .register_type::<LastFocused>()
+            .register_type::<WriteBackState>()
+            .register_type::<FocusHistory>()
+            .register_type::<KomorebiStaticConfig>()
+            .register_type::<KomotoolStaticConfig>()
+            .register_type::<StaticConfigOutput>()
+            .register_type::<SessionDirectory>()
+            .register_type::<UserDataPath>()
+            .register_type::<StateValidation>()
+            .register_type::<ChildOfMonitor>()
+            .register_type::<ChildOfWorkspace>()
+            .register_type::<ChildOfContainer>()
+            .register_type::<MonitorWorkspaces>()
+            .register_type::<WorkspaceContainers>()
+            .register_type::<WorkspaceFloatingWindows>()
+            .register_type::<ContainerWindows>()
+            .register_type::<RelationIndex>()
+            .register_type::<CustomLayout>()
+            .register_type::<Tags>()
+            .register_type::<Meta>()
+            .add_systems(Startup, load_user_data)
             .add_systems(
                 First,
                 (
                     // Process notifications first
                     update_komorebi_state_from_notifications
                         .after(komotool_pipe::handle_pipe_notifications),
+                    // Shadowed messages act on top of komorebi's latest state
+                    apply_shadowed_messages.after(update_komorebi_state_from_notifications),
+                    finish_shadow_resync.after(apply_shadowed_messages),
                     // Then run all imports in parallel
                     (
                         (
@@ -65,23 +150,58 @@ impl Plugin for KomoToolEcsPlugin {
                             import_komorebi_container_state,
                             import_komorebi_workspace_state,
                             import_komorebi_monitor_state,
+                            register_dynamic_markers,
                         )
                             .before(build_relation_registry),
                         import_komorebi_appstate_state,
                         build_relation_registry,
+                        emit_hierarchy_events.after(build_relation_registry),
+                        update_global_focus.after(emit_hierarchy_events),
                     )
-                        .after(update_komorebi_state_from_notifications)
+                        .after(finish_shadow_resync)
                         .run_if(resource_changed::<KomorebiState>),
+                    // Rules run before scripts hear about the events that triggered them
+                    evaluate_window_rules
+                        .after(update_global_focus)
+                        .before(forward_ecs_events_to_scripts),
+                    forward_ecs_events_to_scripts.after(emit_hierarchy_events),
+                    apply_custom_layouts.after(build_relation_registry),
+                    sync_user_data.after(build_relation_registry),
+                ),
+            )
+            .add_systems(
+                First,
+                (
+                    seed_komotool_static_config.run_if(resource_changed::<KomorebiStaticConfig>),
+                    handle_static_config_commands.after(komotoolc_pipe::forward_komotoolc_requests),
+                    handle_session_commands.after(komotoolc_pipe::forward_komotoolc_requests),
+                    handle_undo_commands.after(komotoolc_pipe::forward_komotoolc_requests),
+                    handle_outgoing_mode_commands.after(komotoolc_pipe::forward_komotoolc_requests),
                 ),
             )
             .add_systems(
                 Last,
-                export_state_to_komorebi.before(komotool_framepace::framerate_limiter),
+                (
+                    write_back_state,
+                    // After everything that sends commands this frame
+                    record_undo_history.after(write_back_state),
+                    save_user_data,
+                )
+                    .before(komotool_framepace::framerate_limiter),
             );
         register_container_types(app);
         register_monitor_types(app);
         register_window_types(app);
         register_workspace_types(app);
         register_komorebi_types(app);
+        register_query_functions(app);
+        register_write_back_functions(app);
+        register_static_config_functions(app);
+        register_layout_functions(app);
+        register_session_functions(app);
+        register_undo_functions(app);
+        register_user_data_functions(app);
+        register_state_validation_functions(app);
+        register_outgoing_mode_functions(app);
     }
 }
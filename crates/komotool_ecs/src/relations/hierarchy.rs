@@ -0,0 +1,65 @@
+use bevy_ecs::component::Component;
+use bevy_ecs::entity::Entity;
+use bevy_reflect::Reflect;
+
+/// The monitor a workspace, container or window belongs to.
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChildOfMonitor(pub Entity);
+
+/// The workspace a container or window belongs to.
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChildOfWorkspace(pub Entity);
+
+/// The container a tiled window belongs to.
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChildOfContainer(pub Entity);
+
+/// The workspaces on a monitor, in order.
+#[derive(Component, Reflect, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MonitorWorkspaces(pub Vec<Entity>);
+
+/// The tiled containers on a workspace, in order.
+#[derive(Component, Reflect, Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceContainers(pub Vec<Entity>);
+
+/// The floating windows on a workspace, in order.
+#[derive(Component, Reflect, Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceFloatingWindows(pub Vec<Entity>);
+
+/// The windows in a container, in order.
+#[derive(Component, Reflect, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContainerWindows(pub Vec<Entity>);
+
+/// An entity's 1-based position in the hierarchy, the same key as its [`RelationRegistry`](super::RelationRegistry) record.
+///
+/// Levels that don't apply are 0, e.g. a workspace has `container` and `window` set to 0.
+#[derive(Component, Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RelationIndex {
+    pub monitor: usize,
+    pub workspace: usize,
+    pub container: usize,
+    pub window: usize,
+}
+
+impl RelationIndex {
+    pub fn new(monitor: usize, workspace: usize, container: usize, window: usize) -> Self {
+        Self {
+            monitor,
+            workspace,
+            container,
+            window,
+        }
+    }
+}
+
+/// Every hierarchy component, for clearing an entity before it is placed again.
+pub type RelationComponents = (
+    ChildOfMonitor,
+    ChildOfWorkspace,
+    ChildOfContainer,
+    MonitorWorkspaces,
+    WorkspaceContainers,
+    WorkspaceFloatingWindows,
+    ContainerWindows,
+    RelationIndex,
+);
@@ -1,3 +1,7 @@
+pub mod hierarchy;
+pub mod queries;
 pub mod registry;
 
-pub use registry::{EntityRecord, RelationRegistry};
+pub use hierarchy::*;
+pub use queries::*;
+pub use registry::{EntityRecord, RecordPath, RelationRegistry};
@@ -0,0 +1,125 @@
+use crate::relations::registry::RelationRegistry;
+use crate::resources::{
+    KomorebiState, MonitorToEntityMap, WindowToEntityMap, WorkspaceToEntityMap,
+};
+use crate::systems::workspace_key;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::world::World;
+use komorebi_client::{State, Window};
+
+/// The entity-map key of the workspace komorebi currently has focused.
+pub fn focused_workspace_key(state: &State) -> Option<String> {
+    let monitor_idx = state.monitors.focused_idx();
+    let komo_mon = state.monitors.elements().get(monitor_idx)?;
+    let workspace_idx = komo_mon.focused_workspace_idx();
+    let komo_ws = komo_mon.workspaces().get(workspace_idx)?;
+    Some(workspace_key(komo_mon, monitor_idx, workspace_idx, komo_ws))
+}
+
+/// The hwnd of the window komorebi currently has focused, honouring maximized and monocle.
+pub fn focused_window_hwnd(state: &State) -> Option<isize> {
+    let komo_mon = state.monitors.focused()?;
+    let komo_ws = komo_mon
+        .workspaces()
+        .get(komo_mon.focused_workspace_idx())?;
+    if let Some(maximized) = komo_ws.maximized_window() {
+        return Some(maximized.hwnd);
+    }
+    let container = match komo_ws.monocle_container() {
+        Some(monocle) => Some(monocle),
+        None => komo_ws.focused_container(),
+    };
+    container
+        .and_then(|container| container.focused_window())
+        .map(|window| window.hwnd)
+}
+
+pub fn query_focused_window(world: &World) -> Option<Entity> {
+    let state = world.get_resource::<KomorebiState>()?.komorebi.as_ref()?;
+    let hwnd = focused_window_hwnd(state)?;
+    world
+        .get_resource::<WindowToEntityMap>()?
+        .0
+        .get(&hwnd.to_string())
+        .copied()
+}
+
+pub fn query_focused_workspace(world: &World) -> Option<Entity> {
+    let state = world.get_resource::<KomorebiState>()?.komorebi.as_ref()?;
+    let key = focused_workspace_key(state)?;
+    world
+        .get_resource::<WorkspaceToEntityMap>()?
+        .0
+        .get(&key)
+        .copied()
+}
+
+/// Tiled and floating windows of the named workspace, in registry order.
+pub fn query_windows_on_workspace(world: &World, name: &str) -> Vec<Entity> {
+    let (Some(registry), Some(workspace_map)) = (
+        world.get_resource::<RelationRegistry>(),
+        world.get_resource::<WorkspaceToEntityMap>(),
+    ) else {
+        return Vec::new();
+    };
+    let Some(workspace) = workspace_map
+        .0
+        .get(name)
+        .and_then(|entity| registry.record(*entity))
+    else {
+        return Vec::new();
+    };
+    registry
+        .workspace_subtree(workspace.monitor, workspace.workspace)
+        .filter(|record| record.window != 0)
+        .map(|record| record.entity)
+        .collect()
+}
+
+pub fn query_workspace_of(world: &World, window: Entity) -> Option<Entity> {
+    let registry = world.get_resource::<RelationRegistry>()?;
+    let record = registry.record(window)?;
+    if record.workspace == 0 {
+        return None;
+    }
+    registry.entity_at((record.monitor, record.workspace, 0, 0))
+}
+
+pub fn query_monitor_by_serial(world: &World, serial: &str) -> Option<Entity> {
+    world
+        .get_resource::<MonitorToEntityMap>()?
+        .0
+        .get(serial)
+        .copied()
+}
+
+/// Windows whose executable and title contain the given filters, case-insensitively.
+///
+/// A filter that is `None` matches every window.
+pub fn query_find_windows(world: &World, exe: Option<&str>, title: Option<&str>) -> Vec<Entity> {
+    let Some(registry) = world.get_resource::<RelationRegistry>() else {
+        return Vec::new();
+    };
+    let exe = exe.map(str::to_lowercase);
+    let title = title.map(str::to_lowercase);
+    registry
+        .records
+        .iter()
+        .filter(|record| record.window != 0)
+        .filter(|record| {
+            let Some(window) = world.get::<Window>(record.entity) else {
+                return false;
+            };
+            window_matches(*window, exe.as_deref(), title.as_deref())
+        })
+        .map(|record| record.entity)
+        .collect()
+}
+
+fn window_matches(window: Window, exe: Option<&str>, title: Option<&str>) -> bool {
+    let matches = |filter: Option<&str>, value: Option<String>| match filter {
+        None => true,
+        Some(filter) => value.is_some_and(|value| value.to_lowercase().contains(filter)),
+    };
+    matches(exe, window.exe().ok()) && matches(title, window.title().ok())
+}
@@ -4,6 +4,7 @@ use bevy_reflect::Reflect;
 use indexmap::IndexSet;
 use std::borrow::Borrow;
 use std::cmp::{Eq, Ordering};
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Clone, Reflect, Copy)]
@@ -56,20 +57,45 @@ impl Ord for EntityRecord {
 }
 
 impl EntityRecord {
+    pub fn new(
+        entity: Entity,
+        monitor: usize,
+        workspace: usize,
+        container: usize,
+        window: usize,
+    ) -> Self {
+        Self {
+            entity,
+            monitor,
+            workspace,
+            container,
+            window,
+        }
+    }
+
     /// The canonical key used for ordering.
-    pub fn key(&self) -> (usize, usize, usize, usize) {
+    pub fn key(&self) -> RecordPath {
         (self.monitor, self.workspace, self.container, self.window)
     }
 }
 
+/// A position path `(monitor, workspace, container, window)`, 1-based with 0 meaning n/a.
+pub type RecordPath = (usize, usize, usize, usize);
+
 #[derive(Default, Debug, Clone, Reflect, PartialEq, Eq, Resource)]
 pub struct RelationRegistry {
-    /// The set of all records.
+    /// The set of all records, sorted by their path.
     #[reflect(ignore)]
     pub records: IndexSet<EntityRecord>,
+    /// Secondary index from position path to entity.
+    #[reflect(ignore)]
+    paths: HashMap<RecordPath, Entity>,
 }
 
 impl RelationRegistry {
+    /// Inserts a single record at its sorted position, replacing any previous record of the entity.
+    ///
+    /// Use [`RelationRegistry::rebuild`] when replacing the whole hierarchy.
     pub fn insert(
         &mut self,
         entity: Entity,
@@ -78,16 +104,110 @@ impl RelationRegistry {
         container: usize,
         window: usize,
     ) {
-        let record = EntityRecord {
-            entity,
-            monitor,
-            workspace,
-            container,
-            window,
-        };
+        let record = EntityRecord::new(entity, monitor, workspace, container, window);
+        if let Some(previous) = self.records.shift_take(&entity) {
+            self.paths.remove(&previous.key());
+        }
+        let idx = self
+            .records
+            .as_slice()
+            .partition_point(|existing| existing < &record);
+        self.records.shift_insert(idx, record);
+        self.paths.insert(record.key(), entity);
+    }
+
+    /// Replaces every record at once, sorting a single time and rebuilding the path index.
+    pub fn rebuild(&mut self, mut records: Vec<EntityRecord>) {
+        records.sort_unstable();
+        self.paths = records
+            .iter()
+            .map(|record| (record.key(), record.entity))
+            .collect();
+        self.records = records.into_iter().collect();
+    }
 
-        // Maintain sorted order using IndexSet's insertion order
-        self.records.insert(record);
-        self.records.sort_unstable();
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.paths.clear();
+    }
+
+    /// The record of `entity`, if it is part of the hierarchy.
+    pub fn record(&self, entity: Entity) -> Option<&EntityRecord> {
+        self.records.get(&entity)
+    }
+
+    /// The entity recorded at `(monitor, workspace, container, window)`.
+    pub fn entity_at(&self, path: RecordPath) -> Option<Entity> {
+        self.paths.get(&path).copied()
+    }
+
+    /// All records whose path lies in `start..=end`, in sorted order.
+    pub fn range(
+        &self,
+        start: RecordPath,
+        end: RecordPath,
+    ) -> impl Iterator<Item = &EntityRecord> + '_ {
+        let slice = self.records.as_slice();
+        let lower = slice.partition_point(|record| record.key() < start);
+        let upper = slice.partition_point(|record| record.key() <= end);
+        slice[lower..upper.max(lower)].iter()
+    }
+
+    /// The monitor, its workspaces and everything below them.
+    pub fn monitor_subtree(&self, monitor: usize) -> impl Iterator<Item = &EntityRecord> + '_ {
+        self.range(
+            (monitor, 0, 0, 0),
+            (monitor, usize::MAX, usize::MAX, usize::MAX),
+        )
+    }
+
+    /// The workspace, its containers and all of its tiled and floating windows.
+    pub fn workspace_subtree(
+        &self,
+        monitor: usize,
+        workspace: usize,
+    ) -> impl Iterator<Item = &EntityRecord> + '_ {
+        self.range(
+            (monitor, workspace, 0, 0),
+            (monitor, workspace, usize::MAX, usize::MAX),
+        )
+    }
+
+    pub fn workspaces_of(&self, monitor: usize) -> impl Iterator<Item = &EntityRecord> + '_ {
+        self.monitor_subtree(monitor)
+            .filter(|record| record.workspace != 0 && record.container == 0 && record.window == 0)
+    }
+
+    pub fn containers_of(
+        &self,
+        monitor: usize,
+        workspace: usize,
+    ) -> impl Iterator<Item = &EntityRecord> + '_ {
+        self.workspace_subtree(monitor, workspace)
+            .filter(|record| record.container != 0 && record.window == 0)
+    }
+
+    /// Floating windows are recorded with container index 0, directly after their workspace.
+    pub fn floating_windows_of(
+        &self,
+        monitor: usize,
+        workspace: usize,
+    ) -> impl Iterator<Item = &EntityRecord> + '_ {
+        self.range(
+            (monitor, workspace, 0, 1),
+            (monitor, workspace, 0, usize::MAX),
+        )
+    }
+
+    pub fn windows_of_container(
+        &self,
+        monitor: usize,
+        workspace: usize,
+        container: usize,
+    ) -> impl Iterator<Item = &EntityRecord> + '_ {
+        self.range(
+            (monitor, workspace, container, 1),
+            (monitor, workspace, container, usize::MAX),
+        )
     }
 }
@@ -1,5 +1,6 @@
 use bevy_ecs::component::ComponentId;
 use bevy_ecs::entity::Entity;
+use bevy_ecs::reflect::ReflectResource;
 use bevy_ecs::system::Resource;
 use bevy_reflect::Reflect;
 use komorebi_client::{
@@ -7,6 +8,7 @@ use komorebi_client::{
     WindowContainerBehaviour,
 };
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
 #[derive(Resource, Reflect)]
 pub struct AppState {
@@ -58,24 +60,126 @@ pub struct KomorebiState {
     pub komorebi: Option<komorebi_client::State>,
 }
 
+/// Controls whether script edits to the ECS mirror are sent back to komorebi.
+///
+/// Off by default; scripts switch it with `Komotool.set_write_back`.
 #[derive(Resource, Default, Reflect)]
-pub struct KomotoolState {
-    pub current: Option<komorebi_client::State>,
+#[reflect(Resource)]
+pub struct WriteBackState {
+    pub enabled: bool,
+    /// The last state sent, kept until komorebi's notification echoes it back
+    #[reflect(ignore)]
+    pub last_sent: Option<komorebi_client::State>,
 }
 
+/// Window entities ordered by when they last had global focus, most recent first.
+#[derive(Resource, Default, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct FocusHistory {
+    pub windows: Vec<Entity>,
+}
+
+impl FocusHistory {
+    /// Moves the window to the front of the history.
+    pub fn touch(&mut self, window: Entity) {
+        self.windows.retain(|entity| *entity != window);
+        self.windows.insert(0, window);
+    }
+
+    pub fn remove(&mut self, window: Entity) {
+        self.windows.retain(|entity| *entity != window);
+    }
+
+    /// The focused window, if it is known.
+    pub fn current(&self) -> Option<Entity> {
+        self.windows.first().copied()
+    }
+
+    /// The window focused before the current one.
+    pub fn previous(&self) -> Option<Entity> {
+        self.windows.get(1).copied()
+    }
+}
+
+/// A working copy of the static config that scripts edit before applying it to komorebi.
 #[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
 pub struct KomotoolStaticConfig {
     pub config: Option<StaticConfig>,
 }
 
+/// komorebi's `komorebi.json`, loaded and hot-reloaded by the assets plugin.
 #[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
 pub struct KomorebiStaticConfig {
     pub config: Option<StaticConfig>,
 }
 
+/// File name the working static config is written to before komorebi loads it.
+pub const GENERATED_STATIC_CONFIG_FILE: &str = "komorebi.generated.json";
+
+/// Where the working static config is written before komorebi is told to load it.
+#[derive(Resource, Default, Reflect)]
+pub struct StaticConfigOutput {
+    /// Set by the assets plugin to a file in the komotool config directory
+    pub file: Option<PathBuf>,
+}
+
+impl StaticConfigOutput {
+    pub fn path(&self) -> PathBuf {
+        self.file
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join(GENERATED_STATIC_CONFIG_FILE))
+    }
+}
+
+/// Folder below the komotool config directory that session snapshots are saved in.
+pub const SESSIONS_FOLDER: &str = "sessions";
+
+/// Where session snapshots are saved, one `<name>.json` per session.
+#[derive(Resource, Default, Reflect)]
+pub struct SessionDirectory {
+    /// Set by the assets plugin to a folder in the komotool config directory
+    pub dir: Option<PathBuf>,
+}
+
+impl SessionDirectory {
+    pub fn dir(&self) -> PathBuf {
+        self.dir
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join("komotool").join(SESSIONS_FOLDER))
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir().join(format!("{}.json", name))
+    }
+}
+
+/// File below the komotool config directory that tags and meta are persisted to.
+pub const USER_DATA_FILE: &str = "user_data.json";
+
+/// Where [`UserDataStore`](crate::user_data::UserDataStore) is persisted.
+#[derive(Resource, Default, Reflect)]
+pub struct UserDataPath {
+    /// Set by the assets plugin to a file in the komotool config directory
+    pub file: Option<PathBuf>,
+}
+
+impl UserDataPath {
+    pub fn path(&self) -> PathBuf {
+        self.file
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join("komotool").join(USER_DATA_FILE))
+    }
+}
+
+/// Runtime marker components for indices beyond the generated ones, by 1-based index.
 #[derive(Resource, Default, Reflect)]
 pub struct ExtendedMarkerMap {
-    pub makers: HashMap<usize, ComponentId>,
+    pub monitors: HashMap<usize, ComponentId>,
+    pub workspaces: HashMap<usize, ComponentId>,
+    pub containers: HashMap<usize, ComponentId>,
+    pub windows: HashMap<usize, ComponentId>,
 }
 
 #[derive(Resource, Default, Reflect)]
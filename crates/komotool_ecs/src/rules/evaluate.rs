@@ -0,0 +1,332 @@
+use crate::components::FloatingWindow;
+use crate::events::{FocusChanged, WindowDespawned, WindowSpawned};
+use crate::relations::{ChildOfMonitor, ChildOfWorkspace, RelationIndex, focused_window_hwnd};
+use crate::resources::{KomorebiState, WindowToEntityMap};
+use crate::rules::rule::{RuleAction, WindowProperties, WindowRule, WindowRules};
+use crate::transport::KomorebiTransport;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::event::EventReader;
+use bevy_ecs::query::Has;
+use bevy_ecs::reflect::{AppTypeRegistry, ReflectCommandExt, ReflectComponent, ReflectFromWorld};
+use bevy_ecs::system::{Commands, Local, Query, Res};
+use bevy_ecs::world::EntityRef;
+use bevy_reflect::std_traits::ReflectDefault;
+use bevy_reflect::{
+    DynamicStruct, DynamicTupleStruct, PartialReflect, TypeInfo, TypeRegistration, TypeRegistry,
+};
+use komorebi_client::{NotificationEvent, Window, WindowManagerEvent, Workspace};
+use komotool_pipe::PipeNotificationEvent;
+use std::collections::{HashMap, HashSet};
+
+/// Reads the properties of a window that rule conditions are checked against.
+pub fn window_properties(
+    window: Window,
+    monitor: Option<&RelationIndex>,
+    workspace: Option<&Workspace>,
+) -> WindowProperties {
+    WindowProperties {
+        exe: window.exe().ok(),
+        class: window.class().ok(),
+        title: window.title().ok(),
+        monitor: monitor
+            .map(|index| index.monitor)
+            .filter(|monitor| *monitor > 0),
+        workspace: workspace.and_then(|workspace| workspace.name().clone()),
+    }
+}
+
+/// The rules that match, in evaluation order.
+pub fn matching_rules<'a>(
+    rules: &'a WindowRules,
+    properties: &'a WindowProperties,
+) -> impl Iterator<Item = &'a WindowRule> {
+    rules.iter().filter(|rule| rule.matches(properties))
+}
+
+/// Looks up a component for `insert` and `remove` actions by short or full type path.
+pub fn reflected_component<'a>(
+    registry: &'a TypeRegistry,
+    type_path: &str,
+) -> Option<&'a TypeRegistration> {
+    registry
+        .get_with_short_type_path(type_path)
+        .or_else(|| registry.get_with_type_path(type_path))
+        .filter(|registration| registration.data::<ReflectComponent>().is_some())
+}
+
+/// Applies window rules to windows that spawned, changed title or gained focus.
+///
+/// A rule runs once each time it starts matching a window, so a title that changes back and
+/// forth re-applies it. Rules with socket actions wait until their window is komorebi's
+/// focused window, because komorebi's commands act on the focused window. This runs after the
+/// state import and before events are forwarded to scripts, so scripts see the result.
+#[allow(clippy::too_many_arguments)]
+pub fn evaluate_window_rules(
+    mut commands: Commands,
+    rules: Res<WindowRules>,
+    komorebi_state: Res<KomorebiState>,
+    window_map: Res<WindowToEntityMap>,
+    transport: Res<KomorebiTransport>,
+    type_registry: Res<AppTypeRegistry>,
+    windows: Query<(
+        &Window,
+        Option<&ChildOfMonitor>,
+        Option<&ChildOfWorkspace>,
+        Has<FloatingWindow>,
+    )>,
+    monitors: Query<&RelationIndex>,
+    workspaces: Query<&Workspace>,
+    entities: Query<EntityRef>,
+    mut spawned: EventReader<WindowSpawned>,
+    mut despawned: EventReader<WindowDespawned>,
+    mut focus_changed: EventReader<FocusChanged>,
+    mut notifications: EventReader<PipeNotificationEvent>,
+    mut applied: Local<HashMap<Entity, HashSet<String>>>,
+) {
+    for event in despawned.read() {
+        applied.remove(&event.entity);
+    }
+    if rules.is_changed() {
+        applied.clear();
+    }
+
+    let mut candidates: Vec<Entity> = spawned.read().map(|event| event.entity).collect();
+    candidates.extend(
+        focus_changed
+            .read()
+            .filter_map(|event| event.to.map(|to| to.entity)),
+    );
+    candidates.extend(
+        notifications
+            .read()
+            .filter_map(|event| match &event.notification.event {
+                NotificationEvent::WindowManager(WindowManagerEvent::TitleUpdate(_, window)) => {
+                    window_map.0.get(&window.hwnd.to_string()).copied()
+                }
+                _ => None,
+            }),
+    );
+    if rules.is_empty() || candidates.is_empty() {
+        return;
+    }
+    candidates.sort();
+    candidates.dedup();
+
+    let focused_hwnd = komorebi_state
+        .komorebi
+        .as_ref()
+        .and_then(focused_window_hwnd);
+    let registry = type_registry.read();
+
+    for entity in candidates {
+        let Ok((window, monitor, workspace, floating)) = windows.get(entity) else {
+            continue;
+        };
+        let properties = window_properties(
+            *window,
+            monitor.and_then(|monitor| monitors.get(monitor.0).ok()),
+            workspace.and_then(|workspace| workspaces.get(workspace.0).ok()),
+        );
+        let focused = focused_hwnd == Some(window.hwnd);
+        let applied = applied.entry(entity).or_default();
+
+        let matching: HashSet<String> = matching_rules(&rules, &properties)
+            .map(|rule| rule.name.clone())
+            .collect();
+        applied.retain(|name| matching.contains(name));
+
+        for rule in matching_rules(&rules, &properties) {
+            if applied.contains(&rule.name) {
+                continue;
+            }
+            if !focused && rule.actions.iter().any(RuleAction::is_socket_action) {
+                continue;
+            }
+            log::debug!("Applying window rule '{}' to {}", rule.name, window.hwnd);
+            let messages: Vec<_> = rule
+                .actions
+                .iter()
+                .filter_map(|action| action.message(floating))
+                .collect();
+            if !messages.is_empty() {
+                if let Err(e) = transport.send_all(&messages) {
+                    log::error!("Failed to apply window rule '{}': {}", rule.name, e);
+                }
+            }
+            if let Ok(entity) = entities.get(entity) {
+                for action in &rule.actions {
+                    apply_component_action(&mut commands, &registry, entity, action);
+                }
+            }
+            applied.insert(rule.name.clone());
+        }
+    }
+}
+
+/// Inserts or removes the component an action names. Components the window already has are
+/// left as they are, so inserting `Tags` or `Meta` doesn't reset them.
+fn apply_component_action(
+    commands: &mut Commands,
+    registry: &TypeRegistry,
+    entity: EntityRef,
+    action: &RuleAction,
+) {
+    let (type_path, insert) = match action {
+        RuleAction::Insert(type_path) => (type_path, true),
+        RuleAction::Remove(type_path) => (type_path, false),
+        _ => return,
+    };
+    let Some(registration) = reflected_component(registry, type_path) else {
+        log::error!("Window rule names unknown component '{}'", type_path);
+        return;
+    };
+    if !insert {
+        commands
+            .entity(entity.id())
+            .remove_reflect(registration.type_info().type_path());
+        return;
+    }
+    if entity.contains_type_id(registration.type_id()) {
+        return;
+    }
+    // An empty dynamic value only becomes the component through its reflected Default or
+    // FromWorld, unless the component has no fields to fill
+    let constructible = registration.data::<ReflectDefault>().is_some()
+        || registration.data::<ReflectFromWorld>().is_some();
+    let component: Box<dyn PartialReflect> = match registration.type_info() {
+        TypeInfo::Struct(info) if constructible || info.field_len() == 0 => {
+            let mut component = DynamicStruct::default();
+            component.set_represented_type(Some(registration.type_info()));
+            Box::new(component)
+        }
+        TypeInfo::TupleStruct(info) if constructible || info.field_len() == 0 => {
+            let mut component = DynamicTupleStruct::default();
+            component.set_represented_type(Some(registration.type_info()));
+            Box::new(component)
+        }
+        TypeInfo::Struct(_) | TypeInfo::TupleStruct(_) => {
+            log::error!(
+                "Window rule can't insert '{}': it has fields but no reflected Default or FromWorld",
+                type_path
+            );
+            return;
+        }
+        _ => {
+            log::error!(
+                "Window rules can only insert struct components, not '{}'",
+                type_path
+            );
+            return;
+        }
+    };
+    commands.entity(entity.id()).insert_reflect(component);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Meta, Tags};
+    use bevy_ecs::world::{CommandQueue, World};
+    use serde_json::json;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        {
+            let mut registry = world.resource::<AppTypeRegistry>().write();
+            registry.register::<FloatingWindow>();
+            registry.register::<Tags>();
+            registry.register::<Meta>();
+        }
+        world
+    }
+
+    /// Runs `actions` on `entity` the way a matching rule does.
+    fn apply(world: &mut World, entity: Entity, actions: &[RuleAction]) {
+        let mut queue = CommandQueue::default();
+        {
+            let registry = world.resource::<AppTypeRegistry>().read();
+            let mut commands = Commands::new(&mut queue, world);
+            for action in actions {
+                apply_component_action(&mut commands, &registry, world.entity(entity), action);
+            }
+        }
+        queue.apply(world);
+    }
+
+    #[test]
+    fn components_are_found_by_short_or_full_type_path() {
+        let world = world();
+        let registry = world.resource::<AppTypeRegistry>().read();
+        for type_path in ["FloatingWindow", std::any::type_name::<FloatingWindow>()] {
+            assert!(reflected_component(&registry, type_path).is_some());
+        }
+        // Registered, but not a component
+        assert!(reflected_component(&registry, "String").is_none());
+        assert!(reflected_component(&registry, "Floating").is_none());
+    }
+
+    #[test]
+    fn insert_and_remove_change_the_window() {
+        let mut world = world();
+        let entity = world.spawn(Meta::default()).id();
+        apply(
+            &mut world,
+            entity,
+            &[
+                RuleAction::Insert("FloatingWindow".to_string()),
+                RuleAction::Insert("Tags".to_string()),
+                RuleAction::Remove("Meta".to_string()),
+            ],
+        );
+        assert!(world.entity(entity).contains::<FloatingWindow>());
+        assert_eq!(world.entity(entity).get::<Tags>(), Some(&Tags::default()));
+        assert!(!world.entity(entity).contains::<Meta>());
+    }
+
+    #[test]
+    fn inserting_a_component_the_window_has_keeps_its_data() {
+        let mut world = world();
+        let tags = Tags(vec!["pinned".to_string()]);
+        let meta = Meta([("project".to_string(), "komotool".to_string())].into());
+        let entity = world.spawn((tags.clone(), meta.clone())).id();
+        apply(
+            &mut world,
+            entity,
+            &[
+                RuleAction::Insert("Tags".to_string()),
+                RuleAction::Insert("Meta".to_string()),
+            ],
+        );
+        assert_eq!(world.entity(entity).get::<Tags>(), Some(&tags));
+        assert_eq!(world.entity(entity).get::<Meta>(), Some(&meta));
+    }
+
+    #[test]
+    fn matching_rules_keep_their_order() -> Result<(), String> {
+        let mut rules = WindowRules::default();
+        rules.set_file(
+            "rules.json".to_string(),
+            crate::rules::parse_rule_file(
+                "rules.json",
+                &json!({
+                    "rules": [
+                        { "name": "any" },
+                        { "name": "code", "exe": "code" },
+                        { "name": "editor", "title": "Visual Studio Code$" },
+                    ]
+                }),
+            )?,
+        );
+        let properties = WindowProperties {
+            exe: Some("notepad.exe".to_string()),
+            title: Some("notes.txt - Visual Studio Code".to_string()),
+            ..WindowProperties::default()
+        };
+        let names: Vec<&str> = matching_rules(&rules, &properties)
+            .map(|rule| rule.name.as_str())
+            .collect();
+        assert_eq!(names, ["any", "editor"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+pub mod evaluate;
+pub mod rule;
+
+pub use evaluate::*;
+pub use rule::*;
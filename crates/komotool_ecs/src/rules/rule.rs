@@ -0,0 +1,320 @@
+use bevy_ecs::system::Resource;
+use komorebi_client::SocketMessage;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A rule as written in a rules file.
+///
+/// Every condition that is set must match; a rule without conditions matches every window.
+/// `exe`, `class` and `title` are regular expressions, e.g. `"(?i)^firefox\\.exe$"`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RuleDefinition {
+    pub name: Option<String>,
+    pub exe: Option<String>,
+    pub class: Option<String>,
+    pub title: Option<String>,
+    /// 1-based index of the monitor the window is on
+    pub monitor: Option<usize>,
+    /// Name of the workspace the window is on
+    pub workspace: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<RuleAction>,
+}
+
+/// What a matching rule does to its window.
+///
+/// Socket actions act on komorebi's focused window, so they wait until the window has focus.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Moves the window to the named workspace and follows it
+    MoveToWorkspace(String),
+    /// Sends the window to the named workspace without following it
+    SendToWorkspace(String),
+    /// Moves the window to the 1-based monitor
+    MoveToMonitor(usize),
+    /// Floats the window unless it already floats
+    Float,
+    /// Sends any komorebi message, e.g. `{ send = { type = "ToggleMonocle" } }`.
+    ///
+    /// komorebi's transparency is global rather than per window: `Transparency` and
+    /// `TransparencyAlpha` turn on and tune the transparency of every unfocused window, so a
+    /// rule can't make only its own window transparent.
+    Send(SocketMessage),
+    /// Inserts a reflected component by short or full type path, e.g. `"FloatingWindow"`.
+    /// The component must be a unit struct or reflect `Default`.
+    Insert(String),
+    /// Removes a reflected component by short or full type path
+    Remove(String),
+}
+
+impl RuleAction {
+    /// The komorebi message for socket actions, `None` for component actions and for
+    /// floating a window that already floats.
+    pub fn message(&self, floating: bool) -> Option<SocketMessage> {
+        match self {
+            Self::MoveToWorkspace(name) => {
+                Some(SocketMessage::MoveContainerToNamedWorkspace(name.clone()))
+            }
+            Self::SendToWorkspace(name) => {
+                Some(SocketMessage::SendContainerToNamedWorkspace(name.clone()))
+            }
+            Self::MoveToMonitor(monitor) => Some(SocketMessage::MoveContainerToMonitorNumber(
+                monitor.saturating_sub(1),
+            )),
+            Self::Float => (!floating).then_some(SocketMessage::ToggleFloat),
+            Self::Send(message) => Some(message.clone()),
+            Self::Insert(_) | Self::Remove(_) => None,
+        }
+    }
+
+    pub fn is_socket_action(&self) -> bool {
+        !matches!(self, Self::Insert(_) | Self::Remove(_))
+    }
+}
+
+/// The window properties rule conditions are checked against.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowProperties {
+    pub exe: Option<String>,
+    pub class: Option<String>,
+    pub title: Option<String>,
+    pub monitor: Option<usize>,
+    pub workspace: Option<String>,
+}
+
+/// A [`RuleDefinition`] with its patterns compiled.
+#[derive(Debug, Clone)]
+pub struct WindowRule {
+    pub name: String,
+    pub exe: Option<Regex>,
+    pub class: Option<Regex>,
+    pub title: Option<Regex>,
+    pub monitor: Option<usize>,
+    pub workspace: Option<String>,
+    pub actions: Vec<RuleAction>,
+}
+
+impl WindowRule {
+    /// Compiles a definition; `default_name` is used when it has no `name`.
+    pub fn compile(definition: RuleDefinition, default_name: String) -> Result<Self, String> {
+        let name = definition.name.unwrap_or(default_name);
+        let compile = |pattern: Option<String>, field: &str| {
+            pattern
+                .map(|pattern| Regex::new(&pattern))
+                .transpose()
+                .map_err(|e| format!("Rule '{}' has an invalid {} pattern: {}", name, field, e))
+        };
+        Ok(Self {
+            exe: compile(definition.exe, "exe")?,
+            class: compile(definition.class, "class")?,
+            title: compile(definition.title, "title")?,
+            monitor: definition.monitor,
+            workspace: definition.workspace,
+            actions: definition.actions,
+            name,
+        })
+    }
+
+    pub fn matches(&self, properties: &WindowProperties) -> bool {
+        let pattern_matches = |pattern: &Option<Regex>, value: &Option<String>| match pattern {
+            None => true,
+            Some(pattern) => value
+                .as_deref()
+                .is_some_and(|value| pattern.is_match(value)),
+        };
+        pattern_matches(&self.exe, &properties.exe)
+            && pattern_matches(&self.class, &properties.class)
+            && pattern_matches(&self.title, &properties.title)
+            && self
+                .monitor
+                .is_none_or(|monitor| properties.monitor == Some(monitor))
+            && self
+                .workspace
+                .as_ref()
+                .is_none_or(|workspace| properties.workspace.as_ref() == Some(workspace))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    #[serde(default)]
+    rules: Vec<RuleDefinition>,
+}
+
+/// Parses a rules file, `{ "rules": [ ... ] }`. Unnamed rules are called `<file>#<n>`.
+pub fn parse_rule_file(file: &str, value: &Value) -> Result<Vec<WindowRule>, String> {
+    let rule_file = serde_json::from_value::<RuleFile>(value.clone())
+        .map_err(|e| format!("Invalid rules file: {}", e))?;
+    rule_file
+        .rules
+        .into_iter()
+        .enumerate()
+        .map(|(idx, definition)| WindowRule::compile(definition, format!("{}#{}", file, idx + 1)))
+        .collect()
+}
+
+/// Window rules by the file they came from.
+///
+/// Rules are evaluated file by file in path order, then in the order they are written.
+#[derive(Resource, Default)]
+pub struct WindowRules {
+    files: BTreeMap<String, Vec<WindowRule>>,
+}
+
+impl WindowRules {
+    pub fn set_file(&mut self, file: String, rules: Vec<WindowRule>) {
+        self.files.insert(file, rules);
+    }
+
+    pub fn remove_file(&mut self, file: &str) {
+        self.files.remove(file);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &WindowRule> {
+        self.files.values().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(value: Value) -> Vec<WindowRule> {
+        parse_rule_file("rules/apps.json", &value).unwrap_or_default()
+    }
+
+    fn properties(exe: &str, title: &str) -> WindowProperties {
+        WindowProperties {
+            exe: Some(exe.to_string()),
+            class: Some("MozillaWindowClass".to_string()),
+            title: Some(title.to_string()),
+            monitor: Some(1),
+            workspace: Some("web".to_string()),
+        }
+    }
+
+    #[test]
+    fn unnamed_rules_are_named_after_their_file_and_position() {
+        let rules = rules(json!({
+            "rules": [
+                { "name": "browser", "exe": "firefox" },
+                { "exe": "code" },
+            ]
+        }));
+        let names: Vec<&str> = rules.iter().map(|rule| rule.name.as_str()).collect();
+        assert_eq!(names, ["browser", "rules/apps.json#2"]);
+    }
+
+    #[test]
+    fn actions_are_read_by_snake_case_name() {
+        let rules = rules(json!({
+            "rules": [{
+                "actions": [
+                    { "move_to_workspace": "dev" },
+                    { "send_to_workspace": "chat" },
+                    { "move_to_monitor": 2 },
+                    "float",
+                    { "send": { "type": "ToggleMonocle" } },
+                    { "insert": "FloatingWindow" },
+                    { "remove": "Tags" },
+                ]
+            }]
+        }));
+        assert_eq!(rules.len(), 1);
+        let Some(rule) = rules.first() else {
+            return;
+        };
+        assert!(matches!(
+            rule.actions.as_slice(),
+            [
+                RuleAction::MoveToWorkspace(move_to),
+                RuleAction::SendToWorkspace(send_to),
+                RuleAction::MoveToMonitor(2),
+                RuleAction::Float,
+                RuleAction::Send(SocketMessage::ToggleMonocle),
+                RuleAction::Insert(insert),
+                RuleAction::Remove(remove),
+            ] if move_to == "dev" && send_to == "chat" && insert == "FloatingWindow" && remove == "Tags"
+        ));
+    }
+
+    #[test]
+    fn invalid_rule_files_are_rejected() {
+        let unknown_field = json!({ "rules": [{ "exe": "code", "process": "code" }] });
+        assert!(parse_rule_file("a.json", &unknown_field).is_err());
+
+        let unknown_action = json!({ "rules": [{ "actions": ["minimize"] }] });
+        assert!(parse_rule_file("a.json", &unknown_action).is_err());
+
+        let bad_pattern = json!({ "rules": [{ "name": "broken", "title": "(" }] });
+        let error = parse_rule_file("a.json", &bad_pattern)
+            .err()
+            .unwrap_or_default();
+        assert!(error.contains("Rule 'broken' has an invalid title pattern"));
+    }
+
+    #[test]
+    fn every_set_condition_has_to_match() {
+        let rules = rules(json!({
+            "rules": [
+                { "exe": "(?i)^firefox\\.exe$", "title": "Mozilla", "monitor": 1, "workspace": "web" },
+                { "exe": "firefox", "monitor": 2 },
+                { "workspace": "dev" },
+                {},
+            ]
+        }));
+        let window = properties("Firefox.exe", "Inbox - Mozilla Firefox");
+        let matching: Vec<bool> = rules.iter().map(|rule| rule.matches(&window)).collect();
+        assert_eq!(matching, [true, false, false, true]);
+    }
+
+    #[test]
+    fn patterns_never_match_unknown_properties() {
+        let rules = rules(json!({ "rules": [{ "title": ".*" }, { "class": "Mozilla" }] }));
+        let window = WindowProperties::default();
+        assert!(rules.iter().all(|rule| !rule.matches(&window)));
+    }
+
+    #[test]
+    fn socket_messages_follow_the_window() {
+        assert!(RuleAction::Float.message(true).is_none());
+        assert!(matches!(
+            RuleAction::Float.message(false),
+            Some(SocketMessage::ToggleFloat)
+        ));
+        assert!(matches!(
+            RuleAction::MoveToMonitor(1).message(false),
+            Some(SocketMessage::MoveContainerToMonitorNumber(0))
+        ));
+        let insert = RuleAction::Insert("FloatingWindow".to_string());
+        assert!(insert.message(false).is_none());
+        assert!(!insert.is_socket_action());
+    }
+
+    #[test]
+    fn rules_are_evaluated_in_file_order() {
+        let mut window_rules = WindowRules::default();
+        let named = |name: &str| rules(json!({ "rules": [{ "name": name }] }));
+        window_rules.set_file("b.json".to_string(), named("second"));
+        window_rules.set_file("a.json".to_string(), named("first"));
+        let names: Vec<&str> = window_rules.iter().map(|rule| rule.name.as_str()).collect();
+        assert_eq!(names, ["first", "second"]);
+
+        window_rules.remove_file("a.json");
+        assert_eq!(window_rules.len(), 1);
+    }
+}
@@ -0,0 +1,476 @@
+use crate::layouts::{CustomLayout, LayoutEngines, PendingLayouts};
+use crate::relations::queries::*;
+use crate::resources::{
+    KomorebiStaticConfig, KomotoolStaticConfig, SessionDirectory, StaticConfigOutput,
+    WorkspaceToEntityMap, WriteBackState,
+};
+use crate::sessions::{format_restore, list_sessions, restore_session, save_session};
+use crate::shadow::{ShadowResync, switch_outgoing_mode};
+use crate::systems::{StaticConfigApplyMode, apply_working_static_config, diff_static_configs};
+use crate::transport::KomorebiTransport;
+use crate::undo::{HistoryDirection, step_history};
+use crate::user_data::{
+    add_entity_tag, entity_meta, entity_tags, query_tagged, query_with_meta, remove_entity_tag,
+    set_entity_meta,
+};
+use crate::validation::{InvalidStatePolicy, StateValidation};
+use bevy_app::App;
+use bevy_ecs::change_detection::Mut;
+use bevy_ecs::entity::Entity;
+use bevy_mod_scripting::core::bindings::function::from::Val;
+use bevy_mod_scripting::core::bindings::function::namespace::NamespaceBuilder;
+use bevy_mod_scripting::core::bindings::function::script_function::FunctionCallContext;
+use bevy_mod_scripting::core::error::InteropError;
+use komorebi_client::Rect;
+use komotool_utils::komorebi_commands::{KomorebiOutgoing, OutgoingMode};
+use komotool_utils::script_functions::Komotool;
+use komotoolc_pipe::DEFAULT_SESSION;
+use std::collections::HashMap;
+
+/// Registers the hierarchy query helpers on the `Komotool` namespace.
+///
+/// The query helpers return entity handles, so scripts can read the reflected components with
+/// the usual world functions instead of building dynamic queries over marker components.
+pub fn register_query_functions(app: &mut App) {
+    NamespaceBuilder::<Komotool>::new(app.world_mut())
+        .register(
+            "focused_window",
+            |ctxt: FunctionCallContext| -> Result<Option<Val<Entity>>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| query_focused_window(world).map(Val))
+            },
+        )
+        .register(
+            "focused_workspace",
+            |ctxt: FunctionCallContext| -> Result<Option<Val<Entity>>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| query_focused_workspace(world).map(Val))
+            },
+        )
+        .register(
+            "windows_on_workspace",
+            |ctxt: FunctionCallContext, name: String| -> Result<Vec<Val<Entity>>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| {
+                    query_windows_on_workspace(world, &name)
+                        .into_iter()
+                        .map(Val)
+                        .collect()
+                })
+            },
+        )
+        .register(
+            "workspace_of",
+            |ctxt: FunctionCallContext,
+             window: Val<Entity>|
+             -> Result<Option<Val<Entity>>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| query_workspace_of(world, *window).map(Val))
+            },
+        )
+        .register(
+            "monitor_by_serial",
+            |ctxt: FunctionCallContext,
+             serial: String|
+             -> Result<Option<Val<Entity>>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| query_monitor_by_serial(world, &serial).map(Val))
+            },
+        )
+        .register(
+            "find_windows",
+            |ctxt: FunctionCallContext,
+             filter: HashMap<String, String>|
+             -> Result<Vec<Val<Entity>>, InteropError> {
+                let world = ctxt.world()?;
+                let exe = filter.get("exe").map(String::as_str);
+                let title = filter.get("title").map(String::as_str);
+                world.with_global_access(|world| {
+                    query_find_windows(world, exe, title)
+                        .into_iter()
+                        .map(Val)
+                        .collect()
+                })
+            },
+        );
+}
+
+/// Registers the switch for writing ECS edits back to komorebi.
+pub fn register_write_back_functions(app: &mut App) {
+    NamespaceBuilder::<Komotool>::new(app.world_mut())
+        .register(
+            "write_back_enabled",
+            |ctxt: FunctionCallContext| -> Result<bool, InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource(|write_back: &WriteBackState| write_back.enabled)
+            },
+        )
+        .register(
+            "set_write_back",
+            |ctxt: FunctionCallContext, enabled: bool| -> Result<(), InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource_mut(|mut write_back: Mut<WriteBackState>| {
+                    write_back.enabled = enabled;
+                    write_back.last_sent = None;
+                })
+            },
+        );
+}
+
+/// Registers the functions that diff, apply and reset the working static config.
+pub fn register_static_config_functions(app: &mut App) {
+    NamespaceBuilder::<Komotool>::new(app.world_mut())
+        .register(
+            "static_config_diff",
+            |ctxt: FunctionCallContext| -> Result<Vec<String>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| {
+                    let (Some(current), Some(working)) = (
+                        world.get_resource::<KomorebiStaticConfig>(),
+                        world.get_resource::<KomotoolStaticConfig>(),
+                    ) else {
+                        return Vec::new();
+                    };
+                    diff_static_configs(current.config.as_ref(), working.config.as_ref())
+                        .iter()
+                        .map(|change| change.to_string())
+                        .collect()
+                })
+            },
+        )
+        .register(
+            "apply_static_config",
+            |ctxt: FunctionCallContext, mode: Option<String>| -> Result<bool, InteropError> {
+                let mode = match mode.as_deref().map(StaticConfigApplyMode::parse) {
+                    None => StaticConfigApplyMode::default(),
+                    Some(Some(mode)) => mode,
+                    Some(None) => {
+                        eprintln!("Invalid static config apply mode: {:?}", mode);
+                        return Ok(false);
+                    }
+                };
+                let world = ctxt.world()?;
+                world.with_global_access(|world| {
+                    let (Some(working), Some(current), Some(output), Some(transport)) = (
+                        world.get_resource::<KomotoolStaticConfig>(),
+                        world.get_resource::<KomorebiStaticConfig>(),
+                        world.get_resource::<StaticConfigOutput>(),
+                        world.get_resource::<KomorebiTransport>(),
+                    ) else {
+                        return false;
+                    };
+                    match apply_working_static_config(working, current, output, mode, transport) {
+                        Ok(_) => true,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            false
+                        }
+                    }
+                })
+            },
+        )
+        .register(
+            "reset_static_config",
+            |ctxt: FunctionCallContext| -> Result<(), InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| {
+                    let current = world
+                        .get_resource::<KomorebiStaticConfig>()
+                        .and_then(|current| current.config.clone());
+                    if let Some(mut working) = world.get_resource_mut::<KomotoolStaticConfig>() {
+                        working.config = current;
+                    }
+                })
+            },
+        );
+}
+
+/// Registers the custom layout functions.
+pub fn register_layout_functions(app: &mut App) {
+    NamespaceBuilder::<Komotool>::new(app.world_mut())
+        .register(
+            "set_custom_layout",
+            |ctxt: FunctionCallContext,
+             workspace: String,
+             layout: Option<String>|
+             -> Result<bool, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| {
+                    let Some(entity) = world
+                        .get_resource::<WorkspaceToEntityMap>()
+                        .and_then(|map| map.0.get(&workspace).copied())
+                    else {
+                        eprintln!("Unknown workspace: {}", workspace);
+                        return false;
+                    };
+                    let Ok(mut entity) = world.get_entity_mut(entity) else {
+                        return false;
+                    };
+                    match layout {
+                        Some(name) => {
+                            entity.insert(CustomLayout { name });
+                        }
+                        None => {
+                            entity.remove::<CustomLayout>();
+                        }
+                    }
+                    true
+                })
+            },
+        )
+        .register(
+            "layout_engines",
+            |ctxt: FunctionCallContext| -> Result<Vec<String>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource(|engines: &LayoutEngines| engines.names())
+            },
+        )
+        .register(
+            "apply_layout",
+            |ctxt: FunctionCallContext,
+             workspace: Val<Entity>,
+             rects: Vec<HashMap<String, i64>>|
+             -> Result<bool, InteropError> {
+                let workspace = *workspace;
+                let rects = rects
+                    .iter()
+                    .map(|rect| {
+                        let field = |key: &str| rect.get(key).copied().unwrap_or_default() as i32;
+                        Rect {
+                            left: field("left"),
+                            top: field("top"),
+                            right: field("right"),
+                            bottom: field("bottom"),
+                        }
+                    })
+                    .collect();
+                let world = ctxt.world()?;
+                world.with_resource_mut(|mut pending: Mut<PendingLayouts>| {
+                    pending.0.insert(workspace, rects);
+                    true
+                })
+            },
+        );
+}
+
+/// Registers the session save and restore functions.
+pub fn register_session_functions(app: &mut App) {
+    NamespaceBuilder::<Komotool>::new(app.world_mut())
+        .register(
+            "save_session",
+            |ctxt: FunctionCallContext, name: Option<String>| -> Result<bool, InteropError> {
+                let name = name.unwrap_or_else(|| DEFAULT_SESSION.to_string());
+                let world = ctxt.world()?;
+                world.with_global_access(|world| match save_session(world, &name) {
+                    Ok(_) => true,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        false
+                    }
+                })
+            },
+        )
+        .register(
+            "restore_session",
+            |ctxt: FunctionCallContext, name: Option<String>| -> Result<bool, InteropError> {
+                let name = name.unwrap_or_else(|| DEFAULT_SESSION.to_string());
+                let world = ctxt.world()?;
+                world.with_global_access(|world| match restore_session(world, &name) {
+                    Ok(plan) => {
+                        println!("{}", format_restore(&name, &plan));
+                        true
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        false
+                    }
+                })
+            },
+        )
+        .register(
+            "sessions",
+            |ctxt: FunctionCallContext| -> Result<Vec<String>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource(|sessions: &SessionDirectory| list_sessions(&sessions.dir()))
+            },
+        );
+}
+
+/// Registers undo and redo over the komorebi state history.
+pub fn register_undo_functions(app: &mut App) {
+    NamespaceBuilder::<Komotool>::new(app.world_mut())
+        .register(
+            "undo",
+            |ctxt: FunctionCallContext| -> Result<bool, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| {
+                    match step_history(world, HistoryDirection::Undo) {
+                        Ok(_) => true,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            false
+                        }
+                    }
+                })
+            },
+        )
+        .register(
+            "redo",
+            |ctxt: FunctionCallContext| -> Result<bool, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| {
+                    match step_history(world, HistoryDirection::Redo) {
+                        Ok(_) => true,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            false
+                        }
+                    }
+                })
+            },
+        );
+}
+
+/// Registers the tag and metadata functions.
+pub fn register_user_data_functions(app: &mut App) {
+    NamespaceBuilder::<Komotool>::new(app.world_mut())
+        .register(
+            "tags",
+            |ctxt: FunctionCallContext, entity: Val<Entity>| -> Result<Vec<String>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| entity_tags(world, *entity))
+            },
+        )
+        .register(
+            "has_tag",
+            |ctxt: FunctionCallContext,
+             entity: Val<Entity>,
+             tag: String|
+             -> Result<bool, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| entity_tags(world, *entity).contains(&tag))
+            },
+        )
+        .register(
+            "add_tag",
+            |ctxt: FunctionCallContext,
+             entity: Val<Entity>,
+             tag: String|
+             -> Result<bool, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| add_entity_tag(world, *entity, tag))
+            },
+        )
+        .register(
+            "remove_tag",
+            |ctxt: FunctionCallContext,
+             entity: Val<Entity>,
+             tag: String|
+             -> Result<bool, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| remove_entity_tag(world, *entity, &tag))
+            },
+        )
+        .register(
+            "meta",
+            |ctxt: FunctionCallContext,
+             entity: Val<Entity>|
+             -> Result<HashMap<String, String>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| entity_meta(world, *entity))
+            },
+        )
+        .register(
+            "set_meta",
+            |ctxt: FunctionCallContext,
+             entity: Val<Entity>,
+             key: String,
+             value: Option<String>|
+             -> Result<bool, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| set_entity_meta(world, *entity, key, value))
+            },
+        )
+        .register(
+            "tagged",
+            |ctxt: FunctionCallContext, tag: String| -> Result<Vec<Val<Entity>>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| {
+                    query_tagged(world, &tag).into_iter().map(Val).collect()
+                })
+            },
+        )
+        .register(
+            "with_meta",
+            |ctxt: FunctionCallContext,
+             key: String,
+             value: Option<String>|
+             -> Result<Vec<Val<Entity>>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_global_access(|world| {
+                    query_with_meta(world, &key, value.as_deref())
+                        .into_iter()
+                        .map(Val)
+                        .collect()
+                })
+            },
+        );
+}
+
+/// Registers the functions that read and set what happens to invalid states before export.
+pub fn register_state_validation_functions(app: &mut App) {
+    NamespaceBuilder::<Komotool>::new(app.world_mut())
+        .register(
+            "invalid_state_policy",
+            |ctxt: FunctionCallContext| -> Result<String, InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource(|validation: &StateValidation| {
+                    validation.policy.as_str().to_string()
+                })
+            },
+        )
+        .register(
+            "set_invalid_state_policy",
+            |ctxt: FunctionCallContext, policy: String| -> Result<bool, InteropError> {
+                let Some(policy) = InvalidStatePolicy::parse(&policy) else {
+                    eprintln!("Unknown invalid state policy: {}", policy);
+                    return Ok(false);
+                };
+                let world = ctxt.world()?;
+                world.with_resource_mut(|mut validation: Mut<StateValidation>| {
+                    validation.policy = policy;
+                    true
+                })
+            },
+        );
+}
+
+/// Registers the functions that read and switch the outgoing message mode.
+pub fn register_outgoing_mode_functions(app: &mut App) {
+    NamespaceBuilder::<Komotool>::new(app.world_mut())
+        .register(
+            "outgoing_mode",
+            |ctxt: FunctionCallContext| -> Result<String, InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource(|outgoing: &KomorebiOutgoing| {
+                    outgoing.mode().as_str().to_string()
+                })
+            },
+        )
+        .register(
+            "set_outgoing_mode",
+            |ctxt: FunctionCallContext, mode: String| -> Result<bool, InteropError> {
+                let Some(mode) = OutgoingMode::parse(&mode) else {
+                    eprintln!("Unknown outgoing mode: {}", mode);
+                    return Ok(false);
+                };
+                let world = ctxt.world()?;
+                let outgoing =
+                    world.with_resource(|outgoing: &KomorebiOutgoing| outgoing.clone())?;
+                world.with_resource_mut(|mut resync: Mut<ShadowResync>| {
+                    switch_outgoing_mode(&outgoing, &mut resync, mode);
+                    true
+                })
+            },
+        );
+}
@@ -0,0 +1,7 @@
+pub mod restore;
+pub mod session;
+pub mod snapshot;
+
+pub use restore::*;
+pub use session::*;
+pub use snapshot::*;
@@ -0,0 +1,494 @@
+use crate::sessions::snapshot::{SessionSnapshot, WindowIdentity, match_windows};
+use komorebi_client::{Container, Rect, Ring, State, Window};
+use std::collections::{HashMap, HashSet};
+
+/// The state a session restore aims for, and how much of the snapshot it could place.
+#[derive(Debug, Clone)]
+pub struct SessionRestorePlan {
+    pub target: State,
+    /// Custom layout per restored (monitor, workspace) position, `None` to remove one
+    pub custom_layouts: HashMap<(usize, usize), Option<String>>,
+    /// Saved windows that were matched to a current window
+    pub restored: usize,
+    /// Saved windows with no current counterpart
+    pub missing: Vec<WindowIdentity>,
+}
+
+/// A workspace the snapshot places, with what it restores on it.
+struct RestoredWorkspace {
+    saved_monitor: usize,
+    saved_workspace: usize,
+    resize_dimensions: Vec<Option<Rect>>,
+    /// Index of the saved focused container among the restored ones
+    focused_container: Option<usize>,
+}
+
+/// Matches snapshot monitors to current ones by serial, then by position.
+fn match_monitors(snapshot: &SessionSnapshot, current: &State) -> Vec<Option<usize>> {
+    let serials: Vec<Option<String>> = current
+        .monitors
+        .elements()
+        .iter()
+        .map(|monitor| monitor.serial_number_id().clone())
+        .collect();
+    let mut claimed = HashSet::new();
+    let mut matches: Vec<Option<usize>> = snapshot
+        .monitors
+        .iter()
+        .map(|monitor| {
+            let idx = serials
+                .iter()
+                .position(|serial| serial.is_some() && *serial == monitor.serial)?;
+            claimed.insert(idx).then_some(idx)
+        })
+        .collect();
+    for (idx, matched) in matches.iter_mut().enumerate() {
+        if matched.is_none() && idx < serials.len() && claimed.insert(idx) {
+            *matched = Some(idx);
+        }
+    }
+    matches
+}
+
+/// Works out the state that puts current windows back where a snapshot had them.
+///
+/// Windows are matched by [`identity_score`](super::identity_score), since hwnds change across
+/// restarts. A saved container is restored by moving the current container holding its first
+/// matched window; containers are not split or merged. Workspaces are matched by name, then by
+/// position. Containers the snapshot doesn't place stay on their workspace, after the restored
+/// ones. Layouts, resize dimensions and focus come from the snapshot for every workspace it
+/// places. Pure, so it can be tested on synthetic states.
+pub fn plan_session_restore(
+    snapshot: &SessionSnapshot,
+    current: &State,
+    identities: &HashMap<isize, WindowIdentity>,
+) -> SessionRestorePlan {
+    let mut target = current.clone();
+    let monitor_matches = match_monitors(snapshot, current);
+
+    // Every saved window, tiled then floating, with the current window it matches
+    let saved: Vec<&WindowIdentity> = snapshot
+        .monitors
+        .iter()
+        .flat_map(|monitor| monitor.workspaces.iter())
+        .flat_map(|workspace| {
+            workspace
+                .containers
+                .iter()
+                .flat_map(|container| container.windows.iter())
+                .chain(workspace.floating_windows.iter())
+        })
+        .collect();
+    let mut current_windows: Vec<&WindowIdentity> = identities.values().collect();
+    current_windows.sort_by_key(|identity| identity.hwnd);
+    let matches = match_windows(&saved, &current_windows);
+    let missing = saved
+        .iter()
+        .zip(&matches)
+        .filter(|(_, matched)| matched.is_none())
+        .map(|(identity, _)| (*identity).clone())
+        .collect();
+    let restored = matches.iter().flatten().count();
+    let mut matches = matches.into_iter();
+
+    // Take every container and floating window out of the target, keeping their origin
+    let mut origin_containers: HashMap<(usize, usize), Vec<Container>> = HashMap::new();
+    let mut origin_focus: HashMap<(usize, usize), Option<String>> = HashMap::new();
+    let mut container_of: HashMap<isize, (usize, usize, String)> = HashMap::new();
+    // Origin workspace and position in its floating windows
+    let mut floating_origin: HashMap<isize, ((usize, usize), usize)> = HashMap::new();
+    let mut floating: HashMap<isize, Window> = HashMap::new();
+    for (monitor_idx, monitor) in target.monitors.elements_mut().iter_mut().enumerate() {
+        for (workspace_idx, workspace) in monitor.workspaces.elements_mut().iter_mut().enumerate() {
+            let key = (monitor_idx, workspace_idx);
+            origin_focus.insert(
+                key,
+                workspace
+                    .containers()
+                    .get(workspace.focused_container_idx())
+                    .map(|container| container.id().clone()),
+            );
+            let containers = std::mem::take(&mut workspace.containers);
+            for container in containers.elements() {
+                for window in container.windows() {
+                    container_of.insert(
+                        window.hwnd,
+                        (monitor_idx, workspace_idx, container.id().clone()),
+                    );
+                }
+            }
+            origin_containers.insert(key, containers.elements().iter().cloned().collect());
+            for (idx, window) in std::mem::take(&mut workspace.floating_windows)
+                .into_iter()
+                .enumerate()
+            {
+                floating_origin.insert(window.hwnd, (key, idx));
+                floating.insert(window.hwnd, window);
+            }
+        }
+    }
+
+    let mut placed_containers: HashMap<(usize, usize), Vec<Container>> = HashMap::new();
+    let mut placed_floating: HashMap<(usize, usize), Vec<Window>> = HashMap::new();
+    let mut claimed: HashSet<String> = HashSet::new();
+    let mut custom_layouts = HashMap::new();
+    let mut restored_workspaces = HashMap::new();
+
+    for (saved_monitor, monitor) in snapshot.monitors.iter().enumerate() {
+        let monitor_idx = monitor_matches.get(saved_monitor).copied().flatten();
+        let current_workspaces: Vec<Option<String>> = monitor_idx
+            .and_then(|idx| current.monitors.elements().get(idx))
+            .map(|monitor| {
+                monitor
+                    .workspaces()
+                    .iter()
+                    .map(|workspace| workspace.name().clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for (saved_workspace, workspace) in monitor.workspaces.iter().enumerate() {
+            let workspace_idx = workspace
+                .name
+                .as_ref()
+                .and_then(|name| {
+                    current_workspaces
+                        .iter()
+                        .position(|current| current.as_ref() == Some(name))
+                })
+                .or_else(|| {
+                    (saved_workspace < current_workspaces.len()).then_some(saved_workspace)
+                });
+            let key = monitor_idx.zip(workspace_idx);
+
+            let mut resize_dimensions = Vec::new();
+            let mut focused = None;
+            for (saved_container, container) in workspace.containers.iter().enumerate() {
+                let hwnds: Vec<Option<isize>> =
+                    matches.by_ref().take(container.windows.len()).collect();
+                let Some(key) = key else {
+                    continue;
+                };
+                let Some((origin_m, origin_w, id)) = hwnds
+                    .iter()
+                    .flatten()
+                    .find_map(|hwnd| container_of.get(hwnd))
+                else {
+                    continue;
+                };
+                if !claimed.insert(id.clone()) {
+                    continue;
+                }
+                let Some(origin) = origin_containers.get_mut(&(*origin_m, *origin_w)) else {
+                    continue;
+                };
+                let Some(position) = origin.iter().position(|c| c.id() == id) else {
+                    continue;
+                };
+                let mut moved = origin.remove(position);
+                let focused_hwnd = hwnds.get(container.focused_window).copied().flatten();
+                if let Some(window_idx) = focused_hwnd
+                    .and_then(|hwnd| moved.windows().iter().position(|w| w.hwnd == hwnd))
+                {
+                    moved.focus_window(window_idx);
+                }
+                let placed = placed_containers.entry(key).or_default();
+                if saved_container == workspace.focused_container {
+                    focused = Some(placed.len());
+                }
+                resize_dimensions.push(
+                    workspace
+                        .resize_dimensions
+                        .get(saved_container)
+                        .copied()
+                        .flatten(),
+                );
+                placed.push(moved);
+            }
+
+            for _ in &workspace.floating_windows {
+                let Some(hwnd) = matches.next().flatten() else {
+                    continue;
+                };
+                let Some(key) = key else {
+                    continue;
+                };
+                if let Some(window) = floating.remove(&hwnd) {
+                    placed_floating.entry(key).or_default().push(window);
+                }
+            }
+
+            if let Some(key) = key {
+                custom_layouts.insert(key, workspace.custom_layout.clone());
+                restored_workspaces.insert(
+                    key,
+                    RestoredWorkspace {
+                        saved_monitor,
+                        saved_workspace,
+                        resize_dimensions,
+                        focused_container: focused,
+                    },
+                );
+            }
+        }
+    }
+
+    // Floating windows the snapshot didn't place stay where they were, in their previous order
+    let mut unplaced: Vec<_> = floating
+        .into_iter()
+        .filter_map(|(hwnd, window)| floating_origin.get(&hwnd).map(|origin| (*origin, window)))
+        .collect();
+    unplaced.sort_by_key(|(origin, _)| *origin);
+    for ((key, _), window) in unplaced {
+        placed_floating.entry(key).or_default().push(window);
+    }
+
+    for (monitor_idx, monitor) in target.monitors.elements_mut().iter_mut().enumerate() {
+        for (workspace_idx, workspace) in monitor.workspaces.elements_mut().iter_mut().enumerate() {
+            let key = (monitor_idx, workspace_idx);
+            let mut containers = placed_containers.remove(&key).unwrap_or_default();
+            containers.extend(origin_containers.remove(&key).unwrap_or_default());
+
+            let restored_focus = restored_workspaces
+                .get(&key)
+                .and_then(|restored| restored.focused_container);
+            let focused = match restored_focus {
+                Some(focused) => Some(focused),
+                None => origin_focus
+                    .get(&key)
+                    .cloned()
+                    .flatten()
+                    .and_then(|id| containers.iter().position(|c| *c.id() == id)),
+            };
+            let mut ring = Ring::default();
+            ring.elements_mut().extend(containers);
+            if let Some(focused) = focused {
+                ring.focus(focused);
+            }
+            workspace.containers = ring;
+            workspace.floating_windows = placed_floating.remove(&key).unwrap_or_default();
+
+            let Some(restored) = restored_workspaces.get(&key) else {
+                continue;
+            };
+            let Some(saved) = snapshot
+                .monitors
+                .get(restored.saved_monitor)
+                .and_then(|monitor| monitor.workspaces.get(restored.saved_workspace))
+            else {
+                continue;
+            };
+            workspace.layout = saved.layout.clone();
+            let mut resize_dimensions = restored.resize_dimensions.clone();
+            resize_dimensions.resize(workspace.containers().len(), None);
+            workspace.resize_dimensions = resize_dimensions;
+        }
+    }
+
+    // Focus, as far as the snapshot's focused monitor and workspaces could be placed
+    for (saved_monitor, monitor) in snapshot.monitors.iter().enumerate() {
+        let Some(monitor_idx) = monitor_matches.get(saved_monitor).copied().flatten() else {
+            continue;
+        };
+        let workspace_idx = restored_workspaces
+            .iter()
+            .find(|(_, restored)| {
+                restored.saved_monitor == saved_monitor
+                    && restored.saved_workspace == monitor.focused_workspace
+            })
+            .map(|(key, _)| key.1);
+        if let (Some(workspace_idx), Some(target_monitor)) = (
+            workspace_idx,
+            target.monitors.elements_mut().get_mut(monitor_idx),
+        ) {
+            target_monitor.workspaces.focus(workspace_idx);
+        }
+        if saved_monitor == snapshot.focused_monitor {
+            target.monitors.focus(monitor_idx);
+        }
+    }
+
+    SessionRestorePlan {
+        target,
+        custom_layouts,
+        restored,
+        missing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sessions::snapshot::{identity_score, snapshot_session};
+    use crate::test_states::*;
+    use crate::validation::validate_state;
+
+    fn identity(hwnd: isize, exe: &str, title: &str) -> WindowIdentity {
+        WindowIdentity {
+            hwnd,
+            exe: Some(exe.to_string()),
+            class: None,
+            title: Some(title.to_string()),
+        }
+    }
+
+    fn identities(windows: &[WindowIdentity]) -> HashMap<isize, WindowIdentity> {
+        windows
+            .iter()
+            .map(|identity| (identity.hwnd, identity.clone()))
+            .collect()
+    }
+
+    fn snapshot(state: &State, windows: &[WindowIdentity]) -> SessionSnapshot {
+        snapshot_session(state, &identities(windows), &HashMap::new())
+    }
+
+    #[test]
+    fn windows_are_matched_by_exe_and_title_across_new_hwnds() {
+        let saved = state(vec![monitor(
+            "m1",
+            vec![workspace("a", &[&[10], &[20]]), workspace("b", &[])],
+        )]);
+        let snapshot = snapshot(
+            &saved,
+            &[
+                identity(10, "firefox.exe", "Docs"),
+                identity(20, "code.exe", "main.rs"),
+            ],
+        );
+        let current = state(vec![monitor(
+            "m1",
+            vec![workspace("a", &[&[200]]), workspace("b", &[&[100]])],
+        )]);
+
+        let plan = plan_session_restore(
+            &snapshot,
+            &current,
+            &identities(&[
+                identity(100, "firefox.exe", "Docs"),
+                identity(200, "code.exe", "main.rs"),
+            ]),
+        );
+        assert_eq!(plan.restored, 2);
+        assert!(plan.missing.is_empty());
+        assert_eq!(
+            tiled_hwnds(&plan.target),
+            vec![vec![vec![vec![100], vec![200]], vec![]]]
+        );
+        assert!(validate_state(&plan.target).is_empty());
+    }
+
+    #[test]
+    fn unknown_floating_windows_keep_their_order() {
+        let saved = state(vec![monitor(
+            "m1",
+            vec![workspace("a", &[]), workspace("b", &[])],
+        )]);
+        let snapshot = snapshot(&saved, &[]);
+        let mut a = workspace("a", &[]);
+        a.floating_windows = [5, 4, 3, 2, 1].into_iter().map(window).collect();
+        let current = state(vec![monitor("m1", vec![a, workspace("b", &[])])]);
+
+        let plan = plan_session_restore(&snapshot, &current, &HashMap::new());
+        assert_eq!(
+            floating_hwnds(&plan.target),
+            vec![vec![vec![5, 4, 3, 2, 1], vec![]]]
+        );
+    }
+
+    #[test]
+    fn missing_windows_are_reported_and_the_rest_restored() {
+        let saved = state(vec![monitor(
+            "m1",
+            vec![workspace("a", &[&[10], &[20]]), workspace("b", &[&[30]])],
+        )]);
+        let slack = identity(30, "slack.exe", "general");
+        let snapshot = snapshot(
+            &saved,
+            &[
+                identity(10, "firefox.exe", "Docs"),
+                identity(20, "code.exe", "main.rs"),
+                slack.clone(),
+            ],
+        );
+        let current = state(vec![monitor(
+            "m1",
+            vec![
+                workspace("a", &[]),
+                workspace("b", &[&[200], &[100], &[300]]),
+            ],
+        )]);
+
+        let plan = plan_session_restore(
+            &snapshot,
+            &current,
+            &identities(&[
+                identity(100, "firefox.exe", "Docs"),
+                identity(200, "code.exe", "main.rs"),
+                identity(300, "explorer.exe", "Downloads"),
+            ]),
+        );
+        assert_eq!(plan.restored, 2);
+        assert_eq!(plan.missing, vec![slack]);
+        // Windows the snapshot doesn't know stay where they are
+        assert_eq!(
+            tiled_hwnds(&plan.target),
+            vec![vec![vec![vec![100], vec![200]], vec![vec![300]]]]
+        );
+        assert!(validate_state(&plan.target).is_empty());
+    }
+
+    #[test]
+    fn duplicate_candidates_are_told_apart_by_title() {
+        let saved = state(vec![monitor(
+            "m1",
+            vec![workspace("a", &[&[10]]), workspace("b", &[&[11]])],
+        )]);
+        let snapshot = snapshot(
+            &saved,
+            &[
+                identity(10, "firefox.exe", "Docs"),
+                identity(11, "firefox.exe", "Mail"),
+            ],
+        );
+        let current = state(vec![monitor(
+            "m1",
+            vec![workspace("a", &[&[100]]), workspace("b", &[&[101]])],
+        )]);
+
+        let plan = plan_session_restore(
+            &snapshot,
+            &current,
+            &identities(&[
+                identity(100, "firefox.exe", "Mail"),
+                identity(101, "firefox.exe", "Docs"),
+            ]),
+        );
+        assert_eq!(plan.restored, 2);
+        assert_eq!(
+            tiled_hwnds(&plan.target),
+            vec![vec![vec![vec![101]], vec![vec![100]]]]
+        );
+    }
+
+    #[test]
+    fn each_current_window_matches_one_saved_window() {
+        let saved = [
+            identity(10, "firefox.exe", "Docs"),
+            identity(11, "firefox.exe", "Mail"),
+        ];
+        let current = [identity(100, "firefox.exe", "Inbox")];
+        let matches = match_windows(
+            &saved.iter().collect::<Vec<_>>(),
+            &current.iter().collect::<Vec<_>>(),
+        );
+        assert_eq!(matches, vec![Some(100), None]);
+    }
+
+    #[test]
+    fn reused_hwnds_need_the_same_exe() {
+        let saved = identity(10, "firefox.exe", "Docs");
+        assert_eq!(identity_score(&saved, &identity(10, "code.exe", "Docs")), 0);
+        assert!(identity_score(&saved, &identity(10, "firefox.exe", "Mail")) > 0);
+    }
+}
@@ -0,0 +1,220 @@
+use crate::layouts::CustomLayout;
+use crate::relations::{RelationIndex, RelationRegistry};
+use crate::resources::{KomorebiState, SessionDirectory};
+use crate::sessions::restore::{SessionRestorePlan, plan_session_restore};
+use crate::sessions::snapshot::{
+    SESSION_SNAPSHOT_VERSION, SessionSnapshot, live_identities, snapshot_session,
+};
+use crate::systems::{diff_states, validate_write_back_state};
+use crate::transport::KomorebiTransport;
+use bevy_ecs::event::EventReader;
+use bevy_ecs::system::Commands;
+use bevy_ecs::world::World;
+use komorebi_client::SocketMessage;
+use komotoolc_pipe::{KomotoolcCommandEvent, KomotoolcMessage, KomotoolcResponse};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Session names become file names, so they can't point outside the sessions folder.
+pub fn validate_session_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(['/', '\\', ':']) || name.starts_with('.') {
+        return Err(format!("Invalid session name: '{}'", name));
+    }
+    Ok(())
+}
+
+pub fn write_session_file(path: &Path, snapshot: &SessionSnapshot) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(snapshot)
+        .map_err(|e| format!("Failed to serialise session: {}", e))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Reads a session file, refusing snapshots written by a newer format version.
+pub fn read_session_file(path: &Path) -> Result<SessionSnapshot, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let snapshot = serde_json::from_str::<SessionSnapshot>(&content)
+        .map_err(|e| format!("Invalid session file {}: {}", path.display(), e))?;
+    if snapshot.version > SESSION_SNAPSHOT_VERSION {
+        return Err(format!(
+            "Session file {} has version {}, this komotool reads up to version {}",
+            path.display(),
+            snapshot.version,
+            SESSION_SNAPSHOT_VERSION
+        ));
+    }
+    Ok(snapshot)
+}
+
+/// Names of the saved sessions, sorted.
+pub fn list_sessions(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Custom layout names by 0-based (monitor, workspace) position.
+fn custom_layout_positions(world: &mut World) -> HashMap<(usize, usize), String> {
+    let mut query = world.query::<(&CustomLayout, &RelationIndex)>();
+    query
+        .iter(world)
+        .filter(|(_, index)| index.monitor > 0 && index.workspace > 0 && index.container == 0)
+        .map(|(layout, index)| {
+            (
+                (index.monitor - 1, index.workspace - 1),
+                layout.name.clone(),
+            )
+        })
+        .collect()
+}
+
+fn session_path(world: &World, name: &str) -> Result<PathBuf, String> {
+    validate_session_name(name)?;
+    Ok(world
+        .get_resource::<SessionDirectory>()
+        .map(|sessions| sessions.path(name))
+        .unwrap_or_else(|| SessionDirectory::default().path(name)))
+}
+
+/// Snapshots the current arrangement to the named session file.
+pub fn save_session(world: &mut World, name: &str) -> Result<PathBuf, String> {
+    let path = session_path(world, name)?;
+    let Some(state) = world
+        .get_resource::<KomorebiState>()
+        .and_then(|state| state.komorebi.clone())
+    else {
+        return Err("komorebi's state hasn't been received yet".to_string());
+    };
+    let custom_layouts = custom_layout_positions(world);
+    let snapshot = snapshot_session(&state, &live_identities(&state), &custom_layouts);
+    write_session_file(&path, &snapshot)?;
+    Ok(path)
+}
+
+/// Moves the current windows back into the arrangement of the named session.
+///
+/// The arrangement is sent as granular commands where [`diff_states`] can express it, and as
+/// a single `ApplyState` otherwise. Custom layouts are set on the restored workspaces.
+pub fn restore_session(world: &mut World, name: &str) -> Result<SessionRestorePlan, String> {
+    let snapshot = read_session_file(&session_path(world, name)?)?;
+    let Some(current) = world
+        .get_resource::<KomorebiState>()
+        .and_then(|state| state.komorebi.clone())
+    else {
+        return Err("komorebi's state hasn't been received yet".to_string());
+    };
+    let plan = plan_session_restore(&snapshot, &current, &live_identities(&current));
+    validate_write_back_state(&current, &plan.target)?;
+
+    if current.has_been_modified(&plan.target) {
+        let messages = diff_states(&current, &plan.target)
+            .unwrap_or_else(|| vec![SocketMessage::ApplyState(plan.target.clone())]);
+        let Some(transport) = world.get_resource::<KomorebiTransport>() else {
+            return Err("No komorebi transport".to_string());
+        };
+        transport
+            .send_all(&messages)
+            .map_err(|e| format!("Failed to send session to komorebi: {}", e))?;
+    }
+
+    let workspaces: Vec<_> = {
+        let Some(registry) = world.get_resource::<RelationRegistry>() else {
+            return Ok(plan);
+        };
+        plan.custom_layouts
+            .iter()
+            .filter_map(|((monitor, workspace), layout)| {
+                let entity = registry.entity_at((monitor + 1, workspace + 1, 0, 0))?;
+                Some((entity, layout.clone()))
+            })
+            .collect()
+    };
+    for (entity, layout) in workspaces {
+        let Ok(mut entity) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        match layout {
+            Some(name) => {
+                entity.insert(CustomLayout { name });
+            }
+            None => {
+                entity.remove::<CustomLayout>();
+            }
+        }
+    }
+    Ok(plan)
+}
+
+pub fn format_restore(name: &str, plan: &SessionRestorePlan) -> String {
+    let mut lines = vec![format!(
+        "Restored session '{}': {} window(s) placed, {} missing",
+        name,
+        plan.restored,
+        plan.missing.len()
+    )];
+    lines.extend(plan.missing.iter().map(|identity| {
+        format!(
+            "  missing: {} {}",
+            identity.exe.as_deref().unwrap_or("<unknown exe>"),
+            identity.title.as_deref().unwrap_or("")
+        )
+    }));
+    lines.join("\n")
+}
+
+/// Answers komotoolc's session commands. They need the whole world, so they run as queued commands.
+pub fn handle_session_commands(
+    mut events: EventReader<KomotoolcCommandEvent>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        match &event.message {
+            KomotoolcMessage::SaveSession(_)
+            | KomotoolcMessage::RestoreSession(_)
+            | KomotoolcMessage::Sessions => {}
+            _ => continue,
+        }
+        event.claim();
+        let event = event.clone();
+        commands.queue(move |world: &mut World| {
+            let response = match &event.message {
+                KomotoolcMessage::SaveSession(name) => match save_session(world, name) {
+                    Ok(path) => KomotoolcResponse::Ok(format!(
+                        "Saved session '{}' to {}",
+                        name,
+                        path.display()
+                    )),
+                    Err(e) => KomotoolcResponse::Error(e),
+                },
+                KomotoolcMessage::RestoreSession(name) => match restore_session(world, name) {
+                    Ok(plan) => KomotoolcResponse::Ok(format_restore(name, &plan)),
+                    Err(e) => KomotoolcResponse::Error(e),
+                },
+                _ => {
+                    let dir = world
+                        .get_resource::<SessionDirectory>()
+                        .map(SessionDirectory::dir)
+                        .unwrap_or_default();
+                    KomotoolcResponse::Ok(list_sessions(&dir).join("\n"))
+                }
+            };
+            event.reply(response);
+        });
+    }
+}
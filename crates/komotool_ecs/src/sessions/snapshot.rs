@@ -0,0 +1,201 @@
+use komorebi_client::{Layout, Rect, State, Window};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Bumped whenever the snapshot format changes incompatibly.
+pub const SESSION_SNAPSHOT_VERSION: u32 = 1;
+
+/// The arrangement of every window komorebi manages, as saved to a session file.
+///
+/// Indices are 0-based positions, like komorebi's own. Monocle containers and maximized
+/// windows are not part of a snapshot.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SessionSnapshot {
+    pub version: u32,
+    pub focused_monitor: usize,
+    pub monitors: Vec<MonitorSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MonitorSnapshot {
+    pub serial: Option<String>,
+    pub focused_workspace: usize,
+    pub workspaces: Vec<WorkspaceSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WorkspaceSnapshot {
+    pub name: Option<String>,
+    pub layout: Layout,
+    /// Name of the workspace's [`CustomLayout`](crate::layouts::CustomLayout), if it has one
+    pub custom_layout: Option<String>,
+    /// komorebi's per-container resize adjustments, in container order
+    pub resize_dimensions: Vec<Option<Rect>>,
+    pub focused_container: usize,
+    pub containers: Vec<ContainerSnapshot>,
+    pub floating_windows: Vec<WindowIdentity>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ContainerSnapshot {
+    pub focused_window: usize,
+    pub windows: Vec<WindowIdentity>,
+}
+
+/// What a window is recognised by across restarts, when its hwnd has changed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowIdentity {
+    pub hwnd: isize,
+    pub exe: Option<String>,
+    pub class: Option<String>,
+    pub title: Option<String>,
+}
+
+impl WindowIdentity {
+    /// Reads the identity of a live window.
+    pub fn of(window: Window) -> Self {
+        Self {
+            hwnd: window.hwnd,
+            exe: window.exe().ok(),
+            class: window.class().ok(),
+            title: window.title().ok(),
+        }
+    }
+}
+
+/// Identities of every window in a state, read from the live windows.
+pub fn live_identities(state: &State) -> HashMap<isize, WindowIdentity> {
+    let mut identities = HashMap::new();
+    for monitor in state.monitors.elements() {
+        for workspace in monitor.workspaces() {
+            let tiled = workspace
+                .containers()
+                .iter()
+                .flat_map(|container| container.windows().iter());
+            for window in tiled.chain(workspace.floating_windows.iter()) {
+                identities.insert(window.hwnd, WindowIdentity::of(*window));
+            }
+        }
+    }
+    identities
+}
+
+/// Takes a snapshot of a state.
+///
+/// `identities` holds the identity of each hwnd, and `custom_layouts` the custom layout names
+/// by (monitor, workspace) position. Windows without an identity are saved by hwnd only.
+pub fn snapshot_session(
+    state: &State,
+    identities: &HashMap<isize, WindowIdentity>,
+    custom_layouts: &HashMap<(usize, usize), String>,
+) -> SessionSnapshot {
+    let identity = |window: &Window| {
+        identities
+            .get(&window.hwnd)
+            .cloned()
+            .unwrap_or_else(|| WindowIdentity {
+                hwnd: window.hwnd,
+                ..Default::default()
+            })
+    };
+
+    let monitors = state
+        .monitors
+        .elements()
+        .iter()
+        .enumerate()
+        .map(|(monitor_idx, monitor)| MonitorSnapshot {
+            serial: monitor.serial_number_id().clone(),
+            focused_workspace: monitor.focused_workspace_idx(),
+            workspaces: monitor
+                .workspaces()
+                .iter()
+                .enumerate()
+                .map(|(workspace_idx, workspace)| WorkspaceSnapshot {
+                    name: workspace.name().clone(),
+                    layout: workspace.layout.clone(),
+                    custom_layout: custom_layouts.get(&(monitor_idx, workspace_idx)).cloned(),
+                    resize_dimensions: workspace.resize_dimensions.clone(),
+                    focused_container: workspace.focused_container_idx(),
+                    containers: workspace
+                        .containers()
+                        .iter()
+                        .map(|container| ContainerSnapshot {
+                            focused_window: container.focused_window_idx(),
+                            windows: container.windows().iter().map(identity).collect(),
+                        })
+                        .collect(),
+                    floating_windows: workspace.floating_windows.iter().map(identity).collect(),
+                })
+                .collect(),
+        })
+        .collect();
+
+    SessionSnapshot {
+        version: SESSION_SNAPSHOT_VERSION,
+        focused_monitor: state.monitors.focused_idx(),
+        monitors,
+    }
+}
+
+/// Score below which two identities are not considered the same window.
+const MIN_IDENTITY_SCORE: u32 = 4;
+
+/// How likely a saved window is the current one; 0 means it can't be.
+///
+/// A differing exe or class rules a window out. The same hwnd only counts while the exe
+/// agrees, since hwnds are reused after a reboot.
+pub fn identity_score(saved: &WindowIdentity, current: &WindowIdentity) -> u32 {
+    let compare = |saved: &Option<String>, current: &Option<String>| match (saved, current) {
+        (Some(saved), Some(current)) => Some(saved.eq_ignore_ascii_case(current)),
+        _ => None,
+    };
+    let exe = compare(&saved.exe, &current.exe);
+    let class = compare(&saved.class, &current.class);
+    if exe == Some(false) || class == Some(false) {
+        return 0;
+    }
+
+    let mut score = 0;
+    if saved.hwnd == current.hwnd {
+        score += 8;
+    }
+    if exe == Some(true) {
+        score += 4;
+    }
+    if class == Some(true) {
+        score += 2;
+    }
+    if saved.title.is_some() && saved.title == current.title {
+        score += 3;
+    }
+    if score < MIN_IDENTITY_SCORE { 0 } else { score }
+}
+
+/// Pairs saved windows with current ones, best scores first, each window used at most once.
+///
+/// Returns the matched current hwnd for each saved window, in the order given.
+pub fn match_windows(saved: &[&WindowIdentity], current: &[&WindowIdentity]) -> Vec<Option<isize>> {
+    let mut candidates = Vec::new();
+    for (saved_idx, saved_window) in saved.iter().enumerate() {
+        for (current_idx, current_window) in current.iter().enumerate() {
+            let score = identity_score(saved_window, current_window);
+            if score > 0 {
+                candidates.push((score, saved_idx, current_idx));
+            }
+        }
+    }
+    // Highest score first; ties keep saved and current order so the result is stable
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+    let mut matches = vec![None; saved.len()];
+    let mut used = vec![false; current.len()];
+    for (_, saved_idx, current_idx) in candidates {
+        if matches[saved_idx].is_some() || used[current_idx] {
+            continue;
+        }
+        matches[saved_idx] = Some(current[current_idx].hwnd);
+        used[current_idx] = true;
+    }
+    matches
+}
@@ -0,0 +1,5 @@
+pub mod outgoing;
+pub mod simulate;
+
+pub use outgoing::*;
+pub use simulate::*;
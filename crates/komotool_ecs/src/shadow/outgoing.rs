@@ -0,0 +1,160 @@
+use crate::resources::KomorebiState;
+use crate::shadow::simulate::simulate_message;
+use bevy_ecs::event::EventReader;
+use bevy_ecs::system::{Res, ResMut, Resource};
+use crossbeam_channel::{Receiver, TryRecvError, bounded};
+use komorebi_client::{SocketMessage, State, send_query};
+use komotool_utils::komorebi_commands::{KomorebiOutgoing, OutgoingMode};
+use komotoolc_pipe::{KomotoolcCommandEvent, KomotoolcMessage, KomotoolcResponse};
+use std::thread;
+
+/// Applies the messages intercepted in shadow mode to [`KomorebiState`], so the next import
+/// mirrors them as if komorebi had carried them out.
+///
+/// The simulation lasts until komorebi reports its real state, e.g. after the user focuses
+/// another window.
+pub fn apply_shadowed_messages(
+    outgoing: Res<KomorebiOutgoing>,
+    mut komorebi_state: ResMut<KomorebiState>,
+) {
+    let messages = outgoing.take_shadowed();
+    if messages.is_empty() {
+        return;
+    }
+    let Some(current) = &komorebi_state.komorebi else {
+        return;
+    };
+    let mut simulated = current.clone();
+    for message in &messages {
+        if !simulate_message(&mut simulated, message) {
+            println!("[shadow] not simulated: {:?}", message);
+        }
+    }
+    if current.has_been_modified(&simulated) {
+        komorebi_state.komorebi = Some(simulated);
+    }
+}
+
+/// komorebi's real state, queried off the main thread after leaving shadow mode, and the
+/// komotoolc commands waiting for it.
+#[derive(Resource, Default)]
+pub struct ShadowResync {
+    receiver: Option<Receiver<Result<State, String>>>,
+    waiting: Vec<KomotoolcCommandEvent>,
+}
+
+impl ShadowResync {
+    pub fn is_pending(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    fn finish(&mut self, response: KomotoolcResponse) {
+        self.receiver = None;
+        for event in self.waiting.drain(..) {
+            event.reply(response.clone());
+        }
+    }
+}
+
+fn query_komorebi_state() -> Result<State, String> {
+    let state = send_query(&SocketMessage::State)
+        .map_err(|e| format!("Failed to query komorebi's state: {}", e))?;
+    serde_json::from_str::<State>(&state).map_err(|e| format!("Invalid state from komorebi: {}", e))
+}
+
+/// Switches the [`OutgoingMode`]. Leaving shadow mode starts querying komorebi's real state
+/// in the background, which [`finish_shadow_resync`] puts in place of the simulated one.
+///
+/// Returns whether a resync was started.
+pub fn switch_outgoing_mode(
+    outgoing: &KomorebiOutgoing,
+    resync: &mut ShadowResync,
+    mode: OutgoingMode,
+) -> bool {
+    let previous = outgoing.mode();
+    outgoing.set_mode(mode);
+    if mode == OutgoingMode::Shadow {
+        // A real state arriving now would drop the messages simulated from here on
+        resync.finish(KomotoolcResponse::Ok(format!(
+            "Outgoing komorebi messages: {}",
+            mode.as_str()
+        )));
+        return false;
+    }
+    if previous != OutgoingMode::Shadow {
+        return false;
+    }
+    let (sender, receiver) = bounded(1);
+    thread::spawn(move || {
+        let _ = sender.send(query_komorebi_state());
+    });
+    resync.receiver = Some(receiver);
+    true
+}
+
+/// Replaces the simulated state with komorebi's real one once the query started by
+/// [`switch_outgoing_mode`] answers, and replies to the komotoolc commands waiting for it.
+pub fn finish_shadow_resync(
+    outgoing: Res<KomorebiOutgoing>,
+    mut resync: ResMut<ShadowResync>,
+    mut komorebi_state: ResMut<KomorebiState>,
+) {
+    let Some(receiver) = &resync.receiver else {
+        return;
+    };
+    let result = match receiver.try_recv() {
+        Ok(result) => result,
+        Err(TryRecvError::Empty) => return,
+        Err(TryRecvError::Disconnected) => Err("The komorebi state query stopped".to_string()),
+    };
+    let response = match result {
+        Ok(state) => {
+            komorebi_state.komorebi = Some(state);
+            KomotoolcResponse::Ok(format!(
+                "Outgoing komorebi messages: {}",
+                outgoing.mode().as_str()
+            ))
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            KomotoolcResponse::Error(e)
+        }
+    };
+    resync.finish(response);
+}
+
+/// Answers komotoolc's outgoing mode commands. Switching out of shadow mode answers once the
+/// real state is back.
+pub fn handle_outgoing_mode_commands(
+    mut events: EventReader<KomotoolcCommandEvent>,
+    outgoing: Res<KomorebiOutgoing>,
+    mut resync: ResMut<ShadowResync>,
+) {
+    for event in events.read() {
+        match &event.message {
+            KomotoolcMessage::OutgoingMode => {
+                event.reply(KomotoolcResponse::Ok(outgoing.mode().as_str().to_string()));
+            }
+            KomotoolcMessage::SetOutgoingMode(mode) => {
+                let Some(mode) = OutgoingMode::parse(mode) else {
+                    event.reply(KomotoolcResponse::Error(format!(
+                        "Unknown outgoing mode '{}', expected live, dry-run or shadow",
+                        mode
+                    )));
+                    continue;
+                };
+                switch_outgoing_mode(&outgoing, &mut resync, mode);
+                if resync.is_pending() {
+                    event.claim();
+                    resync.waiting.push(event.clone());
+                } else {
+                    event.reply(KomotoolcResponse::Ok(format!(
+                        "Outgoing komorebi messages: {}",
+                        mode.as_str()
+                    )));
+                }
+            }
+            _ => continue,
+        }
+    }
+}
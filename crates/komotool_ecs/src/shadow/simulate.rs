@@ -0,0 +1,323 @@
+use komorebi_client::{Container, CycleDirection, Layout, SocketMessage, State, Workspace};
+
+fn focused_workspace_mut(state: &mut State) -> Option<&mut Workspace> {
+    let monitor = state.monitors.focused_mut()?;
+    let idx = monitor.focused_workspace_idx();
+    monitor.workspaces.elements_mut().get_mut(idx)
+}
+
+fn focus_workspace(state: &mut State, monitor: usize, workspace: usize) -> Option<()> {
+    let target = state.monitors.elements_mut().get_mut(monitor)?;
+    if workspace >= target.workspaces().len() {
+        return None;
+    }
+    target.workspaces.focus(workspace);
+    state.monitors.focus(monitor);
+    Some(())
+}
+
+fn named_workspace(state: &State, name: &str) -> Option<(usize, usize)> {
+    state
+        .monitors
+        .elements()
+        .iter()
+        .enumerate()
+        .find_map(|(monitor_idx, monitor)| {
+            let workspace_idx = monitor
+                .workspaces()
+                .iter()
+                .position(|workspace| workspace.name().as_deref() == Some(name))?;
+            Some((monitor_idx, workspace_idx))
+        })
+}
+
+/// Takes the focused container out of the focused workspace, focusing the one before it.
+fn take_focused_container(state: &mut State) -> Option<Container> {
+    let workspace = focused_workspace_mut(state)?;
+    let focused = workspace.focused_container_idx();
+    let container = workspace.containers.elements_mut().remove(focused)?;
+    if focused < workspace.resize_dimensions.len() {
+        workspace.resize_dimensions.remove(focused);
+    }
+    workspace.containers.focus(focused.saturating_sub(1));
+    Some(container)
+}
+
+/// Moves the focused container to the end of another workspace, following it if `follow`.
+fn move_focused_container(
+    state: &mut State,
+    monitor: usize,
+    workspace: usize,
+    follow: bool,
+) -> Option<()> {
+    let exists = state
+        .monitors
+        .elements()
+        .get(monitor)
+        .is_some_and(|m| workspace < m.workspaces().len());
+    if !exists {
+        return None;
+    }
+    let container = take_focused_container(state)?;
+    let target = state
+        .monitors
+        .elements_mut()
+        .get_mut(monitor)?
+        .workspaces
+        .elements_mut()
+        .get_mut(workspace)?;
+    target.containers.elements_mut().push_back(container);
+    target.resize_dimensions.push(None);
+    let focused = target.containers().len() - 1;
+    target.containers.focus(focused);
+    if follow {
+        focus_workspace(state, monitor, workspace)?;
+    }
+    Some(())
+}
+
+fn focused_monitor_idx(state: &State) -> usize {
+    state.monitors.focused_idx()
+}
+
+fn focused_workspace_on(state: &State, monitor: usize) -> Option<usize> {
+    Some(
+        state
+            .monitors
+            .elements()
+            .get(monitor)?
+            .focused_workspace_idx(),
+    )
+}
+
+/// Applies a message to a simulated komorebi state, as far as it can be predicted.
+///
+/// Covers `ApplyState`, the commands [`diff_states`](crate::systems::diff_states) emits, and
+/// focusing, moving and sending to workspaces and monitors. Returns `false`, leaving the
+/// state untouched, for messages it can't simulate or that don't apply to the state. Pure, so
+/// it can be tested on synthetic states.
+pub fn simulate_message(state: &mut State, message: &SocketMessage) -> bool {
+    let mut simulated = state.clone();
+    let applied = match message {
+        SocketMessage::ApplyState(target) => {
+            simulated = target.clone();
+            Some(())
+        }
+        SocketMessage::FocusMonitorNumber(monitor) => focused_workspace_on(&simulated, *monitor)
+            .and_then(|workspace| focus_workspace(&mut simulated, *monitor, workspace)),
+        SocketMessage::FocusWorkspaceNumber(workspace) => {
+            let monitor = focused_monitor_idx(&simulated);
+            focus_workspace(&mut simulated, monitor, *workspace)
+        }
+        SocketMessage::FocusMonitorWorkspaceNumber(monitor, workspace) => {
+            focus_workspace(&mut simulated, *monitor, *workspace)
+        }
+        SocketMessage::FocusNamedWorkspace(name) => named_workspace(&simulated, name)
+            .and_then(|(monitor, workspace)| focus_workspace(&mut simulated, monitor, workspace)),
+        SocketMessage::MoveContainerToWorkspaceNumber(workspace) => {
+            let monitor = focused_monitor_idx(&simulated);
+            move_focused_container(&mut simulated, monitor, *workspace, true)
+        }
+        SocketMessage::SendContainerToWorkspaceNumber(workspace) => {
+            let monitor = focused_monitor_idx(&simulated);
+            move_focused_container(&mut simulated, monitor, *workspace, false)
+        }
+        SocketMessage::MoveContainerToMonitorWorkspaceNumber(monitor, workspace) => {
+            move_focused_container(&mut simulated, *monitor, *workspace, true)
+        }
+        SocketMessage::MoveContainerToMonitorNumber(monitor) => {
+            focused_workspace_on(&simulated, *monitor).and_then(|workspace| {
+                move_focused_container(&mut simulated, *monitor, workspace, true)
+            })
+        }
+        SocketMessage::MoveContainerToNamedWorkspace(name) => named_workspace(&simulated, name)
+            .and_then(|(monitor, workspace)| {
+                move_focused_container(&mut simulated, monitor, workspace, true)
+            }),
+        SocketMessage::SendContainerToNamedWorkspace(name) => named_workspace(&simulated, name)
+            .and_then(|(monitor, workspace)| {
+                move_focused_container(&mut simulated, monitor, workspace, false)
+            }),
+        SocketMessage::CycleFocusWindow(direction) => focused_workspace_mut(&mut simulated)
+            .and_then(|workspace| {
+                let len = workspace.containers().len();
+                if len == 0 {
+                    return None;
+                }
+                let focused = workspace.focused_container_idx();
+                let next = match direction {
+                    CycleDirection::Next => (focused + 1) % len,
+                    CycleDirection::Previous => (focused + len - 1) % len,
+                };
+                workspace.containers.focus(next);
+                Some(())
+            }),
+        SocketMessage::FocusStackWindow(idx) => {
+            focused_workspace_mut(&mut simulated).and_then(|workspace| {
+                let focused = workspace.focused_container_idx();
+                let container = workspace.containers.elements_mut().get_mut(focused)?;
+                if *idx >= container.windows().len() {
+                    return None;
+                }
+                container.focus_window(*idx);
+                Some(())
+            })
+        }
+        SocketMessage::ChangeLayout(layout) => {
+            focused_workspace_mut(&mut simulated).map(|workspace| {
+                workspace.layout = Layout::Default(*layout);
+            })
+        }
+        // Floating a tiled window; which floating window is focused isn't known
+        SocketMessage::ToggleFloat => focused_workspace_mut(&mut simulated).and_then(|workspace| {
+            let focused = workspace.focused_container_idx();
+            let container = workspace.containers.elements_mut().get_mut(focused)?;
+            let window_idx = container.focused_window_idx();
+            let window = container.windows_mut().remove(window_idx)?;
+            if container.windows().is_empty() {
+                workspace.containers.elements_mut().remove(focused);
+                if focused < workspace.resize_dimensions.len() {
+                    workspace.resize_dimensions.remove(focused);
+                }
+                workspace.containers.focus(focused.saturating_sub(1));
+            } else {
+                let last = container.windows().len() - 1;
+                container.focus_window(window_idx.min(last));
+            }
+            workspace.floating_windows.push(window);
+            Some(())
+        }),
+        _ => None,
+    };
+    if applied.is_none() {
+        return false;
+    }
+    *state = simulated;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_states::*;
+    use crate::validation::validate_state;
+
+    #[test]
+    fn move_container_follows_it() {
+        let mut state = two_workspaces(&[&[1], &[2, 3]], 1);
+        assert!(simulate_message(
+            &mut state,
+            &SocketMessage::MoveContainerToWorkspaceNumber(1)
+        ));
+        assert_eq!(
+            tiled_hwnds(&state),
+            vec![vec![vec![vec![1]], vec![vec![2, 3]]]]
+        );
+        assert_eq!(focused_workspace(&state), (0, 1));
+        assert!(validate_state(&state).is_empty());
+    }
+
+    #[test]
+    fn send_container_stays_put() {
+        let mut state = two_workspaces(&[&[1], &[2, 3]], 1);
+        assert!(simulate_message(
+            &mut state,
+            &SocketMessage::SendContainerToNamedWorkspace("b".to_string())
+        ));
+        assert_eq!(
+            tiled_hwnds(&state),
+            vec![vec![vec![vec![1]], vec![vec![2, 3]]]]
+        );
+        assert_eq!(focused_workspace(&state), (0, 0));
+        assert!(validate_state(&state).is_empty());
+    }
+
+    #[test]
+    fn move_container_to_monitor_uses_its_focused_workspace() {
+        let mut second = monitor("m2", vec![workspace("c", &[]), workspace("d", &[&[4]])]);
+        second.workspaces.focus(1);
+        let mut state = state(vec![monitor("m1", vec![workspace("a", &[&[1]])]), second]);
+        assert!(simulate_message(
+            &mut state,
+            &SocketMessage::MoveContainerToMonitorNumber(1)
+        ));
+        assert_eq!(
+            tiled_hwnds(&state),
+            vec![vec![vec![]], vec![vec![], vec![vec![4], vec![1]]]]
+        );
+        assert_eq!(focused_workspace(&state), (1, 1));
+        assert!(validate_state(&state).is_empty());
+    }
+
+    #[test]
+    fn focus_workspace_and_monitor() {
+        let mut second = monitor("m2", vec![workspace("c", &[]), workspace("d", &[])]);
+        second.workspaces.focus(1);
+        let mut state = state(vec![
+            monitor("m1", vec![workspace("a", &[]), workspace("b", &[])]),
+            second,
+        ]);
+
+        assert!(simulate_message(
+            &mut state,
+            &SocketMessage::FocusWorkspaceNumber(1)
+        ));
+        assert_eq!(focused_workspace(&state), (0, 1));
+
+        assert!(simulate_message(
+            &mut state,
+            &SocketMessage::FocusMonitorNumber(1)
+        ));
+        assert_eq!(focused_workspace(&state), (1, 1));
+
+        assert!(simulate_message(
+            &mut state,
+            &SocketMessage::FocusNamedWorkspace("a".to_string())
+        ));
+        assert_eq!(focused_workspace(&state), (0, 0));
+    }
+
+    #[test]
+    fn toggle_float_takes_the_focused_window_out() {
+        let mut state = two_workspaces(&[&[1], &[2, 3]], 1);
+        if let Some(container) = state
+            .monitors
+            .elements_mut()
+            .front_mut()
+            .and_then(|monitor| monitor.workspaces.elements_mut().front_mut())
+            .and_then(|workspace| workspace.containers.elements_mut().get_mut(1))
+        {
+            container.focus_window(1);
+        }
+
+        assert!(simulate_message(&mut state, &SocketMessage::ToggleFloat));
+        assert_eq!(
+            tiled_hwnds(&state),
+            vec![vec![vec![vec![1], vec![2]], vec![]]]
+        );
+        assert_eq!(floating_hwnds(&state), vec![vec![vec![3], vec![]]]);
+        assert!(validate_state(&state).is_empty());
+
+        // Floating the last window of a container removes the container
+        assert!(simulate_message(&mut state, &SocketMessage::ToggleFloat));
+        assert_eq!(tiled_hwnds(&state), vec![vec![vec![vec![1]], vec![]]]);
+        assert_eq!(floating_hwnds(&state), vec![vec![vec![3, 2], vec![]]]);
+        assert!(validate_state(&state).is_empty());
+    }
+
+    #[test]
+    fn unsimulated_messages_leave_the_state_untouched() {
+        let mut state = two_workspaces(&[&[1], &[2, 3]], 1);
+        let before = state.clone();
+        assert!(!simulate_message(
+            &mut state,
+            &SocketMessage::FocusWorkspaceNumber(5)
+        ));
+        assert!(!simulate_message(
+            &mut state,
+            &SocketMessage::MoveContainerToNamedWorkspace("missing".to_string())
+        ));
+        assert!(!simulate_message(&mut state, &SocketMessage::Retile));
+        assert!(!before.has_been_modified(&state));
+    }
+}
@@ -0,0 +1,488 @@
+use komorebi_client::{CycleDirection, DefaultLayout, Layout, Ring, SocketMessage, State};
+use std::collections::{HashMap, HashSet};
+
+/// Where a container sits and which windows it holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ContainerSlot {
+    monitor: usize,
+    workspace: usize,
+    hwnds: Vec<isize>,
+}
+
+fn container_slots(state: &State) -> Vec<(String, ContainerSlot)> {
+    let mut slots = Vec::new();
+    for (monitor_idx, monitor) in state.monitors.elements().iter().enumerate() {
+        for (workspace_idx, workspace) in monitor.workspaces().iter().enumerate() {
+            for container in workspace.containers() {
+                slots.push((
+                    container.id().clone(),
+                    ContainerSlot {
+                        monitor: monitor_idx,
+                        workspace: workspace_idx,
+                        hwnds: container.windows().iter().map(|w| w.hwnd).collect(),
+                    },
+                ));
+            }
+        }
+    }
+    slots
+}
+
+fn floating_slots(state: &State) -> HashMap<isize, (usize, usize)> {
+    let mut slots = HashMap::new();
+    for (monitor_idx, monitor) in state.monitors.elements().iter().enumerate() {
+        for (workspace_idx, workspace) in monitor.workspaces().iter().enumerate() {
+            for window in workspace.floating_windows.iter() {
+                slots.insert(window.hwnd, (monitor_idx, workspace_idx));
+            }
+        }
+    }
+    slots
+}
+
+/// A copy of the state with everything the granular exporter can express reset, so whatever
+/// still differs between two stripped states needs an `ApplyState`.
+fn strip_expressible(state: &State) -> State {
+    let mut state = state.clone();
+    for monitor in state.monitors.elements_mut() {
+        for workspace in monitor.workspaces.elements_mut() {
+            workspace.containers = Ring::default();
+            workspace.floating_windows = Vec::new();
+            // Unadjusted containers follow their container around; komorebi pads them itself
+            if workspace.resize_dimensions.iter().all(Option::is_none) {
+                workspace.resize_dimensions = Vec::new();
+            }
+            if let Layout::Default(_) = workspace.layout {
+                workspace.layout = Layout::Default(DefaultLayout::BSP);
+            }
+        }
+        monitor.workspaces.focus(0);
+    }
+    state.monitors.focus(0);
+    state
+}
+
+/// The exporter's running picture of komorebi while commands are queued.
+///
+/// Follows komorebi's behaviour: moved containers go to the back of the target workspace and
+/// focus follows them, and removing a container focuses the one before it.
+struct CommandModel {
+    /// Container ids per (monitor, workspace), in order
+    containers: HashMap<(usize, usize), Vec<String>>,
+    /// Focused container index per (monitor, workspace)
+    focused_container: HashMap<(usize, usize), usize>,
+    /// Focused workspace index per monitor
+    focused_workspace: Vec<usize>,
+    /// The focused (monitor, workspace)
+    active: (usize, usize),
+    commands: Vec<SocketMessage>,
+}
+
+impl CommandModel {
+    fn from_state(state: &State) -> Self {
+        let mut containers = HashMap::new();
+        let mut focused_container = HashMap::new();
+        let mut focused_workspace = Vec::new();
+        for (monitor_idx, monitor) in state.monitors.elements().iter().enumerate() {
+            focused_workspace.push(monitor.focused_workspace_idx());
+            for (workspace_idx, workspace) in monitor.workspaces().iter().enumerate() {
+                let key = (monitor_idx, workspace_idx);
+                containers.insert(
+                    key,
+                    workspace
+                        .containers()
+                        .iter()
+                        .map(|c| c.id().clone())
+                        .collect(),
+                );
+                focused_container.insert(key, workspace.focused_container_idx());
+            }
+        }
+        let focused_monitor = state.monitors.focused_idx();
+        let active = (
+            focused_monitor,
+            focused_workspace
+                .get(focused_monitor)
+                .copied()
+                .unwrap_or_default(),
+        );
+
+        Self {
+            containers,
+            focused_container,
+            focused_workspace,
+            active,
+            commands: Vec::new(),
+        }
+    }
+
+    fn focus_workspace(&mut self, monitor: usize, workspace: usize) {
+        if self.active != (monitor, workspace) {
+            self.commands
+                .push(SocketMessage::FocusMonitorWorkspaceNumber(
+                    monitor, workspace,
+                ));
+            self.active = (monitor, workspace);
+        }
+        if let Some(focused) = self.focused_workspace.get_mut(monitor) {
+            *focused = workspace;
+        }
+    }
+
+    /// Focuses a container by cycling through its workspace.
+    fn focus_container(&mut self, monitor: usize, workspace: usize, id: &str) -> Option<()> {
+        self.focus_workspace(monitor, workspace);
+        let key = (monitor, workspace);
+        let ids = self.containers.get(&key)?;
+        let target = ids.iter().position(|c| c == id)?;
+        let focused = self
+            .focused_container
+            .get(&key)
+            .copied()
+            .unwrap_or_default()
+            .min(ids.len() - 1);
+        let steps = (target + ids.len() - focused) % ids.len();
+        for _ in 0..steps {
+            self.commands
+                .push(SocketMessage::CycleFocusWindow(CycleDirection::Next));
+        }
+        self.focused_container.insert(key, target);
+        Some(())
+    }
+
+    fn remove_focused_container(&mut self) -> Option<String> {
+        let ids = self.containers.get_mut(&self.active)?;
+        let focused = self.focused_container.get(&self.active).copied()?;
+        if focused >= ids.len() {
+            return None;
+        }
+        let id = ids.remove(focused);
+        self.focused_container
+            .insert(self.active, focused.saturating_sub(1));
+        Some(id)
+    }
+
+    fn move_focused_container(&mut self, monitor: usize, workspace: usize) -> Option<()> {
+        self.commands
+            .push(SocketMessage::MoveContainerToMonitorWorkspaceNumber(
+                monitor, workspace,
+            ));
+        let id = self.remove_focused_container()?;
+        let target = self.containers.get_mut(&(monitor, workspace))?;
+        target.push(id);
+        let focused = target.len() - 1;
+        self.focused_container.insert((monitor, workspace), focused);
+        self.active = (monitor, workspace);
+        if let Some(focused_workspace) = self.focused_workspace.get_mut(monitor) {
+            *focused_workspace = workspace;
+        }
+        Some(())
+    }
+
+    fn toggle_float_focused(&mut self) -> Option<()> {
+        self.commands.push(SocketMessage::ToggleFloat);
+        self.remove_focused_container().map(|_| ())
+    }
+}
+
+/// Translates the difference between komorebi's state and a target state into granular
+/// commands.
+///
+/// Container moves, floating toggles, default layout changes and focus changes are expressed
+/// as commands. Returns `None` when the target can't be reached that way, in which case the
+/// caller should fall back to `ApplyState`.
+pub fn diff_states(current: &State, target: &State) -> Option<Vec<SocketMessage>> {
+    if strip_expressible(current).has_been_modified(&strip_expressible(target)) {
+        return None;
+    }
+
+    let current_containers = container_slots(current);
+    let target_containers: HashMap<String, ContainerSlot> =
+        container_slots(target).into_iter().collect();
+    let current_floating = floating_slots(current);
+    let target_floating = floating_slots(target);
+
+    let current_ids: HashSet<&String> = current_containers.iter().map(|(id, _)| id).collect();
+    if target_containers.keys().any(|id| !current_ids.contains(id)) {
+        // New containers can only come from windows komorebi doesn't manage yet
+        return None;
+    }
+    for (hwnd, slot) in &current_floating {
+        if target_floating.get(hwnd) != Some(slot) {
+            return None;
+        }
+    }
+
+    let mut model = CommandModel::from_state(current);
+    let mut floated = HashSet::new();
+
+    for (id, slot) in &current_containers {
+        match target_containers.get(id) {
+            Some(target_slot) => {
+                if target_slot.hwnds != slot.hwnds {
+                    return None;
+                }
+                if (target_slot.monitor, target_slot.workspace) != (slot.monitor, slot.workspace) {
+                    model.focus_container(slot.monitor, slot.workspace, id)?;
+                    model.move_focused_container(target_slot.monitor, target_slot.workspace)?;
+                }
+            }
+            None => {
+                // Only a container whose single window became floating on the same workspace
+                let [hwnd] = slot.hwnds[..] else {
+                    return None;
+                };
+                if target_floating.get(&hwnd) != Some(&(slot.monitor, slot.workspace)) {
+                    return None;
+                }
+                model.focus_container(slot.monitor, slot.workspace, id)?;
+                model.toggle_float_focused()?;
+                floated.insert(hwnd);
+            }
+        }
+    }
+    if target_floating
+        .keys()
+        .any(|hwnd| !current_floating.contains_key(hwnd) && !floated.contains(hwnd))
+    {
+        return None;
+    }
+
+    // The queued commands must leave every workspace in the target's container order
+    for (monitor_idx, monitor) in target.monitors.elements().iter().enumerate() {
+        for (workspace_idx, workspace) in monitor.workspaces().iter().enumerate() {
+            let target_ids: Vec<&String> = workspace.containers().iter().map(|c| c.id()).collect();
+            let model_ids: Vec<&String> = model
+                .containers
+                .get(&(monitor_idx, workspace_idx))
+                .map(|ids| ids.iter().collect())
+                .unwrap_or_default();
+            if target_ids != model_ids {
+                return None;
+            }
+        }
+    }
+
+    let current_monitors = current.monitors.elements();
+    for (monitor_idx, monitor) in target.monitors.elements().iter().enumerate() {
+        let current_monitor = current_monitors.get(monitor_idx)?;
+        for (workspace_idx, workspace) in monitor.workspaces().iter().enumerate() {
+            let current_workspace = current_monitor.workspaces().get(workspace_idx)?;
+            if let (Layout::Default(from), Layout::Default(to)) =
+                (&current_workspace.layout, &workspace.layout)
+            {
+                if from != to {
+                    model.focus_workspace(monitor_idx, workspace_idx);
+                    model.commands.push(SocketMessage::ChangeLayout(*to));
+                }
+            }
+
+            for container in workspace.containers() {
+                let stack_idx = container.focused_window_idx();
+                let current_stack_idx = current_workspace
+                    .containers()
+                    .iter()
+                    .find(|c| c.id() == container.id())
+                    .map(|c| c.focused_window_idx());
+                if container.windows().len() > 1 && current_stack_idx != Some(stack_idx) {
+                    model.focus_container(monitor_idx, workspace_idx, container.id())?;
+                    model
+                        .commands
+                        .push(SocketMessage::FocusStackWindow(stack_idx));
+                }
+            }
+
+            if let Some(focused) = workspace
+                .containers()
+                .get(workspace.focused_container_idx())
+            {
+                let key = (monitor_idx, workspace_idx);
+                if model.focused_container.get(&key) != Some(&workspace.focused_container_idx()) {
+                    model.focus_container(monitor_idx, workspace_idx, focused.id())?;
+                }
+            }
+        }
+    }
+
+    let focused_monitor = target.monitors.focused_idx();
+    for (monitor_idx, monitor) in target.monitors.elements().iter().enumerate() {
+        if monitor_idx != focused_monitor
+            && model.focused_workspace.get(monitor_idx) != Some(&monitor.focused_workspace_idx())
+        {
+            model.focus_workspace(monitor_idx, monitor.focused_workspace_idx());
+        }
+    }
+    let focused_workspace = target
+        .monitors
+        .elements()
+        .get(focused_monitor)?
+        .focused_workspace_idx();
+    model.focus_workspace(focused_monitor, focused_workspace);
+
+    if model.commands.is_empty() {
+        // Something changed that none of the commands above cover
+        return None;
+    }
+    Some(model.commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shadow::simulate_message;
+    use crate::test_states::*;
+    use crate::transport::{KomorebiTransport, MockTransport};
+
+    /// Sends messages through a [`MockTransport`] and returns what it recorded.
+    fn send(messages: &[SocketMessage]) -> Vec<SocketMessage> {
+        let mock = MockTransport::default();
+        let transport = KomorebiTransport(Box::new(mock.clone()));
+        assert!(transport.send_all(messages).is_ok());
+        mock.sent()
+    }
+
+    fn assert_sent(current: &State, target: &State, expected: &[SocketMessage]) {
+        let messages = diff_states(current, target);
+        assert!(
+            messages.is_some(),
+            "expected granular commands, got ApplyState"
+        );
+        let messages = messages.unwrap_or_default();
+        assert_eq!(json(&send(&messages)), json(expected));
+
+        // komorebi carrying out the commands ends up at the target
+        let mut simulated = current.clone();
+        for message in &messages {
+            assert!(simulate_message(&mut simulated, message), "{:?}", message);
+        }
+        assert_eq!(tiled_hwnds(&simulated), tiled_hwnds(target));
+        assert_eq!(floating_hwnds(&simulated), floating_hwnds(target));
+        assert_eq!(focused_workspace(&simulated), focused_workspace(target));
+    }
+
+    /// Moves a container to the end of another workspace, keeping its id.
+    fn move_container(state: &mut State, from: (usize, usize), container: usize, to: usize) {
+        let Some(monitor) = state.monitors.elements_mut().get_mut(from.0) else {
+            return;
+        };
+        let Some(moved) = monitor
+            .workspaces
+            .elements_mut()
+            .get_mut(from.1)
+            .and_then(|workspace| {
+                workspace.resize_dimensions.pop();
+                workspace.containers.elements_mut().remove(container)
+            })
+        else {
+            return;
+        };
+        if let Some(workspace) = monitor.workspaces.elements_mut().get_mut(to) {
+            workspace.containers.elements_mut().push_back(moved);
+            workspace.resize_dimensions.push(None);
+        }
+    }
+
+    #[test]
+    fn moving_a_container_focuses_it_first() {
+        let current = two_workspaces(&[&[1], &[2]], 0);
+        let mut target = current.clone();
+        move_container(&mut target, (0, 0), 1, 1);
+
+        assert_sent(
+            &current,
+            &target,
+            &[
+                SocketMessage::CycleFocusWindow(CycleDirection::Next),
+                SocketMessage::MoveContainerToMonitorWorkspaceNumber(0, 1),
+                SocketMessage::FocusMonitorWorkspaceNumber(0, 0),
+            ],
+        );
+    }
+
+    #[test]
+    fn swapping_containers_moves_both() {
+        let current = state(vec![monitor(
+            "m1",
+            vec![workspace("a", &[&[1]]), workspace("b", &[&[2]])],
+        )]);
+        let mut target = current.clone();
+        move_container(&mut target, (0, 0), 0, 1);
+        move_container(&mut target, (0, 1), 0, 0);
+
+        assert_sent(
+            &current,
+            &target,
+            &[
+                SocketMessage::MoveContainerToMonitorWorkspaceNumber(0, 1),
+                SocketMessage::CycleFocusWindow(CycleDirection::Next),
+                SocketMessage::MoveContainerToMonitorWorkspaceNumber(0, 0),
+            ],
+        );
+    }
+
+    #[test]
+    fn floating_a_window_toggles_it() {
+        let current = two_workspaces(&[&[1], &[2]], 0);
+        let mut target = current.clone();
+        if let Some(workspace) = target
+            .monitors
+            .elements_mut()
+            .front_mut()
+            .and_then(|monitor| monitor.workspaces.elements_mut().front_mut())
+        {
+            workspace.containers.elements_mut().pop_back();
+            workspace.resize_dimensions.pop();
+            workspace.floating_windows.push(window(2));
+        }
+
+        assert_sent(
+            &current,
+            &target,
+            &[
+                SocketMessage::CycleFocusWindow(CycleDirection::Next),
+                SocketMessage::ToggleFloat,
+            ],
+        );
+    }
+
+    #[test]
+    fn layout_changes_focus_their_workspace() {
+        let current = two_workspaces(&[&[1], &[2]], 0);
+        let mut target = current.clone();
+        if let Some(monitor) = target.monitors.elements_mut().front_mut() {
+            if let Some(workspace) = monitor.workspaces.elements_mut().get_mut(1) {
+                workspace.layout = Layout::Default(DefaultLayout::Columns);
+            }
+            monitor.workspaces.focus(1);
+        }
+
+        assert_sent(
+            &current,
+            &target,
+            &[
+                SocketMessage::FocusMonitorWorkspaceNumber(0, 1),
+                SocketMessage::ChangeLayout(DefaultLayout::Columns),
+            ],
+        );
+    }
+
+    #[test]
+    fn unknown_containers_need_apply_state() {
+        let current = two_workspaces(&[&[1], &[2]], 0);
+        let mut target = current.clone();
+        if let Some(workspace) = target
+            .monitors
+            .elements_mut()
+            .front_mut()
+            .and_then(|monitor| monitor.workspaces.elements_mut().get_mut(1))
+        {
+            workspace
+                .containers
+                .elements_mut()
+                .push_back(container(&[3]));
+            workspace.resize_dimensions.push(None);
+        }
+        assert!(diff_states(&current, &target).is_none());
+        assert!(diff_states(&current, &current).is_none());
+    }
+}
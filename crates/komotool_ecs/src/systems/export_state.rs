@@ -1,13 +1,12 @@
 use crate::KomorebiState;
 use crate::components::{FloatingWindow, Focused, MaximizedWindow, MonocleContainer};
 use crate::relations::registry::RelationRegistry;
-use crate::resources::{AppState, KomotoolState};
+use crate::resources::AppState;
+use crate::transport::KomorebiTransport;
+use crate::validation::{StateValidation, checked_export_state};
 use bevy_ecs::query::With;
 use bevy_ecs::system::{Query, Res};
-use bevy_ecs::world::World;
-use komorebi_client::{
-    Container, Monitor, Ring, SocketMessage, State, Window, Workspace, send_message,
-};
+use komorebi_client::{Container, Monitor, Ring, SocketMessage, State, Window, Workspace};
 
 #[allow(clippy::too_many_arguments)]
 pub fn export_state(
@@ -19,7 +18,53 @@ pub fn export_state(
     window_query: Query<(&Window, Option<&FloatingWindow>)>,
     focused_query: Query<(), With<Focused>>,
     komorebi_state: Res<KomorebiState>,
+    validation: Res<StateValidation>,
+    transport: Res<KomorebiTransport>,
 ) {
+    let state = build_state_from_registry(
+        &registry,
+        &app_state,
+        &monitor_query,
+        &workspace_query,
+        &container_query,
+        &window_query,
+        &focused_query,
+    );
+
+    // Send the state to komorebi
+    if let Some(komorebi_state) = komorebi_state.komorebi.as_ref() {
+        if !komorebi_state.has_been_modified(&state) {
+            //println!("No changes to komorebi state, skipping");
+            return;
+        }
+    } else {
+        //println("No komorebi state, skipping");
+        return;
+    }
+    let state = match checked_export_state(state, validation.policy) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("Not sending ApplyState message to komorebi: {}", e);
+            return;
+        }
+    };
+    let message = SocketMessage::ApplyState(state);
+    match transport.send_all(&[message]) {
+        Ok(_) => println!("Successfully sent ApplyState message to komorebi"),
+        Err(e) => eprintln!("Failed to send ApplyState message to komorebi: {}", e),
+    }
+}
+
+/// Rebuilds a komorebi [`State`] from the ECS mirror by walking the sorted [`RelationRegistry`].
+pub fn build_state_from_registry(
+    registry: &RelationRegistry,
+    app_state: &AppState,
+    monitor_query: &Query<&Monitor>,
+    workspace_query: &Query<&Workspace>,
+    container_query: &Query<&Container>,
+    window_query: &Query<(&Window, Option<&FloatingWindow>)>,
+    focused_query: &Query<(), With<Focused>>,
+) -> State {
     // 1. Initialize State with global properties
     let mut state = State {
         monitors: Ring::default(),
@@ -48,7 +93,7 @@ pub fn export_state(
     let mut window_focus_idx: Option<usize> = None;
 
     // 3. Iterate through sorted registry records to reconstruct hierarchy
-    for record in &registry.records {
+    for record in registry.records.iter() {
         let entity = record.entity;
 
         let (m_idx, w_idx, c_idx, win_idx) = record.key();
@@ -65,7 +110,10 @@ pub fn export_state(
                         current_workspace
                             .containers
                             .elements_mut()
-                            .push_back(current_container);
+                            .push_back(finish_container(
+                                current_container,
+                                window_focus_idx.take(),
+                            ));
                     }
                     if let Some(focus_idx) = container_focus_idx.take() {
                         current_workspace.containers.focus(focus_idx);
@@ -97,14 +145,15 @@ pub fn export_state(
         }
 
         // --- Workspace Level ---
-        if c_idx == 0 {
+        // Floating windows are recorded as (monitor, workspace, 0, n) and fall through
+        if c_idx == 0 && win_idx == 0 {
             // Finish previous workspace if exists
             if let Some(mut current_workspace) = current_workspace_opt.take() {
                 if let Some(current_container) = current_container_opt.take() {
                     current_workspace
                         .containers
                         .elements_mut()
-                        .push_back(current_container);
+                        .push_back(finish_container(current_container, window_focus_idx.take()));
                 }
                 if let Some(focus_idx) = container_focus_idx.take() {
                     current_workspace.containers.focus(focus_idx);
@@ -141,6 +190,8 @@ pub fn export_state(
         if win_idx == 0 {
             // Finish previous container if exists
             if let Some(current_container) = current_container_opt.take() {
+                let current_container =
+                    finish_container(current_container, window_focus_idx.take());
                 if let Some(ref mut current_workspace) = current_workspace_opt {
                     current_workspace
                         .containers
@@ -195,14 +246,11 @@ pub fn export_state(
     // 4. Push the last remaining items
     if let Some((mut current_monitor, _)) = current_monitor_opt.take() {
         if let Some(mut current_workspace) = current_workspace_opt.take() {
-            if let Some(mut current_container) = current_container_opt.take() {
-                if let Some(focus_idx) = window_focus_idx.take() {
-                    current_container.focus_window(focus_idx);
-                }
+            if let Some(current_container) = current_container_opt.take() {
                 current_workspace
                     .containers
                     .elements_mut()
-                    .push_back(current_container);
+                    .push_back(finish_container(current_container, window_focus_idx.take()));
             }
             if let Some(focus_idx) = container_focus_idx.take() {
                 current_workspace.containers.focus(focus_idx);
@@ -219,87 +267,26 @@ pub fn export_state(
     }
 
     // 5. Finalize state object
+    // Resize dimensions aren't mirrored per container, keep one per container for validation
+    for monitor in monitors_vec.iter_mut() {
+        for workspace in monitor.workspaces.elements_mut() {
+            let containers = workspace.containers().len();
+            workspace.resize_dimensions.resize(containers, None);
+        }
+    }
     state.monitors.elements_mut().extend(monitors_vec);
     if let Some(focus_idx) = monitor_focus_idx {
         state.monitors.focus(focus_idx);
     }
 
-    // 6. Send the state to komorebi
-    if let Some(komorebi_state) = komorebi_state.komorebi.as_ref() {
-        if !komorebi_state.has_been_modified(&state) {
-            //println!("No changes to komorebi state, skipping");
-            return;
-        }
-    } else {
-        //println("No komorebi state, skipping");
-        return;
-    }
-    let message = SocketMessage::ApplyState(state);
-    match send_message(&message) {
-        Ok(_) => println!("Successfully sent ApplyState message to komorebi"),
-        Err(e) => eprintln!("Failed to send ApplyState message to komorebi: {}", e),
-    }
+    state
 }
-pub fn export_state_to_komorebi(world: &mut World) {
-    let mut komotool_state_is_some = false;
-
-    // Step 1: Check KomotoolState.current in a scope
-    {
-        let komotool_state_res = world.get_resource::<KomotoolState>();
-        if let Some(state_res) = komotool_state_res {
-            if state_res.current.is_some() {
-                komotool_state_is_some = true;
-            }
-        }
-        // Implicitly drop the borrow of komotool_state_res here
-    }
-
-    // Step 2: Conditional Flush
-    if komotool_state_is_some {
-        // Only flush if KomotoolState.current was Some initially
-        world.flush(); // Apply any queued commands
-    } else {
-        // KomotoolState.current was None, nothing to flush or send.
-        // println!("KomotoolState.current is None initially, skipping flush and send.");
-        return;
-    }
-
-    // Step 3: Comparison and Send (after potential flush)
-    // Re-fetch resources after flush
-    let komorebi_state_res = world.get_resource::<KomorebiState>();
-    let komotool_state_res = world.get_resource::<KomotoolState>();
 
-    // Use if let to ensure both states are Some before comparing
-    if let (Some(komorebi_state_res), Some(komotool_state_res)) =
-        (komorebi_state_res, komotool_state_res)
-    {
-        if let (Some(komorebi_s), Some(komotool_s)) = (
-            komorebi_state_res.komorebi.as_ref(),
-            komotool_state_res.current.as_ref(),
-        ) {
-            // Both states exist, compare them
-            if komotool_s.has_been_modified(komorebi_s) {
-                // States are different, send the Komotool state
-                println!(
-                    "Komotool state differs from Komorebi state after flush, sending ApplyState to komotool"
-                );
-                let message = SocketMessage::ApplyState(komotool_s.clone());
-                match send_message(&message) {
-                    Ok(_) => println!("Successfully sent ApplyState message to komotool"),
-                    Err(e) => eprintln!("Failed to send ApplyState message to komotool: {}", e),
-                }
-            } else {
-                // States are the same, do nothing
-                // println!("Komotool state matches Komorebi state after flush, skipping send.");
-            }
-        } else {
-            // At least one of the inner Option<&State> was None, do nothing.
-            // This covers cases where KomorebiState exists but komorebi is None,
-            // or KomotoolState exists but current is None (e.g., after flush).
-            // println!("Either Komorebi or Komotool state content is None after flush, skipping send.");
-        }
-    } else {
-        // At least one of the resources (KomorebiState or KomotoolState) doesn't exist, do nothing.
-        // println!("KomorebiState or KomotoolState resource missing after flush, skipping send.");
+/// Focuses the window the registry marked as focused before the container is pushed, so
+/// every container keeps its own focus.
+fn finish_container(mut container: Container, window_focus_idx: Option<usize>) -> Container {
+    if let Some(focus_idx) = window_focus_idx {
+        container.focus_window(focus_idx);
     }
+    container
 }
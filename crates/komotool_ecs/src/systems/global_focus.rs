@@ -0,0 +1,121 @@
+use crate::components::{FocusedGlobal, LastFocused};
+use crate::events::{FocusChanged, WindowDespawned};
+use crate::resources::{
+    ContainerToEntityMap, FocusHistory, KomorebiState, MonitorToEntityMap, WindowToEntityMap,
+    WorkspaceToEntityMap,
+};
+use crate::systems::workspace_key;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::event::EventReader;
+use bevy_ecs::query::With;
+use bevy_ecs::system::{Commands, Query, Res, ResMut};
+use komorebi_client::State;
+
+/// The globally focused monitor, workspace, container and window, outermost first.
+///
+/// A maximized window has no container, so the chain can be shorter than four entities.
+pub fn global_focus_chain(
+    state: &State,
+    monitor_map: &MonitorToEntityMap,
+    workspace_map: &WorkspaceToEntityMap,
+    container_map: &ContainerToEntityMap,
+    window_map: &WindowToEntityMap,
+) -> Vec<Entity> {
+    let mut chain = Vec::new();
+    let monitor_idx = state.monitors.focused_idx();
+    let Some(komo_mon) = state.monitors.elements().get(monitor_idx) else {
+        return chain;
+    };
+    if let Some(serial) = komo_mon.serial_number_id() {
+        if let Some(entity) = monitor_map.0.get(serial) {
+            chain.push(*entity);
+        }
+    }
+
+    let workspace_idx = komo_mon.focused_workspace_idx();
+    let Some(komo_ws) = komo_mon.workspaces().get(workspace_idx) else {
+        return chain;
+    };
+    let key = workspace_key(komo_mon, monitor_idx, workspace_idx, komo_ws);
+    if let Some(entity) = workspace_map.0.get(&key) {
+        chain.push(*entity);
+    }
+
+    let (container, window) = if let Some(max) = komo_ws.maximized_window() {
+        (None, Some(max))
+    } else if let Some(monocle) = komo_ws.monocle_container() {
+        (Some(monocle), monocle.focused_window())
+    } else {
+        let container = komo_ws.focused_container();
+        (container, container.and_then(|c| c.focused_window()))
+    };
+    if let Some(entity) = container.and_then(|c| container_map.0.get(c.id())) {
+        chain.push(*entity);
+    }
+    if let Some(entity) = window.and_then(|w| window_map.0.get(&w.hwnd.to_string())) {
+        chain.push(*entity);
+    }
+    chain
+}
+
+/// Puts [`FocusedGlobal`] on the focus chain, [`LastFocused`] on the previously focused
+/// window, and keeps [`FocusHistory`] in most-recently-used order.
+#[allow(clippy::too_many_arguments)]
+pub fn update_global_focus(
+    mut commands: Commands,
+    komorebi_state: Res<KomorebiState>,
+    monitor_map: Res<MonitorToEntityMap>,
+    workspace_map: Res<WorkspaceToEntityMap>,
+    container_map: Res<ContainerToEntityMap>,
+    window_map: Res<WindowToEntityMap>,
+    focused_global: Query<Entity, With<FocusedGlobal>>,
+    last_focused: Query<Entity, With<LastFocused>>,
+    mut focus_changed: EventReader<FocusChanged>,
+    mut despawned: EventReader<WindowDespawned>,
+    mut history: ResMut<FocusHistory>,
+) {
+    for event in despawned.read() {
+        history.remove(event.entity);
+    }
+
+    let Some(state) = &komorebi_state.komorebi else {
+        return;
+    };
+    let chain = global_focus_chain(
+        state,
+        &monitor_map,
+        &workspace_map,
+        &container_map,
+        &window_map,
+    );
+    for entity in focused_global.iter() {
+        if !chain.contains(&entity) {
+            commands.entity(entity).remove::<FocusedGlobal>();
+        }
+    }
+    for entity in &chain {
+        if !focused_global.contains(*entity) {
+            commands.entity(*entity).insert(FocusedGlobal);
+        }
+    }
+
+    for event in focus_changed.read() {
+        if let Some(to) = event.to {
+            history.touch(to.entity);
+        }
+        let Some(from) = event.from.filter(|from| Some(*from) != event.to) else {
+            continue;
+        };
+        for entity in last_focused.iter() {
+            if entity != from.entity {
+                commands.entity(entity).remove::<LastFocused>();
+            }
+        }
+        if last_focused.contains(from.entity) {
+            continue;
+        }
+        if let Some(mut entity) = commands.get_entity(from.entity) {
+            entity.insert(LastFocused);
+        }
+    }
+}
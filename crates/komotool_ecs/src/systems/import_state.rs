@@ -1,65 +1,90 @@
 use crate::RelationRegistry;
-use crate::components::Focused;
 use crate::components::*;
+use crate::events::*;
+use crate::relations::*;
 use crate::resources::*;
-use bevy_ecs::query::QueryEntityError;
-use bevy_ecs::system::{Commands, Query, Res, ResMut};
+use bevy_ecs::change_detection::DetectChangesMut;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::event::EventWriter;
+use bevy_ecs::query::{Has, QueryEntityError};
+use bevy_ecs::system::{Commands, Local, Query, Res, ResMut};
 use komorebi_client::{Container, Monitor, Window, Workspace};
-use std::collections::{HashSet, hash_map::Entry};
+use std::collections::{HashMap, HashSet, hash_map::Entry};
 
 pub fn import_komorebi_workspace_state(
     mut commands: Commands,
     mut existing_workspaces: Query<&mut Workspace>,
     komorebi_state: Res<KomorebiState>,
     mut workspace_map: ResMut<WorkspaceToEntityMap>,
-    registry: Res<RelationRegistry>,
-    extended_marker_map: Res<ExtendedMarkerMap>,
     mut keep_alive_workspaces: ResMut<KeepAliveWorkspaces>,
 ) {
     let Some(state) = &komorebi_state.komorebi else {
         return;
     };
 
+    // Move unnamed workspace entities to their new keys before updating
+    let mut slots = Vec::new();
+    for (monitor_idx, komo_mon) in state.monitors.elements().iter().enumerate() {
+        for (workspace_idx, komo_ws) in komo_mon.workspaces().iter().enumerate() {
+            slots.push(WorkspaceSlot {
+                key: workspace_key(komo_mon, monitor_idx, workspace_idx, komo_ws),
+                position_key: workspace_position_key(komo_mon, monitor_idx, workspace_idx),
+                named: komo_ws.name().is_some(),
+                containers: komo_ws
+                    .containers()
+                    .iter()
+                    .map(|c| c.id().clone())
+                    .collect(),
+            });
+        }
+    }
+    let previous_containers = workspace_map
+        .0
+        .iter()
+        .filter(|(key, _)| is_unnamed_workspace_key(key))
+        .filter_map(|(_, entity)| {
+            let workspace = existing_workspaces.get(*entity).ok()?;
+            let containers = workspace
+                .containers()
+                .iter()
+                .map(|c| c.id().clone())
+                .collect();
+            Some((*entity, containers))
+        })
+        .collect();
+    let rekeys = plan_workspace_rekeys(&workspace_map.0, &previous_containers, &slots);
+    let moved: Vec<(String, Entity)> = rekeys
+        .into_iter()
+        .filter_map(|(old_key, new_key)| Some((new_key, workspace_map.0.remove(&old_key)?)))
+        .collect();
+    for (new_key, entity) in moved {
+        if let Some(displaced) = workspace_map.0.insert(new_key.clone(), entity) {
+            // The position now belongs to a workspace that moved here
+            if keep_alive_workspaces.0.contains(&displaced) {
+                workspace_map
+                    .0
+                    .insert(format!("{}~{}", new_key, displaced), displaced);
+            } else {
+                commands.entity(displaced).despawn();
+            }
+        }
+    }
+
     let mut current_keys = HashSet::new();
 
-    for komo_mon in state.monitors.elements() {
-        let workspaces = komo_mon.workspaces();
-        for komo_ws in workspaces.iter() {
-            // Use name if available, otherwise fall back to ID
-            let key = match komo_ws.name() {
-                Some(name) => name.clone(),
-                None => continue,
-            };
+    for (monitor_idx, komo_mon) in state.monitors.elements().iter().enumerate() {
+        for (workspace_idx, komo_ws) in komo_mon.workspaces().iter().enumerate() {
+            let key = workspace_key(komo_mon, monitor_idx, workspace_idx, komo_ws);
             current_keys.insert(key.clone());
 
             match workspace_map.0.entry(key) {
                 Entry::Occupied(entry) => {
                     let entity = *entry.get();
 
-                    // Despawn all relevant marker components
-                    if let Some(record) = registry.records.get(&entity) {
-                        // Despawn Monitor marker
-                        if record.monitor > 0 {
-                            despawn_monitor_marker_component(
-                                record.monitor,
-                                entity,
-                                commands.reborrow(),
-                                &extended_marker_map,
-                            );
-                        }
-                        // Despawn Workspace marker
-                        if record.workspace > 0 {
-                            despawn_workspace_marker_component(
-                                record.workspace,
-                                entity,
-                                commands.reborrow(),
-                                &extended_marker_map,
-                            );
-                        }
-                    }
-
                     if let Ok(mut workspace) = existing_workspaces.get_mut(entity) {
-                        *workspace = komo_ws.clone();
+                        if *workspace != *komo_ws {
+                            *workspace = komo_ws.clone();
+                        }
                     }
                 }
                 Entry::Vacant(entry) => {
@@ -73,8 +98,7 @@ pub fn import_komorebi_workspace_state(
     workspace_map.0.retain(|key, entity| {
         if current_keys.contains(key) {
             // Workspace is still managed by Komorebi, keep it.
-            // Markers were cleared during the update phase if it existed previously.
-            // build_relation_registry will add the correct markers and focus state.
+            // build_relation_registry updates whatever markers and focus state changed.
             true
         } else {
             // Workspace is no longer managed by Komorebi. Check if we should keep it alive.
@@ -82,28 +106,7 @@ pub fn import_komorebi_workspace_state(
                 // Check if the entity actually still exists and has the component
                 match existing_workspaces.get(*entity) {
                     Ok(_) => {
-                        // Entity exists: Keep it alive, but remove its markers and focus.
-                        if let Some(record) = registry.records.get(entity) {
-                            // Despawn Monitor marker
-                            if record.monitor > 0 {
-                                despawn_monitor_marker_component(
-                                    record.monitor,
-                                    *entity,
-                                    commands.reborrow(),
-                                    &extended_marker_map,
-                                );
-                            }
-                            // Despawn Workspace marker
-                            if record.workspace > 0 {
-                                despawn_workspace_marker_component(
-                                    record.workspace,
-                                    *entity,
-                                    commands.reborrow(),
-                                    &extended_marker_map,
-                                );
-                            }
-                        }
-                        commands.entity(*entity).remove::<Focused>();
+                        // Entity exists: Keep it alive, build_relation_registry removes its markers and focus.
                         true // Keep the entity in the map
                     }
                     Err(error) => {
@@ -136,9 +139,11 @@ pub fn import_komorebi_monitor_state(
     mut existing_monitors: Query<&mut Monitor>,
     komorebi_state: Res<KomorebiState>,
     mut monitor_map: ResMut<MonitorToEntityMap>,
-    registry: Res<RelationRegistry>,
-    extended_marker_map: Res<ExtendedMarkerMap>,
     mut keep_alive_monitors: ResMut<KeepAliveMonitors>,
+    mut connected: EventWriter<MonitorConnected>,
+    mut disconnected: EventWriter<MonitorDisconnected>,
+    // Serials connected as of the last import; kept-alive monitors stay mapped while disconnected
+    mut connected_serials: Local<HashSet<String>>,
 ) {
     let Some(state) = &komorebi_state.komorebi else {
         return;
@@ -156,25 +161,26 @@ pub fn import_komorebi_monitor_state(
             Entry::Occupied(entry) => {
                 let entity = *entry.get();
 
-                // Despawn the old marker component if it exists
-                if let Some(record) = registry.records.get(&entity) {
-                    if record.monitor > 0 {
-                        despawn_monitor_marker_component(
-                            record.monitor,
-                            entity,
-                            commands.reborrow(),
-                            &extended_marker_map,
-                        );
+                if let Ok(mut monitor) = existing_monitors.get_mut(entity) {
+                    if *monitor != *komo_mon {
+                        *monitor = komo_mon.clone();
                     }
                 }
-
-                if let Ok(mut monitor) = existing_monitors.get_mut(entity) {
-                    *monitor = komo_mon.clone();
+                // A kept-alive monitor that came back
+                if !connected_serials.contains(serial) {
+                    connected.send(MonitorConnected {
+                        entity,
+                        serial: serial.clone(),
+                    });
                 }
             }
             Entry::Vacant(entry) => {
                 let entity = commands.spawn(komo_mon.clone()).id();
                 entry.insert(entity);
+                connected.send(MonitorConnected {
+                    entity,
+                    serial: serial.clone(),
+                });
             }
         }
     }
@@ -182,27 +188,21 @@ pub fn import_komorebi_monitor_state(
     monitor_map.0.retain(|serial, entity| {
         if current_serials.contains(serial) {
             // Monitor is still managed by Komorebi, keep it.
-            // Markers were cleared during the update phase if it existed previously.
-            // build_relation_registry will add the correct markers and focus state.
+            // build_relation_registry updates whatever markers and focus state changed.
             true
         } else {
+            if connected_serials.contains(serial) {
+                disconnected.send(MonitorDisconnected {
+                    entity: *entity,
+                    serial: serial.clone(),
+                });
+            }
             // Monitor is no longer managed by Komorebi. Check if we should keep it alive.
             if keep_alive_monitors.0.contains(entity) {
                 // Check if the entity actually still exists and has the component
                 match existing_monitors.get(*entity) {
                     Ok(_) => {
-                        // Entity exists: Keep it alive, but remove its marker and focus.
-                        if let Some(record) = registry.records.get(entity) {
-                            if record.monitor > 0 {
-                                despawn_monitor_marker_component(
-                                    record.monitor,
-                                    *entity,
-                                    commands.reborrow(),
-                                    &extended_marker_map,
-                                );
-                            }
-                        }
-                        commands.entity(*entity).remove::<Focused>();
+                        // Entity exists: Keep it alive, build_relation_registry removes its marker and focus.
                         true // Keep the entity in the map
                     }
                     Err(error) => {
@@ -228,15 +228,16 @@ pub fn import_komorebi_monitor_state(
             }
         }
     });
+    *connected_serials = current_serials;
 }
 
 pub fn import_komorebi_window_state(
     mut commands: Commands,
-    mut existing_windows: Query<&mut Window>,
+    mut existing_windows: Query<(&mut Window, Has<FloatingWindow>, Has<MaximizedWindow>)>,
     komorebi_state: Res<KomorebiState>,
     mut window_map: ResMut<WindowToEntityMap>,
-    registry: Res<RelationRegistry>,
-    extended_marker_map: Res<ExtendedMarkerMap>,
+    mut spawned: EventWriter<WindowSpawned>,
+    mut despawned: EventWriter<WindowDespawned>,
 ) {
     let Some(state) = &komorebi_state.komorebi else {
         return;
@@ -249,82 +250,70 @@ pub fn import_komorebi_window_state(
     for komo_mon in state.monitors.elements() {
         let workspaces = komo_mon.workspaces();
         for komo_ws in workspaces.iter() {
-            for komo_cont in komo_ws.containers() {
-                for komo_win in komo_cont.windows() {
-                    let hwnd = komo_win.hwnd.to_string();
-                    current_hwnds.insert(hwnd.clone());
-
-                    match window_map.0.entry(hwnd) {
-                        Entry::Occupied(entry) => {
-                            let entity = *entry.get();
-
-                            // Despawn all relevant marker components
-                            if let Some(record) = registry.records.get(&entity) {
-                                // Despawn Monitor marker
-                                if record.monitor > 0 {
-                                    despawn_monitor_marker_component(
-                                        record.monitor,
-                                        entity,
-                                        commands.reborrow(),
-                                        &extended_marker_map,
-                                    );
-                                }
-                                // Despawn Workspace marker
-                                if record.workspace > 0 {
-                                    despawn_workspace_marker_component(
-                                        record.workspace,
-                                        entity,
-                                        commands.reborrow(),
-                                        &extended_marker_map,
-                                    );
-                                }
-                                // Despawn Container marker
-                                if record.container > 0 {
-                                    despawn_container_marker_component(
-                                        record.container,
-                                        entity,
-                                        commands.reborrow(),
-                                        &extended_marker_map,
-                                    );
-                                }
-                                // Despawn Window marker
-                                if record.window > 0 {
-                                    despawn_window_marker_component(
-                                        record.window,
-                                        entity,
-                                        commands.reborrow(),
-                                        &extended_marker_map,
-                                    );
-                                }
-                            }
-                            commands.entity(entity).remove::<MaximizedWindow>();
-
-                            // Update existing window component
-                            if let Ok(mut window) = existing_windows.get_mut(entity) {
-                                *window = *komo_win;
+            let tiled = komo_ws
+                .containers()
+                .iter()
+                .flat_map(|komo_cont| komo_cont.windows().iter())
+                .map(|komo_win| (komo_win, WindowKind::Tiled));
+            let floating = komo_ws
+                .floating_windows
+                .iter()
+                .map(|komo_win| (komo_win, WindowKind::Floating));
+            let maximized = komo_ws
+                .maximized_window()
+                .into_iter()
+                .map(|komo_win| (komo_win, WindowKind::Maximized));
+
+            for (komo_win, kind) in tiled.chain(floating).chain(maximized) {
+                let hwnd = komo_win.hwnd.to_string();
+                current_hwnds.insert(hwnd.clone());
+
+                match window_map.0.entry(hwnd) {
+                    Entry::Occupied(entry) => {
+                        let entity = *entry.get();
+                        let Ok((mut window, is_floating, is_maximized)) =
+                            existing_windows.get_mut(entity)
+                        else {
+                            continue;
+                        };
+
+                        // Only write what changed, so Changed<T> reflects real changes
+                        window.set_if_neq(*komo_win);
+                        let should_float = kind == WindowKind::Floating;
+                        let should_maximize = kind == WindowKind::Maximized;
+                        if is_floating != should_float {
+                            if should_float {
+                                commands.entity(entity).insert(FloatingWindow);
+                            } else {
+                                commands.entity(entity).remove::<FloatingWindow>();
                             }
                         }
-                        Entry::Vacant(entry) => {
-                            // Spawn new window
-                            let entity = commands.spawn(*komo_win).id();
-                            entry.insert(entity);
+                        if is_maximized != should_maximize {
+                            if should_maximize {
+                                commands.entity(entity).insert(MaximizedWindow);
+                            } else {
+                                commands.entity(entity).remove::<MaximizedWindow>();
+                            }
                         }
                     }
-                }
-            }
-
-            if let Some(max) = komo_ws.maximized_window() {
-                let hwnd = max.hwnd;
-                current_hwnds.insert(hwnd.to_string().clone());
-                match window_map.0.entry(hwnd.to_string().clone()) {
-                    Entry::Occupied(entry) => {
-                        let entity = *entry.get();
-                        commands.entity(entity).insert(MaximizedWindow);
-                    }
-
                     Entry::Vacant(entry) => {
-                        let entity = commands.spawn((*max, MaximizedWindow)).id();
+                        // Spawn new window
+                        let mut spawn = commands.spawn(*komo_win);
+                        match kind {
+                            WindowKind::Tiled => {}
+                            WindowKind::Floating => {
+                                spawn.insert(FloatingWindow);
+                            }
+                            WindowKind::Maximized => {
+                                spawn.insert(MaximizedWindow);
+                            }
+                        }
+                        let entity = spawn.id();
                         entry.insert(entity);
+                        spawned.send(WindowSpawned {
+                            entity,
+                            hwnd: komo_win.hwnd,
+                        });
                     }
                 }
             }
@@ -332,66 +321,28 @@ pub fn import_komorebi_window_state(
     }
 
     // Second pass: Retain/Remove windows based on presence in current state and process status
-    window_map.0.retain(|_hwnd, entity| {
-        if current_hwnds.contains(_hwnd) {
+    window_map.0.retain(|hwnd, entity| {
+        if current_hwnds.contains(hwnd) {
             // Window is still managed by Komorebi, keep it.
-            // Markers were cleared during the update phase if it existed previously.
-            // build_relation_registry will add the correct markers and focus state.
+            // build_relation_registry updates whatever markers and focus state changed.
             true
         } else {
             // Window is no longer managed by Komorebi. Check if its process still exists.
             match existing_windows.get(*entity) {
-                Ok(window) => {
+                Ok((window, _, _)) => {
                     match window.exe() {
                         Ok(_) => {
-                            // Process exists: Keep entity but remove markers and focus.
-                            // Markers *should* have been removed if it was previously managed,
-                            // but clear them again just in case it's an edge case.
-                            if let Some(record) = registry.records.get(entity) {
-                                // Despawn Monitor marker
-                                if record.monitor > 0 {
-                                    despawn_monitor_marker_component(
-                                        record.monitor,
-                                        *entity,
-                                        commands.reborrow(),
-                                        &extended_marker_map,
-                                    );
-                                }
-                                // Despawn Workspace marker
-                                if record.workspace > 0 {
-                                    despawn_workspace_marker_component(
-                                        record.workspace,
-                                        *entity,
-                                        commands.reborrow(),
-                                        &extended_marker_map,
-                                    );
-                                }
-                                // Despawn Container marker
-                                if record.container > 0 {
-                                    despawn_container_marker_component(
-                                        record.container,
-                                        *entity,
-                                        commands.reborrow(),
-                                        &extended_marker_map,
-                                    );
-                                }
-                                // Despawn Window marker
-                                if record.window > 0 {
-                                    despawn_window_marker_component(
-                                        record.window,
-                                        *entity,
-                                        commands.reborrow(),
-                                        &extended_marker_map,
-                                    );
-                                }
-                            }
-                            // Ensure focus is removed as it's no longer managed
-                            commands.entity(*entity).remove::<Focused>();
+                            // Process exists: Keep entity, build_relation_registry removes
+                            // its markers and focus now that it is no longer managed.
                             true // Keep the entity in the map
                         }
                         Err(_) => {
                             // Process doesn't exist: Despawn the entity entirely.
                             commands.entity(*entity).despawn();
+                            despawned.send(WindowDespawned {
+                                entity: *entity,
+                                hwnd: hwnd.parse().unwrap_or_default(),
+                            });
                             false // Remove from map
                         }
                     }
@@ -407,6 +358,10 @@ pub fn import_komorebi_window_state(
                         | QueryEntityError::NoSuchEntity(_) => {
                             // Entity doesn't have the component or doesn't exist. Despawn.
                             commands.entity(*entity).despawn();
+                            despawned.send(WindowDespawned {
+                                entity: *entity,
+                                hwnd: hwnd.parse().unwrap_or_default(),
+                            });
                             false // Remove from map
                         }
                     }
@@ -416,13 +371,19 @@ pub fn import_komorebi_window_state(
     });
 }
 
+/// Where komorebi manages a window on its workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WindowKind {
+    Tiled,
+    Floating,
+    Maximized,
+}
+
 pub fn import_komorebi_container_state(
     mut commands: Commands,
-    mut existing_containers: Query<&mut Container>,
+    mut existing_containers: Query<(&mut Container, Has<MonocleContainer>)>,
     komorebi_state: Res<KomorebiState>,
     mut container_map: ResMut<ContainerToEntityMap>,
-    registry: Res<RelationRegistry>,
-    extended_marker_map: Res<ExtendedMarkerMap>,
     mut keep_alive_containers: ResMut<KeepAliveContainers>,
 ) {
     let Some(state) = &komorebi_state.komorebi else {
@@ -444,44 +405,16 @@ pub fn import_komorebi_container_state(
                     Entry::Occupied(entry) => {
                         let entity = *entry.get();
 
-                        // Despawn all relevant marker components
-                        if let Some(record) = registry.records.get(&entity) {
-                            // Despawn Monitor marker
-                            if record.monitor > 0 {
-                                despawn_monitor_marker_component(
-                                    record.monitor,
-                                    entity,
-                                    commands.reborrow(),
-                                    &extended_marker_map,
-                                );
-                            }
-                            // Despawn Workspace marker
-                            if record.workspace > 0 {
-                                despawn_workspace_marker_component(
-                                    record.workspace,
-                                    entity,
-                                    commands.reborrow(),
-                                    &extended_marker_map,
-                                );
+                        // Update existing container component
+                        if let Ok((mut container, is_monocle)) = existing_containers.get_mut(entity)
+                        {
+                            if *container != *komo_cont {
+                                *container = komo_cont.clone();
                             }
-                            // Despawn Container marker
-                            if record.container > 0 {
-                                despawn_container_marker_component(
-                                    record.container,
-                                    entity,
-                                    commands.reborrow(),
-                                    &extended_marker_map,
-                                );
+                            if is_monocle {
+                                commands.entity(entity).remove::<MonocleContainer>();
                             }
                         }
-                        commands.entity(entity).remove::<MonocleContainer>();
-
-                        // Update existing container component
-                        if let Ok(mut container) = existing_containers.get_mut(entity) {
-                            *container = komo_cont.clone();
-                        }
-
-                        // Insert/update WindowRing component
                     }
                     Entry::Vacant(entry) => {
                         // Spawn new container with WindowRing
@@ -496,7 +429,15 @@ pub fn import_komorebi_container_state(
                 match container_map.0.entry(id.clone()) {
                     Entry::Occupied(entry) => {
                         let entity = *entry.get();
-                        commands.entity(entity).insert(MonocleContainer);
+                        if let Ok((mut container, is_monocle)) = existing_containers.get_mut(entity)
+                        {
+                            if *container != *monocle {
+                                *container = monocle.clone();
+                            }
+                            if !is_monocle {
+                                commands.entity(entity).insert(MonocleContainer);
+                            }
+                        }
                     }
 
                     Entry::Vacant(entry) => {
@@ -513,8 +454,7 @@ pub fn import_komorebi_container_state(
     container_map.0.retain(|id, entity| {
         if current_ids.contains(id) {
             // Container is still managed by Komorebi, keep it.
-            // Markers were cleared during the update phase if it existed previously.
-            // build_relation_registry will add the correct markers and focus state.
+            // build_relation_registry updates whatever markers and focus state changed.
             true
         } else {
             // Container is no longer managed by Komorebi. Check if we should keep it alive.
@@ -522,37 +462,7 @@ pub fn import_komorebi_container_state(
                 // Check if the entity actually still exists and has the component
                 match existing_containers.get(*entity) {
                     Ok(_) => {
-                        // Entity exists: Keep it alive, but remove its markers and focus.
-                        if let Some(record) = registry.records.get(entity) {
-                            // Despawn Monitor marker
-                            if record.monitor > 0 {
-                                despawn_monitor_marker_component(
-                                    record.monitor,
-                                    *entity,
-                                    commands.reborrow(),
-                                    &extended_marker_map,
-                                );
-                            }
-                            // Despawn Workspace marker
-                            if record.workspace > 0 {
-                                despawn_workspace_marker_component(
-                                    record.workspace,
-                                    *entity,
-                                    commands.reborrow(),
-                                    &extended_marker_map,
-                                );
-                            }
-                            // Despawn Container marker
-                            if record.container > 0 {
-                                despawn_container_marker_component(
-                                    record.container,
-                                    *entity,
-                                    commands.reborrow(),
-                                    &extended_marker_map,
-                                );
-                            }
-                        }
-                        commands.entity(*entity).remove::<Focused>();
+                        // Entity exists: Keep it alive, build_relation_registry removes its markers and focus.
                         true // Keep the entity in the map
                     }
                     Err(error) => {
@@ -602,6 +512,10 @@ pub fn import_komorebi_appstate_state(
     }
 }
 
+/// Rebuilds the [`RelationRegistry`] and syncs markers, focus and hierarchy components.
+///
+/// Only the components whose values differ from the previous run are written.
+#[allow(clippy::too_many_arguments)]
 pub fn build_relation_registry(
     mut commands: Commands,
     komorebi_state: Res<KomorebiState>,
@@ -611,200 +525,54 @@ pub fn build_relation_registry(
     window_map: Res<WindowToEntityMap>,
     mut registry: ResMut<RelationRegistry>,
     extended_marker_map: Res<ExtendedMarkerMap>,
+    mut previous_layout: Local<HashMap<Entity, EntityRelations>>,
 ) {
-    registry.records.clear();
-
-    let Some(state) = &komorebi_state.komorebi else {
-        return;
+    let layout = match &komorebi_state.komorebi {
+        Some(state) => relation_layout(
+            state,
+            &monitor_map,
+            &workspace_map,
+            &container_map,
+            &window_map,
+        ),
+        None => HashMap::new(),
     };
 
-    for (monitor_idx, komo_mon) in state.monitors.elements().iter().enumerate() {
-        let Some(serial) = komo_mon.serial_number_id() else {
-            continue;
-        };
-        let Some(monitor_entity) = monitor_map.0.get(serial) else {
+    // Entities that left the hierarchy lose everything they got from it
+    let unplaced = EntityRelations::default();
+    for (entity, previous) in previous_layout.iter() {
+        if layout.contains_key(entity) || commands.get_entity(*entity).is_none() {
             continue;
-        };
-        let monitor_marker_idx = monitor_idx + 1; // 1-based index
-
-        // Insert Monitor Markers
-        insert_monitor_marker_component(
-            monitor_marker_idx,
-            *monitor_entity,
+        }
+        apply_relation_changes(
             commands.reborrow(),
+            *entity,
+            previous,
+            &unplaced,
             &extended_marker_map,
         );
+    }
+    for (entity, current) in layout.iter() {
+        let previous = previous_layout.get(entity).unwrap_or(&unplaced);
+        apply_relation_changes(
+            commands.reborrow(),
+            *entity,
+            previous,
+            current,
+            &extended_marker_map,
+        );
+    }
 
-        // Check and insert/remove monitor focus
-        if state.monitors.focused_idx() == monitor_idx {
-            commands.entity(*monitor_entity).insert(Focused);
-        } else {
-            commands.entity(*monitor_entity).remove::<Focused>();
-        }
-
-        registry.insert(*monitor_entity, monitor_marker_idx, 0, 0, 0);
-
-        for (workspace_idx, komo_ws) in komo_mon.workspaces().iter().enumerate() {
-            let Some(name) = komo_ws.name() else {
-                continue;
-            };
-            let Some(workspace_entity) = workspace_map.0.get(name) else {
-                continue;
-            };
-            let workspace_marker_idx = workspace_idx + 1; // 1-based index
-
-            // Insert Workspace Markers
-            insert_monitor_marker_component(
-                monitor_marker_idx,
-                *workspace_entity,
-                commands.reborrow(),
-                &extended_marker_map,
-            );
-            insert_workspace_marker_component(
-                workspace_marker_idx,
-                *workspace_entity,
-                commands.reborrow(),
-                &extended_marker_map,
-            );
-
-            // Check and insert/remove workspace focus
-            if komo_mon.focused_workspace_idx() == workspace_idx {
-                commands.entity(*workspace_entity).insert(Focused);
-            } else {
-                commands.entity(*workspace_entity).remove::<Focused>();
-            }
-
-            registry.insert(
-                *workspace_entity,
-                monitor_marker_idx,
-                workspace_marker_idx,
-                0,
-                0,
-            );
-
-            for (container_idx, komo_cont) in komo_ws.containers().iter().enumerate() {
-                let Some(container_entity) = container_map.0.get(komo_cont.id()) else {
-                    continue;
-                };
-                let container_marker_idx = container_idx + 1; // 1-based index
-
-                // Insert Container Markers
-                insert_monitor_marker_component(
-                    monitor_marker_idx,
-                    *container_entity,
-                    commands.reborrow(),
-                    &extended_marker_map,
-                );
-                insert_workspace_marker_component(
-                    workspace_marker_idx,
-                    *container_entity,
-                    commands.reborrow(),
-                    &extended_marker_map,
-                );
-                insert_container_marker_component(
-                    container_marker_idx,
-                    *container_entity,
-                    commands.reborrow(),
-                    &extended_marker_map,
-                );
-
-                // Check and insert/remove container focus
-                if komo_ws.focused_container_idx() == container_idx {
-                    commands.entity(*container_entity).insert(Focused);
-                } else {
-                    commands.entity(*container_entity).remove::<Focused>();
-                }
-
-                registry.insert(
-                    *container_entity,
-                    monitor_marker_idx,
-                    workspace_marker_idx,
-                    container_marker_idx,
-                    0,
-                );
-
-                for (window_idx, komo_win) in komo_cont.windows().iter().enumerate() {
-                    let hwnd_str = komo_win.hwnd.to_string();
-                    let Some(window_entity) = window_map.0.get(&hwnd_str) else {
-                        continue;
-                    };
-                    let window_marker_idx = window_idx + 1; // 1-based index
-
-                    // Insert Window Markers
-                    insert_monitor_marker_component(
-                        monitor_marker_idx,
-                        *window_entity,
-                        commands.reborrow(),
-                        &extended_marker_map,
-                    );
-                    insert_workspace_marker_component(
-                        workspace_marker_idx,
-                        *window_entity,
-                        commands.reborrow(),
-                        &extended_marker_map,
-                    );
-                    insert_container_marker_component(
-                        container_marker_idx,
-                        *window_entity,
-                        commands.reborrow(),
-                        &extended_marker_map,
-                    );
-                    insert_window_marker_component(
-                        window_marker_idx,
-                        *window_entity,
-                        commands.reborrow(),
-                        &extended_marker_map,
-                    );
-
-                    // Check and insert/remove window focus
-                    if komo_cont.focused_window_idx() == window_idx {
-                        commands.entity(*window_entity).insert(Focused);
-                    } else {
-                        commands.entity(*window_entity).remove::<Focused>();
-                    }
-
-                    registry.insert(
-                        *window_entity,
-                        monitor_marker_idx,
-                        workspace_marker_idx,
-                        container_marker_idx,
-                        window_marker_idx,
-                    );
-                }
-            }
-            if let Some(monocle) = komo_ws.monocle_container() {
-                if let Some(monocle_entity) = container_map.0.get(monocle.id()) {
-                    insert_monitor_marker_component(
-                        monitor_marker_idx,
-                        *monocle_entity,
-                        commands.reborrow(),
-                        &extended_marker_map,
-                    );
-                    insert_workspace_marker_component(
-                        workspace_marker_idx,
-                        *monocle_entity,
-                        commands.reborrow(),
-                        &extended_marker_map,
-                    );
-                };
-            }
-
-            if let Some(maximized) = komo_ws.maximized_window() {
-                if let Some(maximized_entity) = window_map.0.get(&maximized.hwnd.to_string()) {
-                    insert_monitor_marker_component(
-                        monitor_marker_idx,
-                        *maximized_entity,
-                        commands.reborrow(),
-                        &extended_marker_map,
-                    );
-                    insert_workspace_marker_component(
-                        workspace_marker_idx,
-                        *maximized_entity,
-                        commands.reborrow(),
-                        &extended_marker_map,
-                    );
-                }
-            }
-        }
+    // rebuild sorts the records, so compare them by entity rather than in order
+    let records = layout_records(&layout);
+    let unchanged = registry.records.len() == records.len()
+        && records.iter().all(|new| {
+            registry
+                .record(new.entity)
+                .is_some_and(|old| old.key() == new.key())
+        });
+    if !unchanged {
+        registry.rebuild(records);
     }
+    *previous_layout = layout;
 }
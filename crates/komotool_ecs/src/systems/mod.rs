@@ -1,7 +1,19 @@
+pub mod diff_export;
 pub mod export_state;
 pub mod fetch_state;
+pub mod global_focus;
 pub mod import_state;
+pub mod relation_layout;
+pub mod static_config;
+pub mod workspace_identity;
+pub mod write_back;
 
+pub use diff_export::*;
 pub use export_state::*;
 pub use fetch_state::update_komorebi_state_from_notifications;
+pub use global_focus::*;
 pub use import_state::*;
+pub use relation_layout::*;
+pub use static_config::*;
+pub use workspace_identity::*;
+pub use write_back::*;
@@ -0,0 +1,358 @@
+use crate::components::{
+    Focused, despawn_container_marker_component, despawn_monitor_marker_component,
+    despawn_window_marker_component, despawn_workspace_marker_component,
+    insert_container_marker_component, insert_monitor_marker_component,
+    insert_window_marker_component, insert_workspace_marker_component,
+};
+use crate::relations::{
+    ChildOfContainer, ChildOfMonitor, ChildOfWorkspace, ContainerWindows, EntityRecord,
+    MonitorWorkspaces, RecordPath, RelationIndex, WorkspaceContainers, WorkspaceFloatingWindows,
+};
+use crate::resources::{
+    ContainerToEntityMap, ExtendedMarkerMap, MonitorToEntityMap, WindowToEntityMap,
+    WorkspaceToEntityMap,
+};
+use crate::systems::workspace_key;
+use bevy_ecs::component::Component;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::system::Commands;
+use komorebi_client::State;
+use std::collections::HashMap;
+
+/// Everything [`build_relation_registry`](super::build_relation_registry) puts on one entity.
+///
+/// `markers` holds the 1-based marker index per level, 0 meaning no marker. It differs from
+/// `record` for monocle containers and maximized windows, which carry monitor and workspace
+/// markers but have no registry record.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntityRelations {
+    pub record: Option<RecordPath>,
+    pub markers: RecordPath,
+    pub focused: bool,
+    pub child_of_monitor: Option<Entity>,
+    pub child_of_workspace: Option<Entity>,
+    pub child_of_container: Option<Entity>,
+    pub monitor_workspaces: Option<Vec<Entity>>,
+    pub workspace_containers: Option<Vec<Entity>>,
+    pub workspace_floating_windows: Option<Vec<Entity>>,
+    pub container_windows: Option<Vec<Entity>>,
+}
+
+/// Works out the relations of every entity komorebi currently manages.
+pub fn relation_layout(
+    state: &State,
+    monitor_map: &MonitorToEntityMap,
+    workspace_map: &WorkspaceToEntityMap,
+    container_map: &ContainerToEntityMap,
+    window_map: &WindowToEntityMap,
+) -> HashMap<Entity, EntityRelations> {
+    let mut layout = HashMap::new();
+
+    for (monitor_idx, komo_mon) in state.monitors.elements().iter().enumerate() {
+        let Some(serial) = komo_mon.serial_number_id() else {
+            continue;
+        };
+        let Some(monitor_entity) = monitor_map.0.get(serial) else {
+            continue;
+        };
+        let m = monitor_idx + 1; // 1-based index
+        let mut monitor_workspaces = Vec::new();
+
+        for (workspace_idx, komo_ws) in komo_mon.workspaces().iter().enumerate() {
+            let key = workspace_key(komo_mon, monitor_idx, workspace_idx, komo_ws);
+            let Some(workspace_entity) = workspace_map.0.get(&key) else {
+                continue;
+            };
+            let w = workspace_idx + 1; // 1-based index
+            monitor_workspaces.push(*workspace_entity);
+            let mut workspace_containers = Vec::new();
+            let mut workspace_floating_windows = Vec::new();
+
+            for (container_idx, komo_cont) in komo_ws.containers().iter().enumerate() {
+                let Some(container_entity) = container_map.0.get(komo_cont.id()) else {
+                    continue;
+                };
+                let c = container_idx + 1; // 1-based index
+                workspace_containers.push(*container_entity);
+                let mut container_windows = Vec::new();
+
+                for (window_idx, komo_win) in komo_cont.windows().iter().enumerate() {
+                    let Some(window_entity) = window_map.0.get(&komo_win.hwnd.to_string()) else {
+                        continue;
+                    };
+                    let path = (m, w, c, window_idx + 1);
+                    container_windows.push(*window_entity);
+                    layout.insert(
+                        *window_entity,
+                        EntityRelations {
+                            record: Some(path),
+                            markers: path,
+                            focused: komo_cont.focused_window_idx() == window_idx,
+                            child_of_monitor: Some(*monitor_entity),
+                            child_of_workspace: Some(*workspace_entity),
+                            child_of_container: Some(*container_entity),
+                            ..Default::default()
+                        },
+                    );
+                }
+
+                layout.insert(
+                    *container_entity,
+                    EntityRelations {
+                        record: Some((m, w, c, 0)),
+                        markers: (m, w, c, 0),
+                        focused: komo_ws.focused_container_idx() == container_idx,
+                        child_of_monitor: Some(*monitor_entity),
+                        child_of_workspace: Some(*workspace_entity),
+                        container_windows: Some(container_windows),
+                        ..Default::default()
+                    },
+                );
+            }
+
+            if let Some(monocle) = komo_ws.monocle_container() {
+                if let Some(monocle_entity) = container_map.0.get(monocle.id()) {
+                    layout.insert(
+                        *monocle_entity,
+                        EntityRelations {
+                            markers: (m, w, 0, 0),
+                            child_of_monitor: Some(*monitor_entity),
+                            child_of_workspace: Some(*workspace_entity),
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+
+            // Floating windows sit directly below their workspace, with container index 0
+            for (floating_idx, komo_win) in komo_ws.floating_windows.iter().enumerate() {
+                let Some(window_entity) = window_map.0.get(&komo_win.hwnd.to_string()) else {
+                    continue;
+                };
+                let path = (m, w, 0, floating_idx + 1);
+                workspace_floating_windows.push(*window_entity);
+                layout.insert(
+                    *window_entity,
+                    EntityRelations {
+                        record: Some(path),
+                        markers: path,
+                        child_of_monitor: Some(*monitor_entity),
+                        child_of_workspace: Some(*workspace_entity),
+                        ..Default::default()
+                    },
+                );
+            }
+
+            if let Some(maximized) = komo_ws.maximized_window() {
+                if let Some(maximized_entity) = window_map.0.get(&maximized.hwnd.to_string()) {
+                    layout.insert(
+                        *maximized_entity,
+                        EntityRelations {
+                            markers: (m, w, 0, 0),
+                            child_of_monitor: Some(*monitor_entity),
+                            child_of_workspace: Some(*workspace_entity),
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+
+            layout.insert(
+                *workspace_entity,
+                EntityRelations {
+                    record: Some((m, w, 0, 0)),
+                    markers: (m, w, 0, 0),
+                    focused: komo_mon.focused_workspace_idx() == workspace_idx,
+                    child_of_monitor: Some(*monitor_entity),
+                    workspace_containers: Some(workspace_containers),
+                    workspace_floating_windows: Some(workspace_floating_windows),
+                    ..Default::default()
+                },
+            );
+        }
+
+        layout.insert(
+            *monitor_entity,
+            EntityRelations {
+                record: Some((m, 0, 0, 0)),
+                markers: (m, 0, 0, 0),
+                focused: state.monitors.focused_idx() == monitor_idx,
+                monitor_workspaces: Some(monitor_workspaces),
+                ..Default::default()
+            },
+        );
+    }
+
+    layout
+}
+
+/// The registry records of a layout, unsorted.
+pub fn layout_records(layout: &HashMap<Entity, EntityRelations>) -> Vec<EntityRecord> {
+    layout
+        .iter()
+        .filter_map(|(entity, relations)| {
+            let (m, w, c, win) = relations.record?;
+            Some(EntityRecord::new(*entity, m, w, c, win))
+        })
+        .collect()
+}
+
+/// Queues only the component inserts and removals that turn `previous` into `current`.
+///
+/// Untouched components keep their change ticks, so `Changed<T>` stays meaningful to scripts.
+pub fn apply_relation_changes(
+    mut commands: Commands,
+    entity: Entity,
+    previous: &EntityRelations,
+    current: &EntityRelations,
+    extended_marker_map: &ExtendedMarkerMap,
+) {
+    if previous == current {
+        return;
+    }
+
+    let (prev_m, prev_w, prev_c, prev_win) = previous.markers;
+    let (m, w, c, win) = current.markers;
+    if prev_m != m {
+        if prev_m > 0 {
+            despawn_monitor_marker_component(
+                prev_m,
+                entity,
+                commands.reborrow(),
+                extended_marker_map,
+            );
+        }
+        if m > 0 {
+            insert_monitor_marker_component(m, entity, commands.reborrow(), extended_marker_map);
+        }
+    }
+    if prev_w != w {
+        if prev_w > 0 {
+            despawn_workspace_marker_component(
+                prev_w,
+                entity,
+                commands.reborrow(),
+                extended_marker_map,
+            );
+        }
+        if w > 0 {
+            insert_workspace_marker_component(w, entity, commands.reborrow(), extended_marker_map);
+        }
+    }
+    if prev_c != c {
+        if prev_c > 0 {
+            despawn_container_marker_component(
+                prev_c,
+                entity,
+                commands.reborrow(),
+                extended_marker_map,
+            );
+        }
+        if c > 0 {
+            insert_container_marker_component(c, entity, commands.reborrow(), extended_marker_map);
+        }
+    }
+    if prev_win != win {
+        if prev_win > 0 {
+            despawn_window_marker_component(
+                prev_win,
+                entity,
+                commands.reborrow(),
+                extended_marker_map,
+            );
+        }
+        if win > 0 {
+            insert_window_marker_component(win, entity, commands.reborrow(), extended_marker_map);
+        }
+    }
+
+    if previous.focused != current.focused {
+        if current.focused {
+            commands.entity(entity).insert(Focused);
+        } else {
+            commands.entity(entity).remove::<Focused>();
+        }
+    }
+
+    let index =
+        |record: Option<RecordPath>| record.map(|(m, w, c, win)| RelationIndex::new(m, w, c, win));
+    sync_component(
+        &mut commands,
+        entity,
+        index(previous.record),
+        index(current.record),
+    );
+    sync_component(
+        &mut commands,
+        entity,
+        previous.child_of_monitor.map(ChildOfMonitor),
+        current.child_of_monitor.map(ChildOfMonitor),
+    );
+    sync_component(
+        &mut commands,
+        entity,
+        previous.child_of_workspace.map(ChildOfWorkspace),
+        current.child_of_workspace.map(ChildOfWorkspace),
+    );
+    sync_component(
+        &mut commands,
+        entity,
+        previous.child_of_container.map(ChildOfContainer),
+        current.child_of_container.map(ChildOfContainer),
+    );
+    sync_component(
+        &mut commands,
+        entity,
+        previous.monitor_workspaces.clone().map(MonitorWorkspaces),
+        current.monitor_workspaces.clone().map(MonitorWorkspaces),
+    );
+    sync_component(
+        &mut commands,
+        entity,
+        previous
+            .workspace_containers
+            .clone()
+            .map(WorkspaceContainers),
+        current
+            .workspace_containers
+            .clone()
+            .map(WorkspaceContainers),
+    );
+    sync_component(
+        &mut commands,
+        entity,
+        previous
+            .workspace_floating_windows
+            .clone()
+            .map(WorkspaceFloatingWindows),
+        current
+            .workspace_floating_windows
+            .clone()
+            .map(WorkspaceFloatingWindows),
+    );
+    sync_component(
+        &mut commands,
+        entity,
+        previous.container_windows.clone().map(ContainerWindows),
+        current.container_windows.clone().map(ContainerWindows),
+    );
+}
+
+fn sync_component<T: Component + PartialEq>(
+    commands: &mut Commands,
+    entity: Entity,
+    previous: Option<T>,
+    current: Option<T>,
+) {
+    if previous == current {
+        return;
+    }
+    match current {
+        Some(component) => {
+            commands.entity(entity).insert(component);
+        }
+        None => {
+            commands.entity(entity).remove::<T>();
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use crate::resources::{KomorebiStaticConfig, KomotoolStaticConfig, StaticConfigOutput};
+use crate::transport::KomorebiTransport;
+use bevy_ecs::event::EventReader;
+use bevy_ecs::system::{Local, Res, ResMut};
+use komorebi_client::{SocketMessage, StaticConfig};
+use komotoolc_pipe::{KomotoolcCommandEvent, KomotoolcMessage, KomotoolcResponse};
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How a generated config is handed to komorebi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StaticConfigApplyMode {
+    /// `ReloadStaticConfiguration`: re-reads the static config without reinitialising.
+    #[default]
+    Reload,
+    /// `ReplaceConfiguration`: restarts komorebi's window manager with the new config.
+    Replace,
+}
+
+impl StaticConfigApplyMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.to_lowercase().as_str() {
+            "reload" => Some(Self::Reload),
+            "replace" => Some(Self::Replace),
+            _ => None,
+        }
+    }
+
+    fn message(self, path: PathBuf) -> SocketMessage {
+        match self {
+            Self::Reload => SocketMessage::ReloadStaticConfiguration(path),
+            Self::Replace => SocketMessage::ReplaceConfiguration(path),
+        }
+    }
+}
+
+/// One setting that differs between komorebi's config and the working copy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigChange {
+    /// Dotted path into the config, with list indices in brackets, e.g. `monitors[0].workspaces[1].name`
+    pub path: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.old, &self.new) {
+            (None, Some(new)) => write!(f, "+ {}: {}", self.path, new),
+            (Some(old), None) => write!(f, "- {}: {}", self.path, old),
+            (Some(old), Some(new)) => write!(f, "~ {}: {} -> {}", self.path, old, new),
+            (None, None) => write!(f, "  {}", self.path),
+        }
+    }
+}
+
+/// Lists every leaf setting that differs between two static configs.
+///
+/// A missing config compares as an empty one, so every setting of the other shows up.
+pub fn diff_static_configs(
+    current: Option<&StaticConfig>,
+    working: Option<&StaticConfig>,
+) -> Vec<ConfigChange> {
+    let to_value = |config: Option<&StaticConfig>| {
+        config
+            .and_then(|config| serde_json::to_value(config).ok())
+            .unwrap_or(Value::Object(Default::default()))
+    };
+    let mut changes = Vec::new();
+    diff_values(
+        String::new(),
+        Some(&to_value(current)),
+        Some(&to_value(working)),
+        &mut changes,
+    );
+    changes
+}
+
+fn diff_values(
+    path: String,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<ConfigChange>,
+) {
+    // Settings serialised as null are the same as absent ones
+    let old = old.filter(|value| !value.is_null());
+    let new = new.filter(|value| !value.is_null());
+    match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff_values(child, old.get(key), new.get(key), changes);
+            }
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            for idx in 0..old.len().max(new.len()) {
+                diff_values(
+                    format!("{}[{}]", path, idx),
+                    old.get(idx),
+                    new.get(idx),
+                    changes,
+                );
+            }
+        }
+        (old, new) if old != new => changes.push(ConfigChange {
+            path,
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+/// Writes the working copy to `output` and tells komorebi to load it.
+pub fn apply_static_config(
+    working: &StaticConfig,
+    output: &Path,
+    mode: StaticConfigApplyMode,
+    transport: &KomorebiTransport,
+) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(working)
+        .map_err(|e| format!("Failed to serialise static config: {}", e))?;
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(output, json).map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+    transport
+        .send_all(&[mode.message(output.to_path_buf())])
+        .map_err(|e| format!("Failed to send static config to komorebi: {}", e))
+}
+
+/// Applies the working copy, returning the changes it makes against komorebi's config.
+pub fn apply_working_static_config(
+    working: &KomotoolStaticConfig,
+    current: &KomorebiStaticConfig,
+    output: &StaticConfigOutput,
+    mode: StaticConfigApplyMode,
+    transport: &KomorebiTransport,
+) -> Result<Vec<ConfigChange>, String> {
+    let Some(config) = working.config.as_ref() else {
+        return Err("There is no working static config to apply".to_string());
+    };
+    let changes = diff_static_configs(current.config.as_ref(), Some(config));
+    apply_static_config(config, &output.path(), mode, transport)?;
+    Ok(changes)
+}
+
+/// Seeds the working copy from `komorebi.json`, and refreshes it on reloads until a script edits it.
+pub fn seed_komotool_static_config(
+    komorebi_config: Res<KomorebiStaticConfig>,
+    mut working: ResMut<KomotoolStaticConfig>,
+    mut seeded: Local<Option<Value>>,
+) {
+    let working_value = working
+        .config
+        .as_ref()
+        .and_then(|config| serde_json::to_value(config).ok());
+    let unedited = working.config.is_none() || working_value == *seeded;
+    if !unedited {
+        return;
+    }
+    working.config = komorebi_config.config.clone();
+    *seeded = working
+        .config
+        .as_ref()
+        .and_then(|config| serde_json::to_value(config).ok());
+}
+
+pub fn handle_static_config_commands(
+    mut events: EventReader<KomotoolcCommandEvent>,
+    mut working: ResMut<KomotoolStaticConfig>,
+    current: Res<KomorebiStaticConfig>,
+    output: Res<StaticConfigOutput>,
+    transport: Res<KomorebiTransport>,
+) {
+    for event in events.read() {
+        let response = match &event.message {
+            KomotoolcMessage::StaticConfigDiff => {
+                let changes = diff_static_configs(current.config.as_ref(), working.config.as_ref());
+                KomotoolcResponse::Ok(format_changes(&changes))
+            }
+            KomotoolcMessage::ApplyStaticConfig { replace } => {
+                let mode = if *replace {
+                    StaticConfigApplyMode::Replace
+                } else {
+                    StaticConfigApplyMode::Reload
+                };
+                match apply_working_static_config(&working, &current, &output, mode, &transport) {
+                    Ok(changes) => KomotoolcResponse::Ok(format!(
+                        "Applied static config from {}\n{}",
+                        output.path().display(),
+                        format_changes(&changes)
+                    )),
+                    Err(e) => KomotoolcResponse::Error(e),
+                }
+            }
+            KomotoolcMessage::ResetStaticConfig => {
+                working.config = current.config.clone();
+                KomotoolcResponse::Ok("Reset the working static config".to_string())
+            }
+            _ => continue,
+        };
+        event.reply(response);
+    }
+}
+
+pub fn format_changes(changes: &[ConfigChange]) -> String {
+    if changes.is_empty() {
+        return "No changes".to_string();
+    }
+    changes
+        .iter()
+        .map(|change| change.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
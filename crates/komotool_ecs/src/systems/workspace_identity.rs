@@ -0,0 +1,114 @@
+use bevy_ecs::entity::Entity;
+use komorebi_client::{Monitor, Workspace};
+use std::collections::{HashMap, HashSet};
+
+/// Prefix of the keys given to workspaces without a name.
+pub const UNNAMED_WORKSPACE_PREFIX: &str = "#unnamed:";
+
+/// Key of the workspace at this position: `#unnamed:<monitor serial>:<workspace index>`.
+///
+/// Monitors without a serial number fall back to their index.
+pub fn workspace_position_key(
+    komo_mon: &Monitor,
+    monitor_idx: usize,
+    workspace_idx: usize,
+) -> String {
+    match komo_mon.serial_number_id() {
+        Some(serial) => format!("{}{}:{}", UNNAMED_WORKSPACE_PREFIX, serial, workspace_idx),
+        None => format!(
+            "{}monitor{}:{}",
+            UNNAMED_WORKSPACE_PREFIX, monitor_idx, workspace_idx
+        ),
+    }
+}
+
+/// The [`WorkspaceToEntityMap`](crate::resources::WorkspaceToEntityMap) key of a workspace:
+/// its name, or its position key when it has none.
+pub fn workspace_key(
+    komo_mon: &Monitor,
+    monitor_idx: usize,
+    workspace_idx: usize,
+    komo_ws: &Workspace,
+) -> String {
+    match komo_ws.name() {
+        Some(name) => name.clone(),
+        None => workspace_position_key(komo_mon, monitor_idx, workspace_idx),
+    }
+}
+
+pub fn is_unnamed_workspace_key(key: &str) -> bool {
+    key.starts_with(UNNAMED_WORKSPACE_PREFIX)
+}
+
+/// A komorebi workspace as seen by the identity matching.
+#[derive(Debug, Clone)]
+pub struct WorkspaceSlot {
+    pub key: String,
+    pub position_key: String,
+    pub named: bool,
+    pub containers: HashSet<String>,
+}
+
+/// Works out which unnamed workspace entities moved to a new key, as `(old key, new key)`.
+///
+/// An entity keyed by position follows the workspace that holds most of its previous
+/// containers, so it survives reordering. Otherwise it stays with the workspace at its
+/// position, which also migrates it to the name key once that workspace is named.
+pub fn plan_workspace_rekeys(
+    map: &HashMap<String, Entity>,
+    previous_containers: &HashMap<Entity, HashSet<String>>,
+    slots: &[WorkspaceSlot],
+) -> Vec<(String, String)> {
+    let mut unclaimed: Vec<(&String, Entity)> = map
+        .iter()
+        .filter(|(key, _)| is_unnamed_workspace_key(key))
+        .map(|(key, entity)| (key, *entity))
+        .collect();
+    unclaimed.sort_by(|a, b| a.0.cmp(b.0));
+
+    let needs_entity = |slot: &WorkspaceSlot| !slot.named || !map.contains_key(&slot.key);
+    let mut assigned: Vec<Option<&String>> = vec![None; slots.len()];
+
+    // Follow the containers first
+    for (slot_idx, slot) in slots.iter().enumerate() {
+        if !needs_entity(slot) || slot.containers.is_empty() {
+            continue;
+        }
+        let best = unclaimed
+            .iter()
+            .enumerate()
+            .map(|(idx, (_, entity))| {
+                let overlap = previous_containers.get(entity).map_or(0, |previous| {
+                    previous.intersection(&slot.containers).count()
+                });
+                (idx, overlap)
+            })
+            .filter(|(_, overlap)| *overlap > 0)
+            .max_by_key(|(_, overlap)| *overlap);
+        if let Some((idx, _)) = best {
+            assigned[slot_idx] = Some(unclaimed.remove(idx).0);
+        }
+    }
+
+    // Then fall back to the position
+    for (slot_idx, slot) in slots.iter().enumerate() {
+        if !needs_entity(slot) || assigned[slot_idx].is_some() {
+            continue;
+        }
+        if let Some(idx) = unclaimed
+            .iter()
+            .position(|(key, _)| **key == slot.position_key)
+        {
+            assigned[slot_idx] = Some(unclaimed.remove(idx).0);
+        }
+    }
+
+    slots
+        .iter()
+        .zip(assigned)
+        .filter_map(|(slot, old_key)| {
+            let old_key = old_key?;
+            (*old_key != slot.key).then(|| (old_key.clone(), slot.key.clone()))
+        })
+        .collect()
+}
@@ -0,0 +1,272 @@
+use crate::components::{FloatingWindow, Focused};
+use crate::relations::RelationRegistry;
+use crate::resources::{AppState, KomorebiState, WriteBackState};
+use crate::systems::{build_state_from_registry, diff_states};
+use crate::transport::KomorebiTransport;
+use crate::validation::{StateValidation, checked_export_state, format_violations, validate_state};
+use bevy_ecs::query::{Changed, Or, With};
+use bevy_ecs::system::{Query, Res, ResMut};
+use komorebi_client::{Container, Monitor, SocketMessage, State, Window, Workspace};
+
+/// Checks that a state rebuilt from the ECS mirror is something komorebi can apply: the same
+/// monitors as komorebi has, and no [`validate_state`] violations.
+pub fn validate_write_back_state(current: &State, new: &State) -> Result<(), String> {
+    let current_monitors = current.monitors.elements().len();
+    let new_monitors = new.monitors.elements().len();
+    if current_monitors != new_monitors {
+        return Err(format!(
+            "Monitor count changed from {} to {}, monitors can't be added or removed",
+            current_monitors, new_monitors
+        ));
+    }
+
+    let violations = validate_state(new);
+    if !violations.is_empty() {
+        return Err(format!("Invalid state: {}", format_violations(&violations)));
+    }
+
+    Ok(())
+}
+
+/// Sends ECS edits made during the frame to komorebi, once per frame.
+///
+/// Edits are translated into granular commands by [`diff_states`], falling back to a single
+/// `ApplyState` when no command sequence can express them.
+///
+/// Imports overwrite the mirror with komorebi's own state, so a rebuilt state that matches
+/// [`KomorebiState`] means nothing was edited. After a send, the same state isn't sent again
+/// until komorebi's notification echoes it back and [`KomorebiState`] changes.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn write_back_state(
+    mut write_back: ResMut<WriteBackState>,
+    transport: Res<KomorebiTransport>,
+    validation: Res<StateValidation>,
+    komorebi_state: Res<KomorebiState>,
+    registry: Res<RelationRegistry>,
+    app_state: Res<AppState>,
+    changed: Query<
+        (),
+        Or<(
+            Changed<Monitor>,
+            Changed<Workspace>,
+            Changed<Container>,
+            Changed<Window>,
+            Changed<Focused>,
+        )>,
+    >,
+    monitor_query: Query<&Monitor>,
+    workspace_query: Query<&Workspace>,
+    container_query: Query<&Container>,
+    window_query: Query<(&Window, Option<&FloatingWindow>)>,
+    focused_query: Query<(), With<Focused>>,
+) {
+    if !write_back.enabled {
+        return;
+    }
+
+    if komorebi_state.is_changed() {
+        // komorebi has answered, the next edit may be sent again
+        write_back.last_sent = None;
+    }
+
+    if changed.is_empty() && !app_state.is_changed() && !registry.is_changed() {
+        return;
+    }
+
+    let Some(current) = komorebi_state.komorebi.as_ref() else {
+        return;
+    };
+
+    let state = build_state_from_registry(
+        &registry,
+        &app_state,
+        &monitor_query,
+        &workspace_query,
+        &container_query,
+        &window_query,
+        &focused_query,
+    );
+
+    if !current.has_been_modified(&state) {
+        return;
+    }
+    if write_back
+        .last_sent
+        .as_ref()
+        .is_some_and(|sent| !sent.has_been_modified(&state))
+    {
+        // Still waiting for komorebi to echo the last write back
+        return;
+    }
+
+    // The rebuilt state is what's remembered as sent, so a repaired one isn't sent twice
+    let checked = match checked_export_state(state.clone(), validation.policy) {
+        Ok(checked) => checked,
+        Err(e) => {
+            eprintln!("Not writing ECS changes back to komorebi: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = validate_write_back_state(current, &checked) {
+        eprintln!("Not writing ECS changes back to komorebi: {}", e);
+        return;
+    }
+
+    let messages = diff_states(current, &checked)
+        .unwrap_or_else(|| vec![SocketMessage::ApplyState(checked.clone())]);
+    match transport.send_all(&messages) {
+        Ok(_) => {
+            println!(
+                "Wrote ECS changes back to komorebi with {} message(s)",
+                messages.len()
+            );
+            write_back.last_sent = Some(state);
+        }
+        Err(e) => eprintln!("Failed to write ECS changes back to komorebi: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_states::*;
+    use crate::transport::MockTransport;
+    use bevy_ecs::change_detection::DetectChangesMut;
+    use bevy_ecs::entity::Entity;
+    use bevy_ecs::system::RunSystemOnce;
+    use bevy_ecs::world::World;
+    use komorebi_client::CycleDirection;
+
+    /// Mirrors `state` into `world` the way the importers lay it out, returning the container
+    /// entities by workspace.
+    fn mirror(world: &mut World, state: &State) -> Vec<Vec<Entity>> {
+        let mut registry = RelationRegistry::default();
+        let mut containers = Vec::new();
+        for (m, monitor) in state.monitors.elements().iter().enumerate() {
+            let focused = m == state.monitors.focused_idx();
+            let entity = spawn(world, monitor.clone(), focused);
+            registry.insert(entity, m + 1, 0, 0, 0);
+            for (w, workspace) in monitor.workspaces().iter().enumerate() {
+                let focused = w == monitor.focused_workspace_idx();
+                let entity = spawn(world, workspace.clone(), focused);
+                registry.insert(entity, m + 1, w + 1, 0, 0);
+                let mut workspace_containers = Vec::new();
+                for (c, container) in workspace.containers().iter().enumerate() {
+                    let focused = c == workspace.focused_container_idx();
+                    let entity = spawn(world, container.clone(), focused);
+                    registry.insert(entity, m + 1, w + 1, c + 1, 0);
+                    workspace_containers.push(entity);
+                    for (n, window) in container.windows().iter().enumerate() {
+                        let focused = n == container.focused_window_idx();
+                        let entity = spawn(world, *window, focused);
+                        registry.insert(entity, m + 1, w + 1, c + 1, n + 1);
+                    }
+                }
+                containers.push(workspace_containers);
+            }
+        }
+        world.insert_resource(registry);
+        containers
+    }
+
+    fn spawn(
+        world: &mut World,
+        component: impl bevy_ecs::component::Component,
+        focused: bool,
+    ) -> Entity {
+        let mut entity = world.spawn(component);
+        if focused {
+            entity.insert(Focused);
+        }
+        entity.id()
+    }
+
+    /// A world mirroring `current` with write back switched on, and the transport's recording.
+    fn world(current: &State) -> (World, MockTransport, Vec<Vec<Entity>>) {
+        let mut world = World::new();
+        let mock = MockTransport::default();
+        world.insert_resource(KomorebiTransport(Box::new(mock.clone())));
+        world.insert_resource(WriteBackState {
+            enabled: true,
+            ..Default::default()
+        });
+        world.insert_resource(KomorebiState {
+            komorebi: Some(current.clone()),
+        });
+        world.insert_resource(AppState::default());
+        world.insert_resource(StateValidation::default());
+        let containers = mirror(&mut world, current);
+        (world, mock, containers)
+    }
+
+    /// Moves `container` and its windows to the end of workspace `to` of the first monitor.
+    fn move_container(world: &mut World, container: Entity, to: usize, position: usize) {
+        let mut registry = world.resource_mut::<RelationRegistry>();
+        let Some(&parent) = registry.record(container) else {
+            return;
+        };
+        let windows: Vec<_> = registry
+            .records
+            .iter()
+            .filter(|record| {
+                record.window > 0
+                    && (record.monitor, record.workspace, record.container)
+                        == (parent.monitor, parent.workspace, parent.container)
+            })
+            .map(|record| (record.entity, record.window))
+            .collect();
+        registry.insert(container, 1, to, position, 0);
+        for (window, idx) in windows {
+            registry.insert(window, 1, to, position, idx);
+        }
+    }
+
+    #[test]
+    fn unchanged_mirror_sends_nothing() {
+        let current = two_workspaces(&[&[1], &[2]], 0);
+        let (mut world, mock, _) = world(&current);
+
+        assert!(world.run_system_once(write_back_state).is_ok());
+        assert!(mock.sent().is_empty());
+    }
+
+    #[test]
+    fn moved_container_is_sent_as_commands() {
+        let current = two_workspaces(&[&[1], &[2]], 0);
+        let (mut world, mock, containers) = world(&current);
+        let Some(&moved) = containers.first().and_then(|a| a.get(1)) else {
+            return;
+        };
+        move_container(&mut world, moved, 2, 1);
+
+        assert!(world.run_system_once(write_back_state).is_ok());
+        assert_eq!(
+            json(&mock.sent()),
+            json(&[
+                SocketMessage::CycleFocusWindow(CycleDirection::Next),
+                SocketMessage::MoveContainerToMonitorWorkspaceNumber(0, 1),
+                SocketMessage::FocusMonitorWorkspaceNumber(0, 0),
+            ])
+        );
+
+        // Nothing is sent again until komorebi echoes the change back
+        mock.clear();
+        world.resource_mut::<RelationRegistry>().set_changed();
+        assert!(world.run_system_once(write_back_state).is_ok());
+        assert!(mock.sent().is_empty());
+    }
+
+    #[test]
+    fn disabled_write_back_sends_nothing() {
+        let current = two_workspaces(&[&[1], &[2]], 0);
+        let (mut world, mock, containers) = world(&current);
+        world.resource_mut::<WriteBackState>().enabled = false;
+        let Some(&moved) = containers.first().and_then(|a| a.get(1)) else {
+            return;
+        };
+        move_container(&mut world, moved, 2, 1);
+
+        assert!(world.run_system_once(write_back_state).is_ok());
+        assert!(mock.sent().is_empty());
+    }
+}
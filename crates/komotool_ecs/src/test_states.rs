@@ -0,0 +1,130 @@
+//! Synthetic komorebi states for tests.
+
+use crate::resources::AppState;
+use komorebi_client::{Container, Monitor, Rect, Ring, State, Window, Workspace};
+use serde::Serialize;
+
+pub fn window(hwnd: isize) -> Window {
+    Window::from(hwnd)
+}
+
+/// A container holding `hwnds`, focused on the first.
+pub fn container(hwnds: &[isize]) -> Container {
+    let mut container = Container::default();
+    container
+        .windows_mut()
+        .extend(hwnds.iter().copied().map(window));
+    container
+}
+
+/// A workspace tiling one container per entry of `containers`, with matching resize dimensions.
+pub fn workspace(name: &str, containers: &[&[isize]]) -> Workspace {
+    let mut workspace = Workspace::default();
+    workspace.set_name(Some(name.to_string()));
+    workspace
+        .containers
+        .elements_mut()
+        .extend(containers.iter().map(|hwnds| container(hwnds)));
+    workspace.resize_dimensions = vec![None; containers.len()];
+    workspace
+}
+
+pub fn monitor(serial: &str, workspaces: Vec<Workspace>) -> Monitor {
+    let mut monitor = komorebi::monitor::new(
+        0,
+        Rect::default(),
+        Rect::default(),
+        serial.to_string(),
+        serial.to_string(),
+        serial.to_string(),
+        Some(serial.to_string()),
+    );
+    let mut ring = Ring::default();
+    ring.elements_mut().extend(workspaces);
+    monitor.workspaces = ring;
+    monitor
+}
+
+/// A state with komotool's default global settings.
+pub fn state(monitors: Vec<Monitor>) -> State {
+    let app_state = AppState::default();
+    let mut ring = Ring::default();
+    ring.elements_mut().extend(monitors);
+    State {
+        monitors: ring,
+        monitor_usr_idx_map: app_state.monitor_usr_idx_map,
+        is_paused: app_state.is_paused,
+        resize_delta: app_state.resize_delta,
+        new_window_behaviour: app_state.new_window_behaviour,
+        float_override: app_state.float_override,
+        cross_monitor_move_behaviour: app_state.cross_monitor_move_behaviour,
+        unmanaged_window_operation_behaviour: app_state.unmanaged_window_operation_behaviour,
+        work_area_offset: app_state.work_area_offset,
+        focus_follows_mouse: app_state.focus_follows_mouse,
+        mouse_follows_focus: app_state.mouse_follows_focus,
+        has_pending_raise_op: app_state.has_pending_raise_op,
+    }
+}
+
+/// One monitor with workspaces "a" (tiling `containers`, focused on `focused_container`) and
+/// "b" (empty).
+pub fn two_workspaces(containers: &[&[isize]], focused_container: usize) -> State {
+    let mut a = workspace("a", containers);
+    a.containers.focus(focused_container);
+    state(vec![monitor("m1", vec![a, workspace("b", &[])])])
+}
+
+/// Serializes states or messages for comparison, since komorebi's types don't implement
+/// `PartialEq`.
+pub fn json<T: Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// The hwnds of every tiled container, by monitor and workspace.
+pub fn tiled_hwnds(state: &State) -> Vec<Vec<Vec<Vec<isize>>>> {
+    state
+        .monitors
+        .elements()
+        .iter()
+        .map(|monitor| {
+            monitor
+                .workspaces()
+                .iter()
+                .map(|workspace| {
+                    workspace
+                        .containers()
+                        .iter()
+                        .map(|container| container.windows().iter().map(|w| w.hwnd).collect())
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The hwnds of every floating window, by monitor and workspace.
+pub fn floating_hwnds(state: &State) -> Vec<Vec<Vec<isize>>> {
+    state
+        .monitors
+        .elements()
+        .iter()
+        .map(|monitor| {
+            monitor
+                .workspaces()
+                .iter()
+                .map(|workspace| workspace.floating_windows.iter().map(|w| w.hwnd).collect())
+                .collect()
+        })
+        .collect()
+}
+
+/// The focused monitor and its focused workspace.
+pub fn focused_workspace(state: &State) -> (usize, usize) {
+    let monitor = state.monitors.focused_idx();
+    let workspace = state
+        .monitors
+        .elements()
+        .get(monitor)
+        .map_or(0, |monitor| monitor.focused_workspace_idx());
+    (monitor, workspace)
+}
@@ -0,0 +1,125 @@
+use bevy_ecs::system::Resource;
+use bevy_ecs::world::{FromWorld, World};
+use komorebi_client::{Rect, SocketMessage, Window};
+use komotool_utils::komorebi_commands::{KomorebiCommandCounter, KomorebiOutgoing, OutgoingMode};
+use std::sync::{Arc, Mutex};
+
+/// Something that can deliver [`SocketMessage`]s to komorebi.
+pub trait SocketTransport: Send + Sync {
+    fn send(&self, message: &SocketMessage) -> std::io::Result<()>;
+
+    /// Moves a window into `rect`, for layouts komotool computes itself.
+    fn position(&self, window: &Window, rect: &Rect) -> std::io::Result<()>;
+}
+
+/// Sends messages over komorebi's socket, or intercepts them in dry-run and shadow mode.
+///
+/// Each message counts as a komotool command for undo history.
+#[derive(Default)]
+pub struct KomorebiSocketTransport {
+    pub counter: KomorebiCommandCounter,
+    pub outgoing: KomorebiOutgoing,
+}
+
+impl SocketTransport for KomorebiSocketTransport {
+    fn send(&self, message: &SocketMessage) -> std::io::Result<()> {
+        self.counter.record();
+        self.outgoing.deliver(message)
+    }
+
+    /// komorebi has no command that places a window it isn't tiling, so in live mode the window
+    /// is positioned directly; otherwise the move is only logged.
+    fn position(&self, window: &Window, rect: &Rect) -> std::io::Result<()> {
+        let mode = self.outgoing.mode();
+        if mode == OutgoingMode::Live {
+            return window
+                .set_position(rect, false)
+                .map_err(|e| std::io::Error::other(e.to_string()));
+        }
+        println!("[{}] position {} at {:?}", mode.as_str(), window.hwnd, rect);
+        Ok(())
+    }
+}
+
+/// Records messages instead of sending them, so the exact command sequence can be asserted.
+///
+/// Clones share the same recording, keep one and insert the other into [`KomorebiTransport`].
+#[derive(Default, Clone)]
+pub struct MockTransport {
+    sent: Arc<Mutex<Vec<SocketMessage>>>,
+    positioned: Arc<Mutex<Vec<(isize, Rect)>>>,
+}
+
+impl MockTransport {
+    /// Every message sent so far, in order.
+    pub fn sent(&self) -> Vec<SocketMessage> {
+        self.sent
+            .lock()
+            .map(|sent| sent.clone())
+            .unwrap_or_default()
+    }
+
+    /// Every window moved so far by hwnd, in order.
+    pub fn positioned(&self) -> Vec<(isize, Rect)> {
+        self.positioned
+            .lock()
+            .map(|positioned| positioned.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut sent) = self.sent.lock() {
+            sent.clear();
+        }
+        if let Ok(mut positioned) = self.positioned.lock() {
+            positioned.clear();
+        }
+    }
+}
+
+impl SocketTransport for MockTransport {
+    fn send(&self, message: &SocketMessage) -> std::io::Result<()> {
+        if let Ok(mut sent) = self.sent.lock() {
+            sent.push(message.clone());
+        }
+        Ok(())
+    }
+
+    fn position(&self, window: &Window, rect: &Rect) -> std::io::Result<()> {
+        if let Ok(mut positioned) = self.positioned.lock() {
+            positioned.push((window.hwnd, *rect));
+        }
+        Ok(())
+    }
+}
+
+/// The transport used by systems that write to komorebi.
+#[derive(Resource)]
+pub struct KomorebiTransport(pub Box<dyn SocketTransport>);
+
+impl FromWorld for KomorebiTransport {
+    fn from_world(world: &mut World) -> Self {
+        let counter = world
+            .get_resource_or_insert_with(KomorebiCommandCounter::default)
+            .clone();
+        let outgoing = world
+            .get_resource_or_insert_with(KomorebiOutgoing::default)
+            .clone();
+        Self(Box::new(KomorebiSocketTransport { counter, outgoing }))
+    }
+}
+
+impl KomorebiTransport {
+    /// Sends the messages in order, stopping at the first failure.
+    pub fn send_all(&self, messages: &[SocketMessage]) -> std::io::Result<()> {
+        for message in messages {
+            self.0.send(message)?;
+        }
+        Ok(())
+    }
+
+    /// Moves a window into `rect`. Not a komorebi command, so it isn't counted.
+    pub fn position_window(&self, window: &Window, rect: &Rect) -> std::io::Result<()> {
+        self.0.position(window, rect)
+    }
+}
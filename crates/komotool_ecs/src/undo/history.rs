@@ -0,0 +1,291 @@
+use crate::resources::KomorebiState;
+use crate::systems::{diff_states, validate_write_back_state};
+use crate::transport::KomorebiTransport;
+use bevy_ecs::event::EventReader;
+use bevy_ecs::system::{Commands, Res, ResMut, Resource};
+use bevy_ecs::world::World;
+use komorebi_client::{SocketMessage, State};
+use komotool_utils::komorebi_commands::KomorebiCommandCounter;
+use komotoolc_pipe::{KomotoolcCommandEvent, KomotoolcMessage, KomotoolcResponse};
+use std::collections::{HashSet, VecDeque};
+
+/// Snapshots kept for undo when no capacity is configured.
+pub const DEFAULT_UNDO_CAPACITY: usize = 50;
+
+/// Which way to move through [`UndoHistory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryDirection {
+    Undo,
+    Redo,
+}
+
+impl HistoryDirection {
+    fn verb(self) -> &'static str {
+        match self {
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+        }
+    }
+}
+
+/// Bounded history of komorebi states from before each komotool command.
+#[derive(Resource)]
+pub struct UndoHistory {
+    pub capacity: usize,
+    undo: VecDeque<State>,
+    redo: Vec<State>,
+    /// [`KomorebiCommandCounter::sent`] when history last looked
+    seen_commands: u64,
+    /// The state an undo or redo was sent against, kept until komorebi reports a different one
+    /// so the next step starts from the arrangement it produced
+    stepped_from: Option<State>,
+}
+
+impl Default for UndoHistory {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_UNDO_CAPACITY,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            seen_commands: 0,
+            stepped_from: None,
+        }
+    }
+}
+
+impl UndoHistory {
+    /// Records the state from before a new command. A new command discards the redo stack.
+    pub fn record(&mut self, state: &State) {
+        self.redo.clear();
+        if self
+            .undo
+            .back()
+            .is_some_and(|last| !last.has_been_modified(state))
+        {
+            return;
+        }
+        self.undo.push_back(state.clone());
+        while self.undo.len() > self.capacity {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn peek(&self, direction: HistoryDirection) -> Option<&State> {
+        match direction {
+            HistoryDirection::Undo => self.undo.back(),
+            HistoryDirection::Redo => self.redo.last(),
+        }
+    }
+
+    /// Takes the next state in `direction` and keeps `current` to go back to.
+    fn step(&mut self, direction: HistoryDirection, current: State) -> Option<State> {
+        match direction {
+            HistoryDirection::Undo => {
+                let state = self.undo.pop_back()?;
+                self.redo.push(current);
+                Some(state)
+            }
+            HistoryDirection::Redo => {
+                let state = self.redo.pop()?;
+                self.undo.push_back(current);
+                Some(state)
+            }
+        }
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+fn managed_hwnds(state: &State) -> HashSet<isize> {
+    let mut hwnds = HashSet::new();
+    for monitor in state.monitors.elements() {
+        for workspace in monitor.workspaces() {
+            for container in workspace.containers() {
+                hwnds.extend(container.windows().iter().map(|window| window.hwnd));
+            }
+            hwnds.extend(workspace.floating_windows.iter().map(|window| window.hwnd));
+            if let Some(window) = workspace.maximized_window() {
+                hwnds.insert(window.hwnd);
+            }
+            if let Some(container) = workspace.monocle_container() {
+                hwnds.extend(container.windows().iter().map(|window| window.hwnd));
+            }
+        }
+    }
+    hwnds
+}
+
+/// Why `current` can no longer be rearranged into `target`, or `None` if it can.
+///
+/// The monitors, their workspace counts and the set of managed windows must be unchanged; only
+/// where windows sit, layouts and focus may differ.
+pub fn history_divergence(current: &State, target: &State) -> Option<String> {
+    let current_monitors = current.monitors.elements();
+    let target_monitors = target.monitors.elements();
+    if current_monitors.len() != target_monitors.len() {
+        return Some(format!(
+            "the monitor count changed from {} to {}",
+            target_monitors.len(),
+            current_monitors.len()
+        ));
+    }
+    for (idx, (current_monitor, target_monitor)) in
+        current_monitors.iter().zip(target_monitors).enumerate()
+    {
+        if current_monitor.serial_number_id() != target_monitor.serial_number_id() {
+            return Some(format!("monitor {} was replaced", idx + 1));
+        }
+        if current_monitor.workspaces().len() != target_monitor.workspaces().len() {
+            return Some(format!("the workspaces on monitor {} changed", idx + 1));
+        }
+    }
+
+    let current_hwnds = managed_hwnds(current);
+    let target_hwnds = managed_hwnds(target);
+    let opened = current_hwnds.difference(&target_hwnds).count();
+    let closed = target_hwnds.difference(&current_hwnds).count();
+    if opened > 0 || closed > 0 {
+        return Some(format!(
+            "{} window(s) opened and {} closed since",
+            opened, closed
+        ));
+    }
+    None
+}
+
+/// The arrangement of `snapshot` with the rest of komorebi's current state, so an undo moves
+/// windows without also reverting unrelated settings.
+pub fn history_target(current: &State, snapshot: &State) -> State {
+    let mut target = current.clone();
+    target.monitors = snapshot.monitors.clone();
+    target
+}
+
+/// Snapshots the state from before any command komotool sent this frame.
+///
+/// Commands are sent before `Last`, and komorebi's reply only reaches [`KomorebiState`] in a
+/// later frame, so the state here is still the one the commands were sent against.
+pub fn record_undo_history(
+    komorebi_state: Res<KomorebiState>,
+    counter: Res<KomorebiCommandCounter>,
+    mut history: ResMut<UndoHistory>,
+) {
+    let applied = match (&history.stepped_from, &komorebi_state.komorebi) {
+        (Some(stepped_from), Some(state)) => stepped_from.has_been_modified(state),
+        _ => false,
+    };
+    if applied {
+        history.stepped_from = None;
+    }
+    let sent = counter.sent();
+    if sent == history.seen_commands {
+        return;
+    }
+    history.seen_commands = sent;
+    if let Some(state) = &komorebi_state.komorebi {
+        history.record(state);
+    }
+}
+
+/// Undoes or redoes one step by sending the inverse arrangement to komorebi.
+///
+/// The arrangement is sent as granular commands where [`diff_states`] can express it, and as
+/// a single `ApplyState` otherwise. Returns how many steps are left in that direction.
+pub fn step_history(world: &mut World, direction: HistoryDirection) -> Result<usize, String> {
+    let verb = direction.verb();
+    let Some(current) = world
+        .get_resource::<KomorebiState>()
+        .and_then(|state| state.komorebi.clone())
+    else {
+        return Err("komorebi's state hasn't been received yet".to_string());
+    };
+    let Some(history) = world.get_resource::<UndoHistory>() else {
+        return Err("There is no undo history".to_string());
+    };
+    if history.stepped_from.is_some() {
+        return Err(format!(
+            "Can't {} yet, komorebi hasn't applied the last step",
+            verb
+        ));
+    }
+    let Some(snapshot) = history.peek(direction) else {
+        return Err(format!("Nothing to {}", verb));
+    };
+    let target = history_target(&current, snapshot);
+    if let Some(reason) = history_divergence(&current, &target) {
+        return Err(format!("Can't {}: {}", verb, reason));
+    }
+    validate_write_back_state(&current, &target)?;
+
+    let sent = current.has_been_modified(&target);
+    if sent {
+        let messages = diff_states(&current, &target)
+            .unwrap_or_else(|| vec![SocketMessage::ApplyState(target.clone())]);
+        let Some(transport) = world.get_resource::<KomorebiTransport>() else {
+            return Err("No komorebi transport".to_string());
+        };
+        transport
+            .send_all(&messages)
+            .map_err(|e| format!("Failed to {}: {}", verb, e))?;
+    }
+
+    let sent_commands = world
+        .get_resource::<KomorebiCommandCounter>()
+        .map_or(0, KomorebiCommandCounter::sent);
+    let Some(mut history) = world.get_resource_mut::<UndoHistory>() else {
+        return Err("There is no undo history".to_string());
+    };
+    history.step(direction, current.clone());
+    // The commands of an undo or redo are not new history
+    history.seen_commands = sent_commands;
+    // Nothing to wait for when the snapshot already matched komorebi's state
+    if sent {
+        history.stepped_from = Some(current);
+    }
+    Ok(match direction {
+        HistoryDirection::Undo => history.undo_len(),
+        HistoryDirection::Redo => history.redo_len(),
+    })
+}
+
+/// Answers komotoolc's undo and redo commands. They need the whole world, so they run as
+/// queued commands.
+pub fn handle_undo_commands(
+    mut events: EventReader<KomotoolcCommandEvent>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let direction = match &event.message {
+            KomotoolcMessage::Undo => HistoryDirection::Undo,
+            KomotoolcMessage::Redo => HistoryDirection::Redo,
+            _ => continue,
+        };
+        event.claim();
+        let event = event.clone();
+        commands.queue(move |world: &mut World| {
+            let response = match step_history(world, direction) {
+                Ok(left) => KomotoolcResponse::Ok(format!(
+                    "{} done, {} step(s) left",
+                    match direction {
+                        HistoryDirection::Undo => "Undo",
+                        HistoryDirection::Redo => "Redo",
+                    },
+                    left
+                )),
+                Err(e) => KomotoolcResponse::Error(e),
+            };
+            event.reply(response);
+        });
+    }
+}
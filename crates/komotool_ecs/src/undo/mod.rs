@@ -0,0 +1,3 @@
+pub mod history;
+
+pub use history::*;
@@ -0,0 +1,103 @@
+use crate::components::{Meta, Tags};
+use bevy_ecs::entity::Entity;
+use bevy_ecs::world::World;
+use std::collections::HashMap;
+
+/// The tags on an entity, empty if it has none.
+pub fn entity_tags(world: &World, entity: Entity) -> Vec<String> {
+    world
+        .get::<Tags>(entity)
+        .map(|tags| tags.0.clone())
+        .unwrap_or_default()
+}
+
+/// Adds a tag to an entity. Returns `false` if the entity doesn't exist.
+pub fn add_entity_tag(world: &mut World, entity: Entity, tag: String) -> bool {
+    let Ok(mut entity) = world.get_entity_mut(entity) else {
+        return false;
+    };
+    match entity.get_mut::<Tags>() {
+        Some(mut tags) => {
+            if !tags.contains(&tag) {
+                tags.insert(tag);
+            }
+        }
+        None => {
+            entity.insert(Tags(vec![tag]));
+        }
+    }
+    true
+}
+
+/// Removes a tag from an entity. Returns whether it had the tag.
+pub fn remove_entity_tag(world: &mut World, entity: Entity, tag: &str) -> bool {
+    let Some(mut tags) = world.get_mut::<Tags>(entity) else {
+        return false;
+    };
+    if !tags.contains(tag) {
+        return false;
+    }
+    tags.remove(tag)
+}
+
+/// The meta on an entity, empty if it has none.
+pub fn entity_meta(world: &World, entity: Entity) -> HashMap<String, String> {
+    world
+        .get::<Meta>(entity)
+        .map(|meta| meta.0.clone())
+        .unwrap_or_default()
+}
+
+/// Sets a meta value on an entity, or removes it with `None`. Returns `false` if the entity
+/// doesn't exist.
+pub fn set_entity_meta(
+    world: &mut World,
+    entity: Entity,
+    key: String,
+    value: Option<String>,
+) -> bool {
+    let Ok(mut entity) = world.get_entity_mut(entity) else {
+        return false;
+    };
+    match (entity.get_mut::<Meta>(), value) {
+        (Some(mut meta), Some(value)) => {
+            if meta.0.get(&key) != Some(&value) {
+                meta.0.insert(key, value);
+            }
+        }
+        (Some(mut meta), None) => {
+            if meta.0.contains_key(&key) {
+                meta.0.remove(&key);
+            }
+        }
+        (None, Some(value)) => {
+            entity.insert(Meta(HashMap::from([(key, value)])));
+        }
+        (None, None) => {}
+    }
+    true
+}
+
+/// Entities carrying a tag.
+pub fn query_tagged(world: &mut World, tag: &str) -> Vec<Entity> {
+    let mut query = world.query::<(Entity, &Tags)>();
+    query
+        .iter(world)
+        .filter(|(_, tags)| tags.contains(tag))
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+/// Entities with a meta key, and with that value when one is given.
+pub fn query_with_meta(world: &mut World, key: &str, value: Option<&str>) -> Vec<Entity> {
+    let mut query = world.query::<(Entity, &Meta)>();
+    query
+        .iter(world)
+        .filter(|(_, meta)| {
+            meta.0
+                .get(key)
+                .is_some_and(|v| value.is_none_or(|value| v == value))
+        })
+        .map(|(entity, _)| entity)
+        .collect()
+}
@@ -0,0 +1,7 @@
+pub mod access;
+pub mod store;
+pub mod sync;
+
+pub use access::*;
+pub use store::*;
+pub use sync::*;
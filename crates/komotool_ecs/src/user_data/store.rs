@@ -0,0 +1,180 @@
+use crate::components::{Meta, Tags};
+use bevy_ecs::system::Resource;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Bumped whenever the user data file format changes incompatibly.
+pub const USER_DATA_VERSION: u32 = 1;
+
+/// The stable identity user data is kept under.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UserDataKey {
+    /// A window, by hwnd as in [`WindowToEntityMap`](crate::resources::WindowToEntityMap)
+    Window(String),
+    /// A workspace, by its [`workspace_key`](crate::systems::workspace_key)
+    Workspace(String),
+}
+
+impl UserDataKey {
+    pub fn id(&self) -> &String {
+        match self {
+            Self::Window(id) | Self::Workspace(id) => id,
+        }
+    }
+}
+
+/// The tags and meta of one window or workspace.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserData {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub meta: BTreeMap<String, String>,
+}
+
+impl UserData {
+    pub fn from_components(tags: Option<&Tags>, meta: Option<&Meta>) -> Self {
+        Self {
+            tags: tags.map(|tags| tags.0.clone()).unwrap_or_default(),
+            meta: meta
+                .map(|meta| meta.0.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.meta.is_empty()
+    }
+
+    pub fn tags(&self) -> Tags {
+        Tags(self.tags.clone())
+    }
+
+    pub fn meta(&self) -> Meta {
+        Meta(
+            self.meta
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        )
+    }
+}
+
+/// The user data file, `{ "version": 1, "windows": { ... }, "workspaces": { ... } }`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserDataFile {
+    pub version: u32,
+    #[serde(default)]
+    pub windows: BTreeMap<String, UserData>,
+    #[serde(default)]
+    pub workspaces: BTreeMap<String, UserData>,
+}
+
+/// [`Tags`] and [`Meta`] by stable identity, so they outlive entity churn and restarts.
+///
+/// Windows are forgotten once komorebi stops managing them, since hwnds are reused. Workspaces
+/// are kept, so data on a named workspace returns with it.
+#[derive(Resource, Default)]
+pub struct UserDataStore {
+    windows: BTreeMap<String, UserData>,
+    workspaces: BTreeMap<String, UserData>,
+    /// Set when the store differs from the file
+    pub dirty: bool,
+}
+
+impl UserDataStore {
+    pub fn from_file(file: UserDataFile) -> Self {
+        Self {
+            windows: file.windows,
+            workspaces: file.workspaces,
+            dirty: false,
+        }
+    }
+
+    pub fn to_file(&self) -> UserDataFile {
+        UserDataFile {
+            version: USER_DATA_VERSION,
+            windows: self.windows.clone(),
+            workspaces: self.workspaces.clone(),
+        }
+    }
+
+    fn map_mut(&mut self, key: &UserDataKey) -> &mut BTreeMap<String, UserData> {
+        match key {
+            UserDataKey::Window(_) => &mut self.windows,
+            UserDataKey::Workspace(_) => &mut self.workspaces,
+        }
+    }
+
+    pub fn get(&self, key: &UserDataKey) -> Option<&UserData> {
+        match key {
+            UserDataKey::Window(id) => self.windows.get(id),
+            UserDataKey::Workspace(id) => self.workspaces.get(id),
+        }
+    }
+
+    /// Stores `data` under `key`; empty data removes the entry.
+    pub fn set(&mut self, key: &UserDataKey, data: UserData) {
+        let id = key.id();
+        let map = self.map_mut(key);
+        let changed = if data.is_empty() {
+            map.remove(id).is_some()
+        } else if map.get(id) == Some(&data) {
+            false
+        } else {
+            map.insert(id.clone(), data);
+            true
+        };
+        self.dirty |= changed;
+    }
+
+    pub fn remove(&mut self, key: &UserDataKey) {
+        let removed = self.map_mut(key).remove(key.id()).is_some();
+        self.dirty |= removed;
+    }
+
+    /// Moves an entry to a new key, e.g. when a workspace is renamed.
+    pub fn rename(&mut self, from: &UserDataKey, to: &UserDataKey) {
+        let Some(data) = self.map_mut(from).remove(from.id()) else {
+            return;
+        };
+        self.dirty = true;
+        self.set(to, data);
+    }
+
+    /// Forgets windows whose hwnd is not in `live`, e.g. after a reboot.
+    pub fn retain_windows(&mut self, live: &HashSet<&String>) {
+        let len = self.windows.len();
+        self.windows.retain(|hwnd, _| live.contains(hwnd));
+        self.dirty |= self.windows.len() != len;
+    }
+}
+
+/// Reads the user data file, refusing files written by a newer format version.
+pub fn read_user_data_file(path: &Path) -> Result<UserDataFile, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let file = serde_json::from_str::<UserDataFile>(&content)
+        .map_err(|e| format!("Invalid user data file {}: {}", path.display(), e))?;
+    if file.version > USER_DATA_VERSION {
+        return Err(format!(
+            "User data file {} has version {}, this komotool reads up to version {}",
+            path.display(),
+            file.version,
+            USER_DATA_VERSION
+        ));
+    }
+    Ok(file)
+}
+
+pub fn write_user_data_file(path: &Path, file: &UserDataFile) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(file)
+        .map_err(|e| format!("Failed to serialise user data: {}", e))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
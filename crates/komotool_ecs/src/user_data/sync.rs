@@ -0,0 +1,113 @@
+use crate::components::{Meta, Tags};
+use crate::resources::{UserDataPath, WindowToEntityMap, WorkspaceToEntityMap};
+use crate::user_data::store::{
+    UserData, UserDataKey, UserDataStore, read_user_data_file, write_user_data_file,
+};
+use bevy_ecs::entity::Entity;
+use bevy_ecs::query::{Changed, Or};
+use bevy_ecs::removal_detection::RemovedComponents;
+use bevy_ecs::system::{Commands, Local, Query, Res, ResMut};
+use std::collections::{HashMap, HashSet};
+
+/// Loads the persisted tags and meta. A missing file is an empty store.
+pub fn load_user_data(path: Res<UserDataPath>, mut store: ResMut<UserDataStore>) {
+    let path = path.path();
+    if !path.exists() {
+        return;
+    }
+    match read_user_data_file(&path) {
+        Ok(file) => *store = UserDataStore::from_file(file),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+/// Keeps [`Tags`] and [`Meta`] on window and workspace entities in step with the store.
+///
+/// Entities new to the entity maps get their stored data, script changes and removals are
+/// written back, a workspace entity that moved to a new key takes its data along, and windows
+/// komorebi no longer manages are forgotten. Window entries left over from before a restart
+/// are dropped once the first windows are imported.
+#[allow(clippy::too_many_arguments)]
+pub fn sync_user_data(
+    mut commands: Commands,
+    window_map: Res<WindowToEntityMap>,
+    workspace_map: Res<WorkspaceToEntityMap>,
+    changed: Query<(Entity, Option<&Tags>, Option<&Meta>), Or<(Changed<Tags>, Changed<Meta>)>>,
+    components: Query<(Option<&Tags>, Option<&Meta>)>,
+    mut removed_tags: RemovedComponents<Tags>,
+    mut removed_meta: RemovedComponents<Meta>,
+    mut store: ResMut<UserDataStore>,
+    mut known: Local<HashMap<Entity, UserDataKey>>,
+    mut pruned: Local<bool>,
+) {
+    if !*pruned && !window_map.0.is_empty() {
+        store.retain_windows(&window_map.0.keys().collect::<HashSet<_>>());
+        *pruned = true;
+    }
+
+    let current: HashMap<Entity, UserDataKey> = window_map
+        .0
+        .iter()
+        .map(|(hwnd, entity)| (*entity, UserDataKey::Window(hwnd.clone())))
+        .chain(
+            workspace_map
+                .0
+                .iter()
+                .map(|(key, entity)| (*entity, UserDataKey::Workspace(key.clone()))),
+        )
+        // Entities spawned by commands that haven't been applied yet are picked up next frame
+        .filter(|(entity, _)| components.contains(*entity))
+        .collect();
+
+    for (entity, key) in &current {
+        match known.get(entity) {
+            None => {
+                let Some(data) = store.get(key) else {
+                    continue;
+                };
+                if !matches!(components.get(*entity), Ok((None, None))) {
+                    continue;
+                }
+                let mut entity = commands.entity(*entity);
+                if !data.tags.is_empty() {
+                    entity.insert(data.tags());
+                }
+                if !data.meta.is_empty() {
+                    entity.insert(data.meta());
+                }
+            }
+            Some(previous) if previous != key => store.rename(previous, key),
+            Some(_) => {}
+        }
+    }
+    for (entity, key) in known.iter() {
+        if matches!(key, UserDataKey::Window(_)) && !current.contains_key(entity) {
+            store.remove(key);
+        }
+    }
+
+    for (entity, tags, meta) in &changed {
+        if let Some(key) = current.get(&entity) {
+            store.set(key, UserData::from_components(tags, meta));
+        }
+    }
+    for entity in removed_tags.read().chain(removed_meta.read()) {
+        let (Some(key), Ok((tags, meta))) = (current.get(&entity), components.get(entity)) else {
+            continue;
+        };
+        store.set(key, UserData::from_components(tags, meta));
+    }
+
+    *known = current;
+}
+
+/// Writes the store to disk whenever it changed.
+pub fn save_user_data(path: Res<UserDataPath>, mut store: ResMut<UserDataStore>) {
+    if !store.dirty {
+        return;
+    }
+    store.dirty = false;
+    if let Err(e) = write_user_data_file(&path.path(), &store.to_file()) {
+        eprintln!("{}", e);
+    }
+}
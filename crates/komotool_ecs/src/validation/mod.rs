@@ -0,0 +1,3 @@
+pub mod validator;
+
+pub use validator::*;
@@ -0,0 +1,547 @@
+use bevy_ecs::reflect::ReflectResource;
+use bevy_ecs::system::Resource;
+use bevy_reflect::Reflect;
+use komorebi_client::State;
+use std::collections::HashSet;
+use std::fmt;
+
+/// A structural problem in a [`State`] that komorebi can't be trusted to apply.
+///
+/// Monitors, workspaces and containers are given by 1-based index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateViolation {
+    MonitorFocusOutOfRange {
+        focused: usize,
+        monitors: usize,
+    },
+    MonitorWithoutWorkspaces {
+        monitor: usize,
+    },
+    WorkspaceFocusOutOfRange {
+        monitor: usize,
+        focused: usize,
+        workspaces: usize,
+    },
+    ContainerFocusOutOfRange {
+        monitor: usize,
+        workspace: usize,
+        focused: usize,
+        containers: usize,
+    },
+    WindowFocusOutOfRange {
+        monitor: usize,
+        workspace: usize,
+        container: usize,
+        focused: usize,
+        windows: usize,
+    },
+    EmptyContainer {
+        monitor: usize,
+        workspace: usize,
+        container: usize,
+    },
+    DuplicateContainer {
+        id: String,
+    },
+    DuplicateWindow {
+        hwnd: isize,
+    },
+    ResizeDimensionsMismatch {
+        monitor: usize,
+        workspace: usize,
+        dimensions: usize,
+        containers: usize,
+    },
+}
+
+impl StateViolation {
+    /// Whether [`repair_state`] can fix this violation.
+    pub fn is_repairable(&self) -> bool {
+        !matches!(self, Self::MonitorWithoutWorkspaces { .. })
+    }
+}
+
+impl fmt::Display for StateViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MonitorFocusOutOfRange { focused, monitors } => write!(
+                f,
+                "focused monitor index {} is out of range for {} monitor(s)",
+                focused, monitors
+            ),
+            Self::MonitorWithoutWorkspaces { monitor } => {
+                write!(f, "monitor {} has no workspaces", monitor)
+            }
+            Self::WorkspaceFocusOutOfRange {
+                monitor,
+                focused,
+                workspaces,
+            } => write!(
+                f,
+                "monitor {}: focused workspace index {} is out of range for {} workspace(s)",
+                monitor, focused, workspaces
+            ),
+            Self::ContainerFocusOutOfRange {
+                monitor,
+                workspace,
+                focused,
+                containers,
+            } => write!(
+                f,
+                "monitor {} workspace {}: focused container index {} is out of range for {} container(s)",
+                monitor, workspace, focused, containers
+            ),
+            Self::WindowFocusOutOfRange {
+                monitor,
+                workspace,
+                container,
+                focused,
+                windows,
+            } => write!(
+                f,
+                "monitor {} workspace {} container {}: focused window index {} is out of range for {} window(s)",
+                monitor, workspace, container, focused, windows
+            ),
+            Self::EmptyContainer {
+                monitor,
+                workspace,
+                container,
+            } => write!(
+                f,
+                "monitor {} workspace {}: container {} has no windows",
+                monitor, workspace, container
+            ),
+            Self::DuplicateContainer { id } => {
+                write!(f, "container {} appears more than once", id)
+            }
+            Self::DuplicateWindow { hwnd } => write!(f, "window {} appears more than once", hwnd),
+            Self::ResizeDimensionsMismatch {
+                monitor,
+                workspace,
+                dimensions,
+                containers,
+            } => write!(
+                f,
+                "monitor {} workspace {}: {} resize dimension(s) for {} container(s)",
+                monitor, workspace, dimensions, containers
+            ),
+        }
+    }
+}
+
+pub fn format_violations(violations: &[StateViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Checks the structural invariants komorebi relies on: focus indices in range, every monitor
+/// with a workspace, no empty containers, no container or window in two places, and one resize
+/// dimension per container.
+///
+/// Windows are checked in the order monocle, maximized, tiled, floating, so a duplicate is
+/// always reported where [`repair_state`] can drop it. Pure, so it can be tested on
+/// synthetic states.
+pub fn validate_state(state: &State) -> Vec<StateViolation> {
+    let mut violations = Vec::new();
+    let monitors = state.monitors.elements();
+    if !monitors.is_empty() && state.monitors.focused_idx() >= monitors.len() {
+        violations.push(StateViolation::MonitorFocusOutOfRange {
+            focused: state.monitors.focused_idx(),
+            monitors: monitors.len(),
+        });
+    }
+
+    let mut container_ids = HashSet::new();
+    let mut hwnds = HashSet::new();
+    for (monitor_idx, monitor) in monitors.iter().enumerate() {
+        let monitor_number = monitor_idx + 1;
+        let workspaces = monitor.workspaces();
+        if workspaces.is_empty() {
+            violations.push(StateViolation::MonitorWithoutWorkspaces {
+                monitor: monitor_number,
+            });
+        } else if monitor.focused_workspace_idx() >= workspaces.len() {
+            violations.push(StateViolation::WorkspaceFocusOutOfRange {
+                monitor: monitor_number,
+                focused: monitor.focused_workspace_idx(),
+                workspaces: workspaces.len(),
+            });
+        }
+
+        for (workspace_idx, workspace) in workspaces.iter().enumerate() {
+            let workspace_number = workspace_idx + 1;
+            let containers = workspace.containers();
+            if !containers.is_empty() && workspace.focused_container_idx() >= containers.len() {
+                violations.push(StateViolation::ContainerFocusOutOfRange {
+                    monitor: monitor_number,
+                    workspace: workspace_number,
+                    focused: workspace.focused_container_idx(),
+                    containers: containers.len(),
+                });
+            }
+            if workspace.resize_dimensions.len() != containers.len() {
+                violations.push(StateViolation::ResizeDimensionsMismatch {
+                    monitor: monitor_number,
+                    workspace: workspace_number,
+                    dimensions: workspace.resize_dimensions.len(),
+                    containers: containers.len(),
+                });
+            }
+
+            let mut check_window = |hwnd: isize| {
+                if !hwnds.insert(hwnd) {
+                    violations.push(StateViolation::DuplicateWindow { hwnd });
+                }
+            };
+            if let Some(monocle) = workspace.monocle_container() {
+                monocle.windows().iter().for_each(|w| check_window(w.hwnd));
+            }
+            if let Some(maximized) = workspace.maximized_window() {
+                check_window(maximized.hwnd);
+            }
+            for container in containers {
+                container
+                    .windows()
+                    .iter()
+                    .for_each(|w| check_window(w.hwnd));
+            }
+            workspace
+                .floating_windows
+                .iter()
+                .for_each(|w| check_window(w.hwnd));
+
+            for (container_idx, container) in containers.iter().enumerate() {
+                let container_number = container_idx + 1;
+                if !container_ids.insert(container.id().clone()) {
+                    violations.push(StateViolation::DuplicateContainer {
+                        id: container.id().clone(),
+                    });
+                }
+                let windows = container.windows();
+                if windows.is_empty() {
+                    violations.push(StateViolation::EmptyContainer {
+                        monitor: monitor_number,
+                        workspace: workspace_number,
+                        container: container_number,
+                    });
+                } else if container.focused_window_idx() >= windows.len() {
+                    violations.push(StateViolation::WindowFocusOutOfRange {
+                        monitor: monitor_number,
+                        workspace: workspace_number,
+                        container: container_number,
+                        focused: container.focused_window_idx(),
+                        windows: windows.len(),
+                    });
+                }
+            }
+        }
+    }
+    violations
+}
+
+/// Fixes what it can of [`validate_state`]'s violations and returns the ones it found.
+///
+/// Later occurrences of a window or container are dropped, then empty containers, resize
+/// dimensions are padded or truncated to the containers, and focus indices are clamped.
+/// Monitors without workspaces can't be repaired.
+pub fn repair_state(state: &mut State) -> Vec<StateViolation> {
+    let violations = validate_state(state);
+    if violations.is_empty() {
+        return violations;
+    }
+
+    let mut container_ids = HashSet::new();
+    let mut hwnds = HashSet::new();
+    for monitor in state.monitors.elements_mut() {
+        for workspace in monitor.workspaces.elements_mut() {
+            if let Some(monocle) = workspace.monocle_container() {
+                hwnds.extend(monocle.windows().iter().map(|w| w.hwnd));
+            }
+            if let Some(maximized) = workspace.maximized_window() {
+                hwnds.insert(maximized.hwnd);
+            }
+
+            let focused_container = workspace.focused_container_idx();
+            let mut kept = Vec::new();
+            let mut resize_dimensions = Vec::new();
+            for (idx, mut container) in workspace.containers.elements_mut().drain(..).enumerate() {
+                if !container_ids.insert(container.id().clone()) {
+                    continue;
+                }
+                container.windows_mut().retain(|w| hwnds.insert(w.hwnd));
+                if container.windows().is_empty() {
+                    continue;
+                }
+                let windows = container.windows().len();
+                if container.focused_window_idx() >= windows {
+                    container.focus_window(windows - 1);
+                }
+                resize_dimensions.push(workspace.resize_dimensions.get(idx).copied().flatten());
+                kept.push(container);
+            }
+            let containers = kept.len();
+            workspace.containers.elements_mut().extend(kept);
+            workspace.resize_dimensions = resize_dimensions;
+            if containers > 0 && focused_container >= containers {
+                workspace.containers.focus(containers - 1);
+            }
+
+            workspace.floating_windows.retain(|w| hwnds.insert(w.hwnd));
+        }
+
+        let workspaces = monitor.workspaces.elements().len();
+        if workspaces > 0 && monitor.focused_workspace_idx() >= workspaces {
+            monitor.workspaces.focus(workspaces - 1);
+        }
+    }
+
+    let monitors = state.monitors.elements().len();
+    if monitors > 0 && state.monitors.focused_idx() >= monitors {
+        state.monitors.focus(monitors - 1);
+    }
+    violations
+}
+
+/// What export paths do with a state that fails [`validate_state`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum InvalidStatePolicy {
+    /// Don't send it
+    #[default]
+    Reject,
+    /// Send it after [`repair_state`], if that leaves it valid
+    Repair,
+}
+
+impl InvalidStatePolicy {
+    pub fn parse(policy: &str) -> Option<Self> {
+        match policy {
+            "reject" => Some(Self::Reject),
+            "repair" => Some(Self::Repair),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Reject => "reject",
+            Self::Repair => "repair",
+        }
+    }
+}
+
+/// How states are validated before they are sent to komorebi.
+///
+/// Scripts pick the policy with `Komotool.set_invalid_state_policy`.
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct StateValidation {
+    pub policy: InvalidStatePolicy,
+}
+
+/// Validates a state about to be sent to komorebi, repairing it if the policy allows.
+///
+/// The error lists every violation, and repairs are logged.
+pub fn checked_export_state(state: State, policy: InvalidStatePolicy) -> Result<State, String> {
+    let violations = validate_state(&state);
+    if violations.is_empty() {
+        return Ok(state);
+    }
+    if policy == InvalidStatePolicy::Reject || violations.iter().any(|v| !v.is_repairable()) {
+        return Err(format!("Invalid state: {}", format_violations(&violations)));
+    }
+
+    let mut state = state;
+    let repaired = repair_state(&mut state);
+    let remaining = validate_state(&state);
+    if !remaining.is_empty() {
+        return Err(format!(
+            "Invalid state after repair: {}",
+            format_violations(&remaining)
+        ));
+    }
+    eprintln!(
+        "Repaired state before export: {}",
+        format_violations(&repaired)
+    );
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_states::*;
+    use komorebi_client::Workspace;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    /// (focused window, hwnds)
+    type ContainerSpec = (usize, Vec<isize>);
+
+    /// (focused container, containers, resize dimension count, floating hwnds, duplicate the
+    /// first container)
+    type WorkspaceSpec = (usize, Vec<ContainerSpec>, usize, Vec<isize>, bool);
+
+    /// (focused monitor, monitors as (focused workspace, workspaces))
+    type StateSpec = (usize, Vec<(usize, Vec<WorkspaceSpec>)>);
+
+    /// States with out of range focus, duplicate and empty containers, duplicate windows and
+    /// mismatched resize dimensions, but a workspace on every monitor.
+    fn repairable_states() -> impl Strategy<Value = StateSpec> {
+        let container = (0..4usize, vec(1..12isize, 0..4));
+        let workspace = (
+            0..5usize,
+            vec(container, 0..4),
+            0..5usize,
+            vec(1..12isize, 0..3),
+            any::<bool>(),
+        );
+        (0..4usize, vec((0..4usize, vec(workspace, 1..3)), 1..3))
+    }
+
+    fn build_workspace(spec: &WorkspaceSpec) -> Workspace {
+        let (focused, containers, resize_dimensions, floating, duplicate) = spec;
+        let mut workspace = workspace("", &[]);
+        for (focused_window, hwnds) in containers {
+            let mut container = container(hwnds);
+            container.focus_window(*focused_window);
+            workspace.containers.elements_mut().push_back(container);
+        }
+        if *duplicate {
+            if let Some(first) = workspace.containers().front().cloned() {
+                workspace.containers.elements_mut().push_back(first);
+            }
+        }
+        workspace.containers.focus(*focused);
+        workspace.resize_dimensions = vec![None; *resize_dimensions];
+        workspace.floating_windows = floating.iter().copied().map(window).collect();
+        workspace
+    }
+
+    fn build_state(spec: &StateSpec) -> State {
+        let (focused, monitors) = spec;
+        let monitors = monitors
+            .iter()
+            .enumerate()
+            .map(|(idx, (focused_workspace, workspaces))| {
+                let mut monitor = monitor(
+                    &format!("m{}", idx),
+                    workspaces.iter().map(build_workspace).collect(),
+                );
+                monitor.workspaces.focus(*focused_workspace);
+                monitor
+            })
+            .collect();
+        let mut state = state(monitors);
+        state.monitors.focus(*focused);
+        state
+    }
+
+    /// Turns a spec into a valid one: every hwnd unique, no empty containers, focus in range
+    /// and one resize dimension per container.
+    fn make_valid(spec: &StateSpec) -> StateSpec {
+        let mut next_hwnd = 0;
+        let mut hwnd = || {
+            next_hwnd += 1;
+            next_hwnd
+        };
+        let (focused, monitors) = spec;
+        let monitors: Vec<_> = monitors
+            .iter()
+            .map(|(focused_workspace, workspaces)| {
+                let workspaces: Vec<WorkspaceSpec> = workspaces
+                    .iter()
+                    .map(|(focused_container, containers, _, floating, _)| {
+                        let containers: Vec<ContainerSpec> = containers
+                            .iter()
+                            .map(|(focused_window, hwnds)| {
+                                let len = hwnds.len().max(1);
+                                (focused_window % len, (0..len).map(|_| hwnd()).collect())
+                            })
+                            .collect();
+                        let floating = floating.iter().map(|_| hwnd()).collect();
+                        let len = containers.len();
+                        (
+                            focused_container % len.max(1),
+                            containers,
+                            len,
+                            floating,
+                            false,
+                        )
+                    })
+                    .collect();
+                (focused_workspace % workspaces.len(), workspaces)
+            })
+            .collect();
+        (focused % monitors.len(), monitors)
+    }
+
+    proptest! {
+        #[test]
+        fn repaired_states_are_valid(spec in repairable_states()) {
+            let original = build_state(&spec);
+            prop_assert!(validate_state(&original).iter().all(StateViolation::is_repairable));
+            let mut repaired = original.clone();
+            repair_state(&mut repaired);
+            prop_assert_eq!(validate_state(&repaired), Vec::new());
+
+            // Duplicates are dropped, but every window is kept once
+            let hwnds = |state: &State| -> Vec<isize> {
+                let tiled = tiled_hwnds(state).into_iter().flatten().flatten().flatten();
+                let floating = floating_hwnds(state).into_iter().flatten().flatten();
+                tiled.chain(floating).collect()
+            };
+            let repaired_hwnds = hwnds(&repaired);
+            let unique: HashSet<isize> = repaired_hwnds.iter().copied().collect();
+            prop_assert_eq!(unique.len(), repaired_hwnds.len());
+            prop_assert_eq!(unique, hwnds(&original).into_iter().collect::<HashSet<_>>());
+
+            prop_assert!(repaired.monitors.focused_idx() < repaired.monitors.elements().len());
+            for monitor in repaired.monitors.elements() {
+                prop_assert!(monitor.focused_workspace_idx() < monitor.workspaces().len());
+                for workspace in monitor.workspaces() {
+                    let containers = workspace.containers();
+                    prop_assert_eq!(workspace.resize_dimensions.len(), containers.len());
+                    if !containers.is_empty() {
+                        prop_assert!(workspace.focused_container_idx() < containers.len());
+                    }
+                    for container in containers {
+                        prop_assert!(!container.windows().is_empty());
+                        prop_assert!(container.focused_window_idx() < container.windows().len());
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn valid_states_are_left_alone(spec in repairable_states()) {
+            let state = build_state(&make_valid(&spec));
+            prop_assert_eq!(validate_state(&state), Vec::new());
+            let mut repaired = state.clone();
+            prop_assert_eq!(repair_state(&mut repaired), Vec::new());
+            prop_assert_eq!(json(&repaired), json(&state));
+        }
+    }
+
+    #[test]
+    fn policies_round_trip_through_their_names() {
+        for policy in [InvalidStatePolicy::Reject, InvalidStatePolicy::Repair] {
+            assert_eq!(InvalidStatePolicy::parse(policy.as_str()), Some(policy));
+        }
+        assert_eq!(InvalidStatePolicy::parse("fix"), None);
+    }
+
+    #[test]
+    fn monitors_without_workspaces_are_not_repairable() {
+        let mut state = state(vec![monitor("m0", Vec::new())]);
+        let violations = repair_state(&mut state);
+        assert_eq!(
+            violations,
+            vec![StateViolation::MonitorWithoutWorkspaces { monitor: 1 }]
+        );
+        assert!(!violations.iter().any(StateViolation::is_repairable));
+        assert_eq!(validate_state(&state), violations);
+    }
+}
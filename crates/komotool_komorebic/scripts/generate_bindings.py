@@ -206,11 +206,43 @@ def generate_conversion_code(def_name: str, def_data: dict, param_name: str) ->
     
     return ''
 
+HEADER = """use bevy_app::{App, Plugin};
+use bevy_mod_scripting::core::bindings::function::namespace::NamespaceBuilder;
+use bevy_reflect::Reflect;
+use komorebi_client::*;
+use komotool_utils::komorebi_commands::send_komorebi_message;
+
+#[derive(Reflect)]
+struct Komorebic;
+
+#[derive(Default)]
+pub struct KomoToolKomorebicPlugin;
+
+impl Plugin for KomoToolKomorebicPlugin {
+    fn build(&self, app: &mut App) {
+        NamespaceBuilder::<Komorebic>::new(app.world_mut())"""
+
+FOOTER = """;
+    }
+}"""
+
+def indent(code: str, first: int, rest: int) -> str:
+    """Indents the first line of a registration by `first` spaces and the others by `rest`"""
+    lines = code.split('\n')
+    return '\n'.join(
+        [' ' * first + lines[0]] + [(' ' * rest + line) if line else line for line in lines[1:]]
+    )
+
 def generate_registrations(schema_file):
+    """Prints crates/komotool_komorebic/src/lib.rs for komorebi's SocketMessage schema.
+
+    Messages go through komotool_utils' send_komorebi_message, so they count towards undo
+    history and follow the outgoing mode.
+    """
     with open(schema_file) as f:
         schema = json.load(f)
     
-    print("NamespaceBuilder::<KomorebiMessageWrapper>::new(world)")
+    print(HEADER, end='')
     
     for variant in schema['oneOf']:
         msg_type = variant['properties']['type']['enum'][0]
@@ -242,23 +274,24 @@ def generate_registrations(schema_file):
                     converted_params.append(param_name)
                 elif is_optional:
                     conversion = f"""let {param_name} = match {param_name}.as_str() {{
-        "" => None,
-        _ => Some({param_name}),
-    }};"""
+    "" => None,
+    _ => Some({param_name}),
+}};
+"""
                     conversions.append(conversion)
                     converted_params.append(param_name)
                 else:
                     converted_params.append(param_name)
             
-            # Add all conversion blocks
-            registration += '\n'.join(conversions)
+            # Add all conversion blocks, indented into the closure body
+            registration += ''.join(indent(conversion, 4, 4) for conversion in conversions)
             
             # Build message with converted params
             # Convert snake_case parameter names to CamelCase for enum variants
             camelized_params = [snake_to_camel(p) if p in schema['definitions'] else p for p in converted_params]
             registration += f"    let message = SocketMessage::{msg_type}({', '.join(camelized_params)});\n"
         
-        registration += f"""    match send_message(&message) {{
+        registration += f"""    match send_komorebi_message(&message) {{
         Ok(_) => true,
         Err(e) => {{
             log::error!("Failed to send {fn_name} message: {{}}", e);
@@ -266,7 +299,8 @@ def generate_registrations(schema_file):
         }}
     }}
 }})"""
-        print(f"    {registration}")
+        print('\n' + indent(registration, 12, 12), end='')
+    print(FOOTER)
 
 if __name__ == "__main__":
     if len(sys.argv) != 2:
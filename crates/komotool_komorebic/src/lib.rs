@@ -2,6 +2,7 @@ use bevy_app::{App, Plugin};
 use bevy_mod_scripting::core::bindings::function::namespace::NamespaceBuilder;
 use bevy_reflect::Reflect;
 use komorebi_client::*;
+use komotool_utils::komorebi_commands::send_komorebi_message;
 
 #[derive(Reflect)]
 struct Komorebic;
@@ -24,7 +25,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::FocusWindow(operation_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send focus_window message: {}", e);
@@ -44,7 +45,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::MoveWindow(operation_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send move_window message: {}", e);
@@ -62,7 +63,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CycleFocusWindow(cycle_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cycle_focus_window message: {}", e);
@@ -80,7 +81,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CycleMoveWindow(cycle_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cycle_move_window message: {}", e);
@@ -100,7 +101,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::StackWindow(operation_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stack_window message: {}", e);
@@ -110,7 +111,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("unstack_window", || {
                 let message = SocketMessage::UnstackWindow;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send unstack_window message: {}", e);
@@ -128,7 +129,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CycleStack(cycle_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cycle_stack message: {}", e);
@@ -146,7 +147,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CycleStackIndex(cycle_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cycle_stack_index message: {}", e);
@@ -156,7 +157,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("focus_stack_window", |param: usize| {
                 let message = SocketMessage::FocusStackWindow(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send focus_stack_window message: {}", e);
@@ -166,7 +167,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("stack_all", || {
                 let message = SocketMessage::StackAll;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stack_all message: {}", e);
@@ -176,7 +177,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("unstack_all", || {
                 let message = SocketMessage::UnstackAll;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send unstack_all message: {}", e);
@@ -205,7 +206,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::ResizeWindowEdge(operation_direction, sizing);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send resize_window_edge message: {}", e);
@@ -233,7 +234,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::ResizeWindowAxis(axis, sizing);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send resize_window_axis message: {}", e);
@@ -243,7 +244,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("move_container_to_monitor_number", |param: usize| {
                 let message = SocketMessage::MoveContainerToMonitorNumber(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send move_container_to_monitor_number message: {}", e);
@@ -261,7 +262,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CycleMoveContainerToMonitor(cycle_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cycle_move_container_to_monitor message: {}", e);
@@ -271,7 +272,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("move_container_to_workspace_number", |param: usize| {
                 let message = SocketMessage::MoveContainerToWorkspaceNumber(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send move_container_to_workspace_number message: {}", e);
@@ -281,7 +282,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("move_container_to_named_workspace", |param: String| {
                 let message = SocketMessage::MoveContainerToNamedWorkspace(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send move_container_to_named_workspace message: {}", e);
@@ -299,7 +300,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CycleMoveContainerToWorkspace(cycle_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cycle_move_container_to_workspace message: {}", e);
@@ -309,7 +310,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("send_container_to_monitor_number", |param: usize| {
                 let message = SocketMessage::SendContainerToMonitorNumber(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send send_container_to_monitor_number message: {}", e);
@@ -327,7 +328,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CycleSendContainerToMonitor(cycle_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cycle_send_container_to_monitor message: {}", e);
@@ -337,7 +338,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("send_container_to_workspace_number", |param: usize| {
                 let message = SocketMessage::SendContainerToWorkspaceNumber(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send send_container_to_workspace_number message: {}", e);
@@ -355,7 +356,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CycleSendContainerToWorkspace(cycle_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cycle_send_container_to_workspace message: {}", e);
@@ -365,7 +366,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("send_container_to_monitor_workspace_number", |param_0: usize, param_1: usize| {
                 let message = SocketMessage::SendContainerToMonitorWorkspaceNumber(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send send_container_to_monitor_workspace_number message: {}", e);
@@ -375,7 +376,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("move_container_to_monitor_workspace_number", |param_0: usize, param_1: usize| {
                 let message = SocketMessage::MoveContainerToMonitorWorkspaceNumber(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send move_container_to_monitor_workspace_number message: {}", e);
@@ -385,7 +386,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("send_container_to_named_workspace", |param: String| {
                 let message = SocketMessage::SendContainerToNamedWorkspace(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send send_container_to_named_workspace message: {}", e);
@@ -403,7 +404,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CycleMoveWorkspaceToMonitor(cycle_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cycle_move_workspace_to_monitor message: {}", e);
@@ -413,7 +414,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("move_workspace_to_monitor_number", |param: usize| {
                 let message = SocketMessage::MoveWorkspaceToMonitorNumber(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send move_workspace_to_monitor_number message: {}", e);
@@ -423,7 +424,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("swap_workspaces_to_monitor_number", |param: usize| {
                 let message = SocketMessage::SwapWorkspacesToMonitorNumber(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send swap_workspaces_to_monitor_number message: {}", e);
@@ -433,7 +434,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("force_focus", || {
                 let message = SocketMessage::ForceFocus;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send force_focus message: {}", e);
@@ -443,7 +444,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("close", || {
                 let message = SocketMessage::Close;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send close message: {}", e);
@@ -453,7 +454,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("minimize", || {
                 let message = SocketMessage::Minimize;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send minimize message: {}", e);
@@ -463,7 +464,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("promote", || {
                 let message = SocketMessage::Promote;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send promote message: {}", e);
@@ -473,7 +474,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("promote_focus", || {
                 let message = SocketMessage::PromoteFocus;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send promote_focus message: {}", e);
@@ -493,7 +494,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::PromoteWindow(operation_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send promote_window message: {}", e);
@@ -503,7 +504,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("eager_focus", |param: String| {
                 let message = SocketMessage::EagerFocus(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send eager_focus message: {}", e);
@@ -513,7 +514,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_float", || {
                 let message = SocketMessage::ToggleFloat;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_float message: {}", e);
@@ -523,7 +524,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_monocle", || {
                 let message = SocketMessage::ToggleMonocle;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_monocle message: {}", e);
@@ -533,7 +534,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_maximize", || {
                 let message = SocketMessage::ToggleMaximize;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_maximize message: {}", e);
@@ -543,7 +544,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_window_container_behaviour", || {
                 let message = SocketMessage::ToggleWindowContainerBehaviour;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_window_container_behaviour message: {}", e);
@@ -553,7 +554,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_float_override", || {
                 let message = SocketMessage::ToggleFloatOverride;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_float_override message: {}", e);
@@ -572,7 +573,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::WindowHidingBehaviour(hiding_behaviour);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send window_hiding_behaviour message: {}", e);
@@ -582,7 +583,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_cross_monitor_move_behaviour", || {
                 let message = SocketMessage::ToggleCrossMonitorMoveBehaviour;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_cross_monitor_move_behaviour message: {}", e);
@@ -601,7 +602,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CrossMonitorMoveBehaviour(move_behaviour);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cross_monitor_move_behaviour message: {}", e);
@@ -619,7 +620,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::UnmanagedWindowOperationBehaviour(operation_behaviour);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send unmanaged_window_operation_behaviour message: {}", e);
@@ -629,7 +630,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("manage_focused_window", || {
                 let message = SocketMessage::ManageFocusedWindow;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send manage_focused_window message: {}", e);
@@ -639,7 +640,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("unmanage_focused_window", || {
                 let message = SocketMessage::UnmanageFocusedWindow;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send unmanage_focused_window message: {}", e);
@@ -657,7 +658,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::AdjustContainerPadding(sizing, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send adjust_container_padding message: {}", e);
@@ -675,7 +676,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::AdjustWorkspacePadding(sizing, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send adjust_workspace_padding message: {}", e);
@@ -699,7 +700,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::ChangeLayout(default_layout);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send change_layout message: {}", e);
@@ -717,7 +718,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CycleLayout(cycle_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cycle_layout message: {}", e);
@@ -727,7 +728,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("change_layout_custom", |param: String| {
                 let message = SocketMessage::ChangeLayoutCustom(std::path::PathBuf::from(param));
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send change_layout_custom message: {}", e);
@@ -746,7 +747,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::FlipLayout(axis);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send flip_layout message: {}", e);
@@ -756,7 +757,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_workspace_window_container_behaviour", || {
                 let message = SocketMessage::ToggleWorkspaceWindowContainerBehaviour;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_workspace_window_container_behaviour message: {}", e);
@@ -766,7 +767,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_workspace_float_override", || {
                 let message = SocketMessage::ToggleWorkspaceFloatOverride;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_workspace_float_override message: {}", e);
@@ -776,7 +777,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("monitor_index_preference", |param_0: usize, param_1: i32, param_2: i32, param_3: i32, param_4: i32| {
                 let message = SocketMessage::MonitorIndexPreference(param_0, param_1, param_2, param_3, param_4);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send monitor_index_preference message: {}", e);
@@ -786,7 +787,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("display_index_preference", |param_0: usize, param_1: String| {
                 let message = SocketMessage::DisplayIndexPreference(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send display_index_preference message: {}", e);
@@ -796,7 +797,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("ensure_workspaces", |param_0: usize, param_1: usize| {
                 let message = SocketMessage::EnsureWorkspaces(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send ensure_workspaces message: {}", e);
@@ -806,7 +807,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("ensure_named_workspaces", |param_0: usize, param_1: Vec<String>| {
                 let message = SocketMessage::EnsureNamedWorkspaces(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send ensure_named_workspaces message: {}", e);
@@ -816,7 +817,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("new_workspace", || {
                 let message = SocketMessage::NewWorkspace;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send new_workspace message: {}", e);
@@ -826,7 +827,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_tiling", || {
                 let message = SocketMessage::ToggleTiling;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_tiling message: {}", e);
@@ -836,7 +837,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("stop", || {
                 let message = SocketMessage::Stop;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stop message: {}", e);
@@ -846,7 +847,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("stop_ignore_restore", || {
                 let message = SocketMessage::StopIgnoreRestore;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stop_ignore_restore message: {}", e);
@@ -856,7 +857,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_pause", || {
                 let message = SocketMessage::TogglePause;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_pause message: {}", e);
@@ -866,7 +867,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("retile", || {
                 let message = SocketMessage::Retile;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send retile message: {}", e);
@@ -876,7 +877,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("retile_with_resize_dimensions", || {
                 let message = SocketMessage::RetileWithResizeDimensions;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send retile_with_resize_dimensions message: {}", e);
@@ -886,7 +887,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("quick_save", || {
                 let message = SocketMessage::QuickSave;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send quick_save message: {}", e);
@@ -896,7 +897,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("quick_load", || {
                 let message = SocketMessage::QuickLoad;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send quick_load message: {}", e);
@@ -906,7 +907,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("save", |param: String| {
                 let message = SocketMessage::Save(std::path::PathBuf::from(param));
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send save message: {}", e);
@@ -916,7 +917,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("load", |param: String| {
                 let message = SocketMessage::Load(std::path::PathBuf::from(param));
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send load message: {}", e);
@@ -934,7 +935,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CycleFocusMonitor(cycle_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cycle_focus_monitor message: {}", e);
@@ -952,7 +953,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::CycleFocusWorkspace(cycle_direction);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send cycle_focus_workspace message: {}", e);
@@ -962,7 +963,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("focus_monitor_number", |param: usize| {
                 let message = SocketMessage::FocusMonitorNumber(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send focus_monitor_number message: {}", e);
@@ -972,7 +973,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("focus_last_workspace", || {
                 let message = SocketMessage::FocusLastWorkspace;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send focus_last_workspace message: {}", e);
@@ -982,7 +983,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("close_workspace", || {
                 let message = SocketMessage::CloseWorkspace;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send close_workspace message: {}", e);
@@ -992,7 +993,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("focus_workspace_number", |param: usize| {
                 let message = SocketMessage::FocusWorkspaceNumber(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send focus_workspace_number message: {}", e);
@@ -1002,7 +1003,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("focus_workspace_numbers", |param: usize| {
                 let message = SocketMessage::FocusWorkspaceNumbers(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send focus_workspace_numbers message: {}", e);
@@ -1012,7 +1013,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("focus_monitor_workspace_number", |param_0: usize, param_1: usize| {
                 let message = SocketMessage::FocusMonitorWorkspaceNumber(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send focus_monitor_workspace_number message: {}", e);
@@ -1022,7 +1023,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("focus_named_workspace", |param: String| {
                 let message = SocketMessage::FocusNamedWorkspace(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send focus_named_workspace message: {}", e);
@@ -1032,7 +1033,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("container_padding", |param_0: usize, param_1: usize, param_2: i32| {
                 let message = SocketMessage::ContainerPadding(param_0, param_1, param_2);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send container_padding message: {}", e);
@@ -1042,7 +1043,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("named_workspace_container_padding", |param_0: String, param_1: i32| {
                 let message = SocketMessage::NamedWorkspaceContainerPadding(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send named_workspace_container_padding message: {}", e);
@@ -1052,7 +1053,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("focused_workspace_container_padding", |param: i32| {
                 let message = SocketMessage::FocusedWorkspaceContainerPadding(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send focused_workspace_container_padding message: {}", e);
@@ -1062,7 +1063,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("workspace_padding", |param_0: usize, param_1: usize, param_2: i32| {
                 let message = SocketMessage::WorkspacePadding(param_0, param_1, param_2);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send workspace_padding message: {}", e);
@@ -1072,7 +1073,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("named_workspace_padding", |param_0: String, param_1: i32| {
                 let message = SocketMessage::NamedWorkspacePadding(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send named_workspace_padding message: {}", e);
@@ -1082,7 +1083,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("focused_workspace_padding", |param: i32| {
                 let message = SocketMessage::FocusedWorkspacePadding(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send focused_workspace_padding message: {}", e);
@@ -1092,7 +1093,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("workspace_tiling", |param_0: usize, param_1: usize, param_2: bool| {
                 let message = SocketMessage::WorkspaceTiling(param_0, param_1, param_2);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send workspace_tiling message: {}", e);
@@ -1102,7 +1103,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("named_workspace_tiling", |param_0: String, param_1: bool| {
                 let message = SocketMessage::NamedWorkspaceTiling(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send named_workspace_tiling message: {}", e);
@@ -1112,7 +1113,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("workspace_name", |param_0: usize, param_1: usize, param_2: String| {
                 let message = SocketMessage::WorkspaceName(param_0, param_1, param_2);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send workspace_name message: {}", e);
@@ -1136,7 +1137,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::WorkspaceLayout(param_0, param_1, default_layout);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send workspace_layout message: {}", e);
@@ -1160,7 +1161,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::NamedWorkspaceLayout(param_0, default_layout);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send named_workspace_layout message: {}", e);
@@ -1170,7 +1171,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("workspace_layout_custom", |param_0: usize, param_1: usize, param_2: String| {
                 let message = SocketMessage::WorkspaceLayoutCustom(param_0, param_1, std::path::PathBuf::from(param_2));
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send workspace_layout_custom message: {}", e);
@@ -1180,7 +1181,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("named_workspace_layout_custom", |param_0: String, param_1: String| {
                 let message = SocketMessage::NamedWorkspaceLayoutCustom(param_0, std::path::PathBuf::from(param_1));
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send named_workspace_layout_custom message: {}", e);
@@ -1204,7 +1205,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::WorkspaceLayoutRule(param_0, param_1, param_2, default_layout);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send workspace_layout_rule message: {}", e);
@@ -1228,7 +1229,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::NamedWorkspaceLayoutRule(param_0, param_1, default_layout);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send named_workspace_layout_rule message: {}", e);
@@ -1238,7 +1239,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("workspace_layout_custom_rule", |param_0: usize, param_1: usize, param_2: usize, param_3: String| {
                 let message = SocketMessage::WorkspaceLayoutCustomRule(param_0, param_1, param_2, std::path::PathBuf::from(param_3));
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send workspace_layout_custom_rule message: {}", e);
@@ -1248,7 +1249,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("named_workspace_layout_custom_rule", |param_0: String, param_1: usize, param_2: String| {
                 let message = SocketMessage::NamedWorkspaceLayoutCustomRule(param_0, param_1, std::path::PathBuf::from(param_2));
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send named_workspace_layout_custom_rule message: {}", e);
@@ -1258,7 +1259,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("clear_workspace_layout_rules", |param_0: usize, param_1: usize| {
                 let message = SocketMessage::ClearWorkspaceLayoutRules(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send clear_workspace_layout_rules message: {}", e);
@@ -1268,7 +1269,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("clear_named_workspace_layout_rules", |param: String| {
                 let message = SocketMessage::ClearNamedWorkspaceLayoutRules(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send clear_named_workspace_layout_rules message: {}", e);
@@ -1278,7 +1279,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("reload_configuration", || {
                 let message = SocketMessage::ReloadConfiguration;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send reload_configuration message: {}", e);
@@ -1288,7 +1289,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("replace_configuration", |param: String| {
                 let message = SocketMessage::ReplaceConfiguration(std::path::PathBuf::from(param));
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send replace_configuration message: {}", e);
@@ -1298,7 +1299,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("reload_static_configuration", |param: String| {
                 let message = SocketMessage::ReloadStaticConfiguration(std::path::PathBuf::from(param));
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send reload_static_configuration message: {}", e);
@@ -1308,7 +1309,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("watch_configuration", |param: bool| {
                 let message = SocketMessage::WatchConfiguration(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send watch_configuration message: {}", e);
@@ -1318,7 +1319,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("complete_configuration", || {
                 let message = SocketMessage::CompleteConfiguration;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send complete_configuration message: {}", e);
@@ -1328,7 +1329,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("alt_focus_hack", |param: bool| {
                 let message = SocketMessage::AltFocusHack(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send alt_focus_hack message: {}", e);
@@ -1347,7 +1348,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::Animation(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send animation message: {}", e);
@@ -1366,7 +1367,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::AnimationDuration(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send animation_duration message: {}", e);
@@ -1376,7 +1377,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("animation_fps", |param: u64| {
                 let message = SocketMessage::AnimationFps(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send animation_fps message: {}", e);
@@ -1431,7 +1432,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::AnimationStyle(animation_style, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send animation_style message: {}", e);
@@ -1441,7 +1442,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("border", |param: bool| {
                 let message = SocketMessage::Border(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send border message: {}", e);
@@ -1462,7 +1463,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::BorderColour(window_kind, param_1, param_2, param_3);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send border_colour message: {}", e);
@@ -1481,7 +1482,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::BorderStyle(border_style);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send border_style message: {}", e);
@@ -1491,7 +1492,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("border_width", |param: i32| {
                 let message = SocketMessage::BorderWidth(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send border_width message: {}", e);
@@ -1501,7 +1502,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("border_offset", |param: i32| {
                 let message = SocketMessage::BorderOffset(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send border_offset message: {}", e);
@@ -1519,7 +1520,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::BorderImplementation(border_implementation);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send border_implementation message: {}", e);
@@ -1529,7 +1530,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("transparency", |param: bool| {
                 let message = SocketMessage::Transparency(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send transparency message: {}", e);
@@ -1539,7 +1540,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_transparency", || {
                 let message = SocketMessage::ToggleTransparency;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_transparency message: {}", e);
@@ -1549,7 +1550,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("transparency_alpha", |param: u8| {
                 let message = SocketMessage::TransparencyAlpha(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send transparency_alpha message: {}", e);
@@ -1565,7 +1566,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     top,
                 };
                 let message = SocketMessage::InvisibleBorders(rect);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send invisible_borders message: {}", e);
@@ -1584,7 +1585,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::StackbarMode(stackbar_mode);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stackbar_mode message: {}", e);
@@ -1602,7 +1603,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::StackbarLabel(stackbar_label);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stackbar_label message: {}", e);
@@ -1612,7 +1613,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("stackbar_focused_text_colour", |param_0: u32, param_1: u32, param_2: u32| {
                 let message = SocketMessage::StackbarFocusedTextColour(param_0, param_1, param_2);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stackbar_focused_text_colour message: {}", e);
@@ -1622,7 +1623,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("stackbar_unfocused_text_colour", |param_0: u32, param_1: u32, param_2: u32| {
                 let message = SocketMessage::StackbarUnfocusedTextColour(param_0, param_1, param_2);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stackbar_unfocused_text_colour message: {}", e);
@@ -1632,7 +1633,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("stackbar_background_colour", |param_0: u32, param_1: u32, param_2: u32| {
                 let message = SocketMessage::StackbarBackgroundColour(param_0, param_1, param_2);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stackbar_background_colour message: {}", e);
@@ -1642,7 +1643,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("stackbar_height", |param: i32| {
                 let message = SocketMessage::StackbarHeight(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stackbar_height message: {}", e);
@@ -1652,7 +1653,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("stackbar_tab_width", |param: i32| {
                 let message = SocketMessage::StackbarTabWidth(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stackbar_tab_width message: {}", e);
@@ -1662,7 +1663,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("stackbar_font_size", |param: i32| {
                 let message = SocketMessage::StackbarFontSize(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stackbar_font_size message: {}", e);
@@ -1676,7 +1677,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     _ => Some(param),
                 };
                 let message = SocketMessage::StackbarFontFamily(font);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send stackbar_font_family message: {}", e);
@@ -1692,7 +1693,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     top,
                 };
                 let message = SocketMessage::WorkAreaOffset(rect);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send work_area_offset message: {}", e);
@@ -1708,7 +1709,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     top,
                 };
                 let message = SocketMessage::MonitorWorkAreaOffset(param_0, rect);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send monitor_work_area_offset message: {}", e);
@@ -1718,7 +1719,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("resize_delta", |param: i32| {
                 let message = SocketMessage::ResizeDelta(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send resize_delta message: {}", e);
@@ -1738,7 +1739,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::InitialWorkspaceRule(application_identifier, param_1, param_2, param_3);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send initial_workspace_rule message: {}", e);
@@ -1758,7 +1759,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::InitialNamedWorkspaceRule(application_identifier, param_1, param_2);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send initial_named_workspace_rule message: {}", e);
@@ -1778,7 +1779,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::WorkspaceRule(application_identifier, param_1, param_2, param_3);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send workspace_rule message: {}", e);
@@ -1798,7 +1799,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::NamedWorkspaceRule(application_identifier, param_1, param_2);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send named_workspace_rule message: {}", e);
@@ -1808,7 +1809,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("clear_workspace_rules", |param_0: usize, param_1: usize| {
                 let message = SocketMessage::ClearWorkspaceRules(param_0, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send clear_workspace_rules message: {}", e);
@@ -1818,7 +1819,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("clear_named_workspace_rules", |param: String| {
                 let message = SocketMessage::ClearNamedWorkspaceRules(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send clear_named_workspace_rules message: {}", e);
@@ -1828,7 +1829,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("clear_all_workspace_rules", || {
                 let message = SocketMessage::ClearAllWorkspaceRules;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send clear_all_workspace_rules message: {}", e);
@@ -1838,7 +1839,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("enforce_workspace_rules", || {
                 let message = SocketMessage::EnforceWorkspaceRules;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send enforce_workspace_rules message: {}", e);
@@ -1858,7 +1859,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::IgnoreRule(application_identifier, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send ignore_rule message: {}", e);
@@ -1878,7 +1879,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::ManageRule(application_identifier, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send manage_rule message: {}", e);
@@ -1898,7 +1899,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::IdentifyObjectNameChangeApplication(application_identifier, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send identify_object_name_change_application message: {}", e);
@@ -1918,7 +1919,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::IdentifyTrayApplication(application_identifier, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send identify_tray_application message: {}", e);
@@ -1938,7 +1939,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::IdentifyLayeredApplication(application_identifier, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send identify_layered_application message: {}", e);
@@ -1958,7 +1959,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::IdentifyBorderOverflowApplication(application_identifier, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send identify_border_overflow_application message: {}", e);
@@ -1968,7 +1969,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("state", || {
                 let message = SocketMessage::State;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send state message: {}", e);
@@ -1978,7 +1979,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("global_state", || {
                 let message = SocketMessage::GlobalState;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send global_state message: {}", e);
@@ -1988,7 +1989,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("visible_windows", || {
                 let message = SocketMessage::VisibleWindows;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send visible_windows message: {}", e);
@@ -1998,7 +1999,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("monitor_information", || {
                 let message = SocketMessage::MonitorInformation;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send monitor_information message: {}", e);
@@ -2018,7 +2019,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::Query(state_query);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send query message: {}", e);
@@ -2036,7 +2037,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::FocusFollowsMouse(focus_follows_mouse_implementation, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send focus_follows_mouse message: {}", e);
@@ -2054,7 +2055,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::ToggleFocusFollowsMouse(focus_follows_mouse_implementation);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_focus_follows_mouse message: {}", e);
@@ -2064,7 +2065,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("mouse_follows_focus", |param: bool| {
                 let message = SocketMessage::MouseFollowsFocus(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send mouse_follows_focus message: {}", e);
@@ -2074,7 +2075,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_mouse_follows_focus", || {
                 let message = SocketMessage::ToggleMouseFollowsFocus;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_mouse_follows_focus message: {}", e);
@@ -2094,7 +2095,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     }
                 };
                 let message = SocketMessage::RemoveTitleBar(application_identifier, param_1);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send remove_title_bar message: {}", e);
@@ -2104,7 +2105,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("toggle_title_bars", || {
                 let message = SocketMessage::ToggleTitleBars;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send toggle_title_bars message: {}", e);
@@ -2114,7 +2115,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("add_subscriber_socket", |param: String| {
                 let message = SocketMessage::AddSubscriberSocket(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send add_subscriber_socket message: {}", e);
@@ -2127,7 +2128,7 @@ impl Plugin for KomoToolKomorebicPlugin {
                     filter_state_changes: param_1,
                 };
                 let message = SocketMessage::AddSubscriberSocketWithOptions(param_0, subscribe_options);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send add_subscriber_socket_with_options message: {}", e);
@@ -2137,7 +2138,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("remove_subscriber_socket", |param: String| {
                 let message = SocketMessage::RemoveSubscriberSocket(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send remove_subscriber_socket message: {}", e);
@@ -2147,7 +2148,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("add_subscriber_pipe", |param: String| {
                 let message = SocketMessage::AddSubscriberPipe(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send add_subscriber_pipe message: {}", e);
@@ -2157,7 +2158,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("remove_subscriber_pipe", |param: String| {
                 let message = SocketMessage::RemoveSubscriberPipe(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send remove_subscriber_pipe message: {}", e);
@@ -2167,7 +2168,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("application_specific_configuration_schema", || {
                 let message = SocketMessage::ApplicationSpecificConfigurationSchema;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send application_specific_configuration_schema message: {}", e);
@@ -2177,7 +2178,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("notification_schema", || {
                 let message = SocketMessage::NotificationSchema;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send notification_schema message: {}", e);
@@ -2187,7 +2188,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("socket_schema", || {
                 let message = SocketMessage::SocketSchema;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send socket_schema message: {}", e);
@@ -2197,7 +2198,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("static_config_schema", || {
                 let message = SocketMessage::StaticConfigSchema;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send static_config_schema message: {}", e);
@@ -2207,7 +2208,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("generate_static_config", || {
                 let message = SocketMessage::GenerateStaticConfig;
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send generate_static_config message: {}", e);
@@ -2217,7 +2218,7 @@ impl Plugin for KomoToolKomorebicPlugin {
             })
             .register("debug_window", |param: isize| {
                 let message = SocketMessage::DebugWindow(param);
-                match send_message(&message) {
+                match send_komorebi_message(&message) {
                     Ok(_) => true,
                     Err(e) => {
                         log::error!("Failed to send debug_window message: {}", e);
@@ -1,9 +1,13 @@
 use bevy_app::{App, Plugin};
+use bevy_ecs::entity::Entity;
 use bevy_ecs::schedule::IntoSystemConfigs;
 use bevy_ecs::system::Commands;
+use bevy_mod_scripting::core::ConfigureScriptPlugin;
+use bevy_mod_scripting::core::error::ScriptError;
 use bevy_mod_scripting::lua::LuaScriptingPlugin;
+use bevy_mod_scripting::lua::mlua::{self, Lua};
 use bevy_state::condition::in_state;
-use komotool_assets::{check_scripts_loaded, handle_script_store_updates};
+use komotool_assets::{check_scripts_loaded, handle_script_store_updates, script_config};
 use komotool_utils::callbacklabels::{OnPostStartUp, OnPreStartUp, OnStartUp};
 use komotool_utils::handler::{KomoToolScriptStore, komotool_event_handler};
 use komotool_utils::loading_systems::GlobalLoadingState;
@@ -11,6 +15,7 @@ use komotool_utils::send_event_systems::{
     advance_to_all_done, send_post_startup_events, send_pre_startup_events, send_startup_events,
 };
 use komotool_utils::startup_schedule::{PostUpdateStartup, PreUpdateStartup, UpdateStartup};
+use serde_json::Value;
 
 /// Adds Lua Scripting functionality to your [`App`]
 #[derive(Default)]
@@ -18,7 +23,9 @@ pub struct KomoToolLuaPlugin;
 
 impl Plugin for KomoToolLuaPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(LuaScriptingPlugin::default())
+        let lua_plugin =
+            LuaScriptingPlugin::default().add_context_pre_handling_initializer(set_config_global);
+        app.add_plugins(lua_plugin)
             .init_resource::<KomoToolScriptStore<LuaScriptingPlugin, OnPreStartUp>>()
             .init_resource::<KomoToolScriptStore<LuaScriptingPlugin, OnStartUp>>()
             .init_resource::<KomoToolScriptStore<LuaScriptingPlugin, OnPostStartUp>>()
@@ -66,3 +73,45 @@ pub fn lua_cleanup_script_stores(mut commands: Commands) {
 
     println!("All lua script stores removed.");
 }
+
+/// Exposes the script's sibling config file as the global `config` table, or `nil` without one.
+fn set_config_global(
+    script_id: &str,
+    _entity: Entity,
+    context: &mut Lua,
+) -> Result<(), ScriptError> {
+    let config = match script_config(script_id) {
+        Some(value) => json_to_lua(context, &value).map_err(ScriptError::from_mlua_error)?,
+        None => mlua::Value::Nil,
+    };
+    context
+        .globals()
+        .set("config", config)
+        .map_err(ScriptError::from_mlua_error)
+}
+
+fn json_to_lua(lua: &Lua, value: &Value) -> mlua::Result<mlua::Value> {
+    Ok(match value {
+        Value::Null => mlua::Value::Nil,
+        Value::Bool(b) => mlua::Value::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => mlua::Value::Integer(i),
+            None => mlua::Value::Number(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => mlua::Value::String(lua.create_string(s)?),
+        Value::Array(items) => {
+            let table = lua.create_table()?;
+            for (idx, item) in items.iter().enumerate() {
+                table.set(idx + 1, json_to_lua(lua, item)?)?;
+            }
+            mlua::Value::Table(table)
+        }
+        Value::Object(map) => {
+            let table = lua.create_table()?;
+            for (key, item) in map {
+                table.set(key.as_str(), json_to_lua(lua, item)?)?;
+            }
+            mlua::Value::Table(table)
+        }
+    })
+}
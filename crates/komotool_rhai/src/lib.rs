@@ -1,9 +1,13 @@
 use bevy_app::{App, Plugin};
+use bevy_ecs::entity::Entity;
 use bevy_ecs::schedule::IntoSystemConfigs;
 use bevy_ecs::system::Commands;
-use bevy_mod_scripting::rhai::RhaiScriptingPlugin;
+use bevy_mod_scripting::core::ConfigureScriptPlugin;
+use bevy_mod_scripting::core::error::ScriptError;
+use bevy_mod_scripting::rhai::rhai::Dynamic;
+use bevy_mod_scripting::rhai::{RhaiScriptContext, RhaiScriptingPlugin};
 use bevy_state::condition::in_state;
-use komotool_assets::{check_scripts_loaded, handle_script_store_updates};
+use komotool_assets::{check_scripts_loaded, handle_script_store_updates, script_config};
 use komotool_utils::callbacklabels::{OnPostStartUp, OnPreStartUp, OnStartUp};
 use komotool_utils::handler::{KomoToolScriptStore, komotool_event_handler};
 use komotool_utils::loading_systems::GlobalLoadingState;
@@ -11,6 +15,7 @@ use komotool_utils::send_event_systems::{
     advance_to_all_done, send_post_startup_events, send_pre_startup_events, send_startup_events,
 };
 use komotool_utils::startup_schedule::{PostUpdateStartup, PreUpdateStartup, UpdateStartup};
+use serde_json::Value;
 
 /// Adds Rhai Scripting functionality to your [`App`]
 #[derive(Default)]
@@ -18,7 +23,9 @@ pub struct KomoToolRhaiPlugin;
 
 impl Plugin for KomoToolRhaiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(RhaiScriptingPlugin::default())
+        let rhai_plugin =
+            RhaiScriptingPlugin::default().add_context_pre_handling_initializer(set_config_global);
+        app.add_plugins(rhai_plugin)
             .init_resource::<KomoToolScriptStore<RhaiScriptingPlugin, OnPreStartUp>>()
             .init_resource::<KomoToolScriptStore<RhaiScriptingPlugin, OnStartUp>>()
             .init_resource::<KomoToolScriptStore<RhaiScriptingPlugin, OnPostStartUp>>()
@@ -66,3 +73,32 @@ pub fn rhai_cleanup_script_stores(mut commands: Commands) {
 
     println!("All rhai script stores removed.");
 }
+
+/// Exposes the script's sibling config file as the `config` map, or `()` without one.
+fn set_config_global(
+    script_id: &str,
+    _entity: Entity,
+    context: &mut RhaiScriptContext,
+) -> Result<(), ScriptError> {
+    let config = script_config(script_id).map_or(Dynamic::UNIT, |value| json_to_dynamic(&value));
+    context.scope.set_or_push("config", config);
+    Ok(())
+}
+
+fn json_to_dynamic(value: &Value) -> Dynamic {
+    match value {
+        Value::Null => Dynamic::UNIT,
+        Value::Bool(b) => Dynamic::from_bool(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Dynamic::from_int(i),
+            None => Dynamic::from_float(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => Dynamic::from(s.clone()),
+        Value::Array(items) => Dynamic::from_array(items.iter().map(json_to_dynamic).collect()),
+        Value::Object(map) => Dynamic::from_map(
+            map.iter()
+                .map(|(key, item)| (key.as_str().into(), json_to_dynamic(item)))
+                .collect(),
+        ),
+    }
+}
@@ -6,7 +6,16 @@ callback_labels!(
     OnPostStartUp => "on_post_startup",
     OnPreUpdate => "on_pre_update",
     OnUpdate => "on_update",
-    OnPostUpdate => "on_post_update"
+    OnPostUpdate => "on_post_update",
+    OnConfigChanged => "on_config_changed",
+    OnWindowSpawned => "on_window_spawned",
+    OnWindowDespawned => "on_window_despawned",
+    OnContainerMoved => "on_container_moved",
+    OnWorkspaceFocused => "on_workspace_focused",
+    OnMonitorConnected => "on_monitor_connected",
+    OnMonitorDisconnected => "on_monitor_disconnected",
+    OnFocusChanged => "on_focus_changed",
+    OnLayout => "on_layout"
 );
 
 impl Default for OnUpdate {
@@ -44,3 +53,57 @@ impl Default for OnPostStartUp {
         Self
     }
 }
+
+impl Default for OnConfigChanged {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl Default for OnWindowSpawned {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl Default for OnWindowDespawned {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl Default for OnContainerMoved {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl Default for OnWorkspaceFocused {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl Default for OnMonitorConnected {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl Default for OnMonitorDisconnected {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl Default for OnFocusChanged {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl Default for OnLayout {
+    fn default() -> Self {
+        Self
+    }
+}
@@ -0,0 +1,50 @@
+use bevy_ecs::reflect::ReflectResource;
+use bevy_ecs::system::Resource;
+use bevy_reflect::Reflect;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Location and message of a script that failed validation.
+#[derive(Debug, Clone, Reflect, PartialEq, Eq)]
+pub struct ScriptDiagnostic {
+    /// Script path relative to the komotool config directory
+    pub file: String,
+    /// 1-based line, if the parser reported one
+    pub line: Option<usize>,
+    /// 1-based column, if the parser reported one
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => {
+                write!(f, "{}:{}:{}: {}", self.file, line, column, self.message)
+            }
+            (Some(line), None) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            _ => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+/// Latest diagnostic per script. A script with no entry last validated cleanly.
+#[derive(Resource, Default, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct ScriptDiagnostics {
+    pub entries: HashMap<String, ScriptDiagnostic>,
+}
+
+impl ScriptDiagnostics {
+    pub fn report(&mut self, diagnostic: ScriptDiagnostic) {
+        self.entries.insert(diagnostic.file.clone(), diagnostic);
+    }
+
+    pub fn clear(&mut self, file: &str) {
+        self.entries.remove(file);
+    }
+
+    pub fn get(&self, file: &str) -> Option<&ScriptDiagnostic> {
+        self.entries.get(file)
+    }
+}
@@ -1,5 +1,6 @@
 use super::ScriptFunctionChecker;
 use super::{KomoToolScriptStore, KomoToolScriptStoreAll};
+use crate::script_groups::ScriptGroups;
 use bevy_asset::AssetPath;
 use bevy_ecs::component::Tick;
 use bevy_ecs::entity::Entity;
@@ -101,6 +102,7 @@ pub struct SystemResScope<
     L: IntoCallbackLabel + Send + Sync + 'static + std::default::Default,
 > {
     pub(crate) store: ResScope<'w, KomoToolScriptStore<P, L>>,
+    pub(crate) groups: ResScope<'w, ScriptGroups>,
 }
 
 #[derive(SystemParam)]
@@ -110,6 +112,7 @@ pub struct SystemResScopeAll<
 > {
     pub(crate) store: ResScope<'w, KomoToolScriptStoreAll<L>>,
     pub(crate) settings: ResScope<'w, ScriptAssetSettings>,
+    pub(crate) groups: ResScope<'w, ScriptGroups>,
 }
 
 macro_rules! push_err_and_continue {
@@ -192,6 +195,10 @@ fn komotool_event_handler_inner<
         };
 
         for script_id in scripts_to_process {
+            // Scripts in a deactivated group stay loaded but receive no callbacks
+            if !script_store_query.groups.0.is_script_active(&script_id) {
+                continue;
+            }
             let entity = Entity::from_raw(0);
             let call_result = handler_ctxt.call_dynamic_label(
                 &callback_label,
@@ -299,6 +306,10 @@ fn komotool_event_handler_inner_all<
         };
 
         for script_id in scripts_to_process {
+            // Scripts in a deactivated group stay loaded but receive no callbacks
+            if !script_store_query.groups.0.is_script_active(&script_id) {
+                continue;
+            }
             let language = script_store_query
                 .settings
                 .0
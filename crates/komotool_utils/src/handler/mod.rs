@@ -5,5 +5,5 @@ pub mod script_store;
 
 pub use insert_handler_functions::*;
 pub use komotool_event_handler::*;
-pub use script_function_checker::ScriptFunctionChecker;
+pub use script_function_checker::{ScriptFunctionChecker, SyntaxError};
 pub use script_store::*;
@@ -1,3 +1,4 @@
+use crate::diagnostics::ScriptDiagnostic;
 use bevy_mod_scripting::lua::LuaScriptingPlugin;
 use bevy_mod_scripting::rhai::RhaiScriptingPlugin;
 use bevy_mod_scripting::rhai::rhai::{AST, Engine};
@@ -7,10 +8,39 @@ use full_moon::{
 };
 use std::collections::HashSet;
 
+/// Parse failure reported by [`ScriptFunctionChecker::check_syntax`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl SyntaxError {
+    fn without_position(message: impl Into<String>) -> Self {
+        Self {
+            line: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn into_diagnostic(self, file: impl Into<String>) -> ScriptDiagnostic {
+        ScriptDiagnostic {
+            file: file.into(),
+            line: self.line,
+            column: self.column,
+            message: self.message,
+        }
+    }
+}
+
 pub trait ScriptFunctionChecker {
     /// Check if a script implementation contains a specific function
     fn has_function(script_bytes: &[u8], function_name: &str) -> bool;
     fn get_functions(script_bytes: &[u8]) -> HashSet<String>;
+    /// Compile the script without running it, returning the first parse error
+    fn check_syntax(script_bytes: &[u8]) -> Result<(), SyntaxError>;
 }
 
 impl ScriptFunctionChecker for RhaiScriptingPlugin {
@@ -21,6 +51,27 @@ impl ScriptFunctionChecker for RhaiScriptingPlugin {
     fn get_functions(script_bytes: &[u8]) -> HashSet<String> {
         extract_rhai_functions(script_bytes)
     }
+
+    fn check_syntax(script_bytes: &[u8]) -> Result<(), SyntaxError> {
+        check_rhai_syntax(script_bytes)
+    }
+}
+
+fn check_rhai_syntax(rhai_code: &[u8]) -> Result<(), SyntaxError> {
+    let code_str = std::str::from_utf8(rhai_code)
+        .map_err(|e| SyntaxError::without_position(format!("Script is not valid UTF-8: {}", e)))?;
+
+    match Engine::new().compile(code_str) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            let position = e.position();
+            Err(SyntaxError {
+                line: position.line(),
+                column: position.position(),
+                message: e.err_type().to_string(),
+            })
+        }
+    }
 }
 
 fn has_rhai_function(rhai_code: &[u8], function_name: &str) -> bool {
@@ -77,6 +128,30 @@ impl ScriptFunctionChecker for LuaScriptingPlugin {
     fn get_functions(script_bytes: &[u8]) -> HashSet<String> {
         get_lua_functions(script_bytes)
     }
+
+    fn check_syntax(script_bytes: &[u8]) -> Result<(), SyntaxError> {
+        check_lua_syntax(script_bytes)
+    }
+}
+
+fn check_lua_syntax(lua_code: &[u8]) -> Result<(), SyntaxError> {
+    let code_str = std::str::from_utf8(lua_code)
+        .map_err(|e| SyntaxError::without_position(format!("Script is not valid UTF-8: {}", e)))?;
+
+    match parse(code_str) {
+        Ok(_) => Ok(()),
+        Err(errors) => match errors.first() {
+            Some(error) => {
+                let (start, _) = error.range();
+                Err(SyntaxError {
+                    line: Some(start.line()),
+                    column: Some(start.character()),
+                    message: error.error_message().to_string(),
+                })
+            }
+            None => Err(SyntaxError::without_position("Failed to parse Lua script")),
+        },
+    }
 }
 
 fn has_global_function(lua_code: &[u8], function_name: &str) -> bool {
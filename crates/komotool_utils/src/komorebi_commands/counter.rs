@@ -0,0 +1,22 @@
+use bevy_ecs::system::Resource;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Messages komotool has sent to komorebi since startup. Undo history compares it between
+/// frames to notice that komotool changed something and snapshot the state from before.
+///
+/// Clones share the count, so the socket transport keeps one while the komorebic script
+/// functions reach the resource through the world they are called with.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct KomorebiCommandCounter(Arc<AtomicU64>);
+
+impl KomorebiCommandCounter {
+    /// Counts a message komotool is about to send to komorebi.
+    pub fn record(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn sent(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,5 @@
+pub mod counter;
+pub mod outgoing;
+
+pub use counter::*;
+pub use outgoing::*;
@@ -0,0 +1,119 @@
+use crate::komorebi_commands::KomorebiCommandCounter;
+use bevy_ecs::system::Resource;
+use bevy_mod_scripting::core::bindings::{ThreadWorldContainer, WorldContainer};
+use komorebi_client::{SocketMessage, send_message};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// What happens to messages komotool sends to komorebi.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutgoingMode {
+    /// Messages are sent
+    #[default]
+    Live,
+    /// Messages are logged instead of sent
+    DryRun,
+    /// Messages are logged and applied to a simulated copy of komorebi's state instead of sent
+    Shadow,
+}
+
+impl OutgoingMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "live" => Some(Self::Live),
+            "dry-run" | "dry_run" => Some(Self::DryRun),
+            "shadow" => Some(Self::Shadow),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Live => "live",
+            Self::DryRun => "dry-run",
+            Self::Shadow => "shadow",
+        }
+    }
+
+    fn from_u8(mode: u8) -> Self {
+        match mode {
+            1 => Self::DryRun,
+            2 => Self::Shadow,
+            _ => Self::Live,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            Self::Live => 0,
+            Self::DryRun => 1,
+            Self::Shadow => 2,
+        }
+    }
+}
+
+/// Where messages komotool sends to komorebi go: the [`OutgoingMode`], and the messages
+/// intercepted in shadow mode waiting to be simulated.
+///
+/// Clones share both, so the socket transport delivers with the mode scripts and komotoolc
+/// switch on the resource.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct KomorebiOutgoing {
+    mode: Arc<AtomicU8>,
+    shadowed: Arc<Mutex<Vec<SocketMessage>>>,
+}
+
+impl KomorebiOutgoing {
+    pub fn mode(&self) -> OutgoingMode {
+        OutgoingMode::from_u8(self.mode.load(Ordering::Relaxed))
+    }
+
+    pub fn set_mode(&self, mode: OutgoingMode) {
+        let previous = OutgoingMode::from_u8(self.mode.swap(mode.as_u8(), Ordering::Relaxed));
+        if previous != mode {
+            println!("Outgoing komorebi messages: {}", mode.as_str());
+        }
+        if mode != OutgoingMode::Shadow {
+            self.take_shadowed();
+        }
+    }
+
+    /// Drains the messages intercepted in shadow mode, in the order they were sent.
+    pub fn take_shadowed(&self) -> Vec<SocketMessage> {
+        self.shadowed
+            .lock()
+            .map(|mut messages| std::mem::take(&mut *messages))
+            .unwrap_or_default()
+    }
+
+    /// Delivers a message according to the [`OutgoingMode`] without counting it.
+    pub fn deliver(&self, message: &SocketMessage) -> std::io::Result<()> {
+        let mode = self.mode();
+        if mode == OutgoingMode::Live {
+            return send_message(message);
+        }
+        println!("[{}] {:?}", mode.as_str(), message);
+        if mode == OutgoingMode::Shadow {
+            if let Ok(mut messages) = self.shadowed.lock() {
+                messages.push(message.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sends a message from a script function, counting it in the world's
+/// [`KomorebiCommandCounter`] for undo history.
+///
+/// Outside [`OutgoingMode::Live`] the message is intercepted instead.
+pub fn send_komorebi_message(message: &SocketMessage) -> std::io::Result<()> {
+    let world = ThreadWorldContainer
+        .try_get_world()
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    world
+        .with_resource(|counter: &KomorebiCommandCounter| counter.record())
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    world
+        .with_resource(|outgoing: &KomorebiOutgoing| outgoing.deliver(message))
+        .map_err(|e| std::io::Error::other(e.to_string()))?
+}
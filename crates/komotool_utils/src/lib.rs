@@ -1,14 +1,22 @@
 pub mod callbacklabels;
+pub mod diagnostics;
 pub mod handler;
+pub mod komorebi_commands;
 pub mod loading_systems;
+pub mod script_functions;
+pub mod script_groups;
 pub mod send_event_systems;
 pub mod startup_schedule;
 
 pub mod prelude {
     pub use super::*;
     pub use callbacklabels::*;
+    pub use diagnostics::*;
     pub use handler::*;
+    pub use komorebi_commands::*;
     pub use loading_systems::*;
+    pub use script_functions::*;
+    pub use script_groups::*;
     pub use send_event_systems::*;
     pub use startup_schedule::*;
 }
@@ -18,10 +26,13 @@ use bevy_ecs::prelude::{not, resource_added, resource_changed};
 use bevy_ecs::schedule::{Condition, IntoSystemConfigs, Schedule};
 use bevy_state::app::AppExtStates;
 use bevy_state::condition::in_state;
+use diagnostics::ScriptDiagnostics;
 use handler::KomoToolScriptStoreAll;
 use handler::insert_komotool_handlers;
 use loading_systems::*;
 use prelude::*;
+use script_functions::{Komotool, register_script_group_functions};
+use script_groups::ScriptGroups;
 use startup_schedule::configure_single_threaded_schedules;
 use startup_schedule::{PostUpdateStartup, PreUpdateStartup, UpdateStartup};
 
@@ -32,13 +43,28 @@ impl Plugin for KomoToolUtilsPlugin {
     fn build(&self, app: &mut App) {
         let app = app
             .init_resource::<LoadingCounter>()
+            .register_type::<ScriptDiagnostics>()
+            .init_resource::<ScriptDiagnostics>()
+            .register_type::<ScriptGroups>()
+            .init_resource::<ScriptGroups>()
+            .register_type::<Komotool>()
             .init_resource::<KomoToolScriptStoreAll<OnPreUpdate>>()
             .init_resource::<KomoToolScriptStoreAll<OnUpdate>>()
             .init_resource::<KomoToolScriptStoreAll<OnPostUpdate>>()
+            .init_resource::<KomoToolScriptStoreAll<OnConfigChanged>>()
+            .init_resource::<KomoToolScriptStoreAll<OnWindowSpawned>>()
+            .init_resource::<KomoToolScriptStoreAll<OnWindowDespawned>>()
+            .init_resource::<KomoToolScriptStoreAll<OnContainerMoved>>()
+            .init_resource::<KomoToolScriptStoreAll<OnWorkspaceFocused>>()
+            .init_resource::<KomoToolScriptStoreAll<OnMonitorConnected>>()
+            .init_resource::<KomoToolScriptStoreAll<OnMonitorDisconnected>>()
+            .init_resource::<KomoToolScriptStoreAll<OnFocusChanged>>()
+            .init_resource::<KomoToolScriptStoreAll<OnLayout>>()
             .init_state::<GlobalLoadingState>()
             .add_schedule(Schedule::new(PreUpdateStartup))
             .add_schedule(Schedule::new(UpdateStartup))
             .add_schedule(Schedule::new(PostUpdateStartup));
+        register_script_group_functions(app);
         if let Some(mut main_schedule_order) =
             app.world_mut().get_resource_mut::<MainScheduleOrder>()
         {
@@ -92,6 +118,23 @@ impl Plugin for KomoToolUtilsPlugin {
         .add_systems(
             UpdateStartup,
             insert_komotool_handlers.run_if(in_state(GlobalLoadingState::CleanupDone)),
+        )
+        // Config changes are event driven, so they are handled every frame rather than on the fixed clock
+        .add_systems(PostUpdate, komotool_event_handler_all::<OnConfigChanged>)
+        // Lifecycle and focus callbacks follow komorebi's notifications the same way
+        .add_systems(
+            PostUpdate,
+            (
+                komotool_event_handler_all::<OnWindowSpawned>,
+                komotool_event_handler_all::<OnWindowDespawned>,
+                komotool_event_handler_all::<OnContainerMoved>,
+                komotool_event_handler_all::<OnWorkspaceFocused>,
+                komotool_event_handler_all::<OnMonitorConnected>,
+                komotool_event_handler_all::<OnMonitorDisconnected>,
+                komotool_event_handler_all::<OnFocusChanged>,
+                komotool_event_handler_all::<OnLayout>,
+            )
+                .chain(),
         );
     }
 }
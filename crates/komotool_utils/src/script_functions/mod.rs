@@ -0,0 +1,3 @@
+pub mod namespace;
+
+pub use namespace::*;
@@ -0,0 +1,88 @@
+use crate::diagnostics::ScriptDiagnostics;
+use crate::script_groups::ScriptGroups;
+use bevy_app::App;
+use bevy_ecs::change_detection::Mut;
+use bevy_mod_scripting::core::bindings::function::namespace::NamespaceBuilder;
+use bevy_mod_scripting::core::bindings::function::script_function::FunctionCallContext;
+use bevy_mod_scripting::core::error::InteropError;
+use bevy_reflect::Reflect;
+
+/// Namespace for komotool's own script functions, available to scripts as `Komotool`.
+#[derive(Reflect)]
+pub struct Komotool;
+
+pub fn register_script_group_functions(app: &mut App) {
+    NamespaceBuilder::<Komotool>::new(app.world_mut())
+        .register(
+            "activate_group",
+            |ctxt: FunctionCallContext, group: String| -> Result<(), InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource_mut(|mut groups: Mut<ScriptGroups>| {
+                    groups.activate_group(&group);
+                })
+            },
+        )
+        .register(
+            "deactivate_group",
+            |ctxt: FunctionCallContext, group: String| -> Result<(), InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource_mut(|mut groups: Mut<ScriptGroups>| {
+                    groups.deactivate_group(&group);
+                })
+            },
+        )
+        .register(
+            "is_group_active",
+            |ctxt: FunctionCallContext, group: String| -> Result<bool, InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource(|groups: &ScriptGroups| groups.is_group_active(&group))
+            },
+        )
+        .register(
+            "active_groups",
+            |ctxt: FunctionCallContext| -> Result<Vec<String>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource(|groups: &ScriptGroups| groups.active_groups())
+            },
+        )
+        .register(
+            "switch_profile",
+            |ctxt: FunctionCallContext, profile: String| -> Result<bool, InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource_mut(|mut groups: Mut<ScriptGroups>| {
+                    let switched = groups.switch_profile(&profile);
+                    if !switched {
+                        bevy_log::warn!("Unknown script profile: {}", profile);
+                    }
+                    switched
+                })
+            },
+        )
+        .register(
+            "clear_profile",
+            |ctxt: FunctionCallContext| -> Result<(), InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource_mut(|mut groups: Mut<ScriptGroups>| groups.clear_profile())
+            },
+        )
+        .register(
+            "active_profile",
+            |ctxt: FunctionCallContext| -> Result<Option<String>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource(|groups: &ScriptGroups| groups.active_profile.clone())
+            },
+        )
+        .register(
+            "script_diagnostics",
+            |ctxt: FunctionCallContext| -> Result<Vec<String>, InteropError> {
+                let world = ctxt.world()?;
+                world.with_resource(|diagnostics: &ScriptDiagnostics| {
+                    diagnostics
+                        .entries
+                        .values()
+                        .map(|diagnostic| diagnostic.to_string())
+                        .collect()
+                })
+            },
+        );
+}
@@ -0,0 +1,118 @@
+use bevy_ecs::reflect::ReflectResource;
+use bevy_ecs::system::Resource;
+use bevy_reflect::Reflect;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path};
+
+/// Folder below the config directory that scripts are loaded from.
+pub const SCRIPTS_FOLDER: &str = "scripts";
+
+/// Returns the group of a script, which is the first folder below `scripts/`.
+///
+/// Scripts placed directly in `scripts/` have no group and are always active.
+pub fn script_group(script_id: &str) -> Option<String> {
+    let path = Path::new(script_id);
+    let relative = path.strip_prefix(SCRIPTS_FOLDER).unwrap_or(path);
+    let mut components = relative.components();
+    let first = components.next()?;
+    // A single component is the file itself
+    components.next()?;
+    match first {
+        Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+        _ => None,
+    }
+}
+
+/// Which script groups receive callbacks, and the profiles that switch between them.
+#[derive(Resource, Default, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct ScriptGroups {
+    /// Every group seen so far
+    pub known: HashSet<String>,
+    /// Groups the active profile enables. `None` means every group not in `disabled` is active.
+    pub enabled: Option<HashSet<String>>,
+    /// Groups switched off by hand, on top of the active profile
+    pub disabled: HashSet<String>,
+    /// Profile name to the groups it enables
+    pub profiles: HashMap<String, HashSet<String>>,
+    pub active_profile: Option<String>,
+}
+
+impl ScriptGroups {
+    pub fn register_script(&mut self, script_id: &str) {
+        if let Some(group) = script_group(script_id) {
+            self.known.insert(group);
+        }
+    }
+
+    pub fn is_group_active(&self, group: &str) -> bool {
+        if self.disabled.contains(group) {
+            return false;
+        }
+        match &self.enabled {
+            Some(enabled) => enabled.contains(group),
+            None => true,
+        }
+    }
+
+    /// Whether callbacks should be dispatched to this script.
+    pub fn is_script_active(&self, script_id: &str) -> bool {
+        match script_group(script_id) {
+            Some(group) => self.is_group_active(&group),
+            None => true,
+        }
+    }
+
+    pub fn activate_group(&mut self, group: &str) {
+        self.disabled.remove(group);
+        if let Some(enabled) = &mut self.enabled {
+            enabled.insert(group.to_string());
+        }
+    }
+
+    pub fn deactivate_group(&mut self, group: &str) {
+        self.disabled.insert(group.to_string());
+    }
+
+    /// Makes exactly the profile's groups active. Returns false for an unknown profile.
+    pub fn switch_profile(&mut self, profile: &str) -> bool {
+        let Some(groups) = self.profiles.get(profile) else {
+            return false;
+        };
+        self.enabled = Some(groups.clone());
+        self.disabled.clear();
+        self.active_profile = Some(profile.to_string());
+        true
+    }
+
+    /// Replaces the profiles after `profiles.json` changed. The active profile picks up its new
+    /// groups, and is dropped if it no longer exists.
+    pub fn set_profiles(&mut self, profiles: HashMap<String, HashSet<String>>) {
+        self.profiles = profiles;
+        let Some(active) = &self.active_profile else {
+            return;
+        };
+        match self.profiles.get(active) {
+            Some(groups) => self.enabled = Some(groups.clone()),
+            None => self.clear_profile(),
+        }
+    }
+
+    /// Drops the active profile so every group not disabled by hand is active again.
+    pub fn clear_profile(&mut self) {
+        self.enabled = None;
+        self.active_profile = None;
+    }
+
+    /// Known groups that currently receive callbacks, sorted by name.
+    pub fn active_groups(&self) -> Vec<String> {
+        let mut groups: Vec<String> = self
+            .known
+            .iter()
+            .filter(|group| self.is_group_active(group))
+            .cloned()
+            .collect();
+        groups.sort();
+        groups
+    }
+}
@@ -0,0 +1,3 @@
+pub mod groups;
+
+pub use groups::*;
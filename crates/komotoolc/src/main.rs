@@ -1,3 +1,93 @@
-fn main() {
-    println!("Hello, world!");
+use komotoolc_pipe::{
+    DEFAULT_SESSION, KomotoolcMessage, KomotoolcResponse, komotoolc_pipe_name,
+    send_komotoolc_message,
+};
+use std::env;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage:
+  komotoolc groups                      List the active profile and script groups
+  komotoolc group activate <group>      Activate a script group
+  komotoolc group deactivate <group>    Deactivate a script group
+  komotoolc profile <profile>           Switch to a script profile
+  komotoolc profile --clear             Leave the active profile
+  komotoolc diagnostics                 Show script validation errors
+  komotoolc static-config diff          Show the working static config's changes
+  komotoolc static-config apply         Write the working static config and reload it in komorebi
+  komotoolc static-config apply --dry-run
+                                        Same as diff, without applying anything
+  komotoolc static-config apply --replace
+                                        Replace komorebi's configuration instead of reloading
+  komotoolc static-config reset         Discard script edits to the working static config
+  komotoolc session save [<name>]       Snapshot the window arrangement
+  komotoolc session restore [<name>]    Move windows back into a saved arrangement
+  komotoolc session list                List saved sessions
+  komotoolc undo                        Revert the arrangement before komotool's last command
+  komotoolc redo                        Reapply an undone arrangement
+  komotoolc traffic                     Show whether messages to komorebi are sent
+  komotoolc traffic live                Send messages to komorebi
+  komotoolc traffic dry-run             Log messages to komorebi instead of sending them
+  komotoolc traffic shadow              Log messages and simulate them on komotool's state";
+
+fn parse_args(args: &[String]) -> Option<KomotoolcMessage> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["groups"] => Some(KomotoolcMessage::ScriptGroups),
+        ["group", "activate", group] => Some(KomotoolcMessage::ActivateGroup(group.to_string())),
+        ["group", "deactivate", group] => {
+            Some(KomotoolcMessage::DeactivateGroup(group.to_string()))
+        }
+        ["profile", "--clear"] => Some(KomotoolcMessage::ClearProfile),
+        ["profile", profile] => Some(KomotoolcMessage::SwitchProfile(profile.to_string())),
+        ["diagnostics"] => Some(KomotoolcMessage::ScriptDiagnostics),
+        ["static-config", "diff"] | ["static-config", "apply", "--dry-run"] => {
+            Some(KomotoolcMessage::StaticConfigDiff)
+        }
+        ["static-config", "apply"] => Some(KomotoolcMessage::ApplyStaticConfig { replace: false }),
+        ["static-config", "apply", "--replace"] => {
+            Some(KomotoolcMessage::ApplyStaticConfig { replace: true })
+        }
+        ["static-config", "reset"] => Some(KomotoolcMessage::ResetStaticConfig),
+        ["session", "save"] => Some(KomotoolcMessage::SaveSession(DEFAULT_SESSION.to_string())),
+        ["session", "save", name] => Some(KomotoolcMessage::SaveSession(name.to_string())),
+        ["session", "restore"] => Some(KomotoolcMessage::RestoreSession(
+            DEFAULT_SESSION.to_string(),
+        )),
+        ["session", "restore", name] => Some(KomotoolcMessage::RestoreSession(name.to_string())),
+        ["session", "list"] => Some(KomotoolcMessage::Sessions),
+        ["undo"] => Some(KomotoolcMessage::Undo),
+        ["redo"] => Some(KomotoolcMessage::Redo),
+        ["traffic"] => Some(KomotoolcMessage::OutgoingMode),
+        ["traffic", mode] => Some(KomotoolcMessage::SetOutgoingMode(mode.to_string())),
+        _ => None,
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some(message) = parse_args(&args) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    match send_komotoolc_message(&message) {
+        Ok(KomotoolcResponse::Ok(output)) => {
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+            ExitCode::SUCCESS
+        }
+        Ok(KomotoolcResponse::Error(e)) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!(
+                "Failed to reach komotool on pipe {}: {}",
+                komotoolc_pipe_name(),
+                e
+            );
+            ExitCode::FAILURE
+        }
+    }
 }
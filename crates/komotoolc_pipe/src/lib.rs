@@ -1,8 +1,268 @@
-use bevy_app::{App, Plugin};
+pub mod protocol;
+
+pub use protocol::*;
+
+use bevy_app::{App, First, Last, Plugin};
+use bevy_ecs::event::{Event, EventReader, EventWriter};
+use bevy_ecs::schedule::IntoSystemConfigs;
+use bevy_ecs::system::{NonSend, Res, ResMut};
+use crossbeam_channel::{Receiver, Sender, unbounded};
+#[cfg(windows)]
+use interprocess::local_socket::GenericNamespaced;
+use interprocess::local_socket::{Listener, ListenerOptions, Name, Stream, prelude::*};
+#[cfg(unix)]
+use interprocess::{local_socket::GenericFilePath, os::unix::local_socket::ListenerOptionsExt};
+use komotool_utils::diagnostics::ScriptDiagnostics;
+use komotool_utils::script_groups::ScriptGroups;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// How long a komotoolc connection waits for a system that claimed its command to answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Default)]
 pub struct KomoToolcPipePlugin;
 
+/// A command received from komotoolc.
+///
+/// Systems that understand the message answer through [`KomotoolcCommandEvent::reply`];
+/// the first reply is sent back to komotoolc. Commands nobody replied to or claimed by the
+/// end of the frame are answered with an unknown command error.
+#[derive(Event, Clone)]
+pub struct KomotoolcCommandEvent {
+    pub message: KomotoolcMessage,
+    reply_sender: Sender<KomotoolcResponse>,
+    claimed: Arc<AtomicBool>,
+}
+
+impl KomotoolcCommandEvent {
+    pub fn reply(&self, response: KomotoolcResponse) {
+        self.claim();
+        let _ = self.reply_sender.send(response);
+    }
+
+    /// Marks the command as handled by a system that replies in a later frame.
+    pub fn claim(&self) {
+        self.claimed.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_claimed(&self) -> bool {
+        self.claimed.load(Ordering::Relaxed)
+    }
+}
+
+/// A command handed from the listener thread to the main world.
+pub struct KomotoolcRequest {
+    message: KomotoolcMessage,
+    reply_sender: Sender<KomotoolcResponse>,
+}
+
 impl Plugin for KomoToolcPipePlugin {
-    fn build(&self, app: &mut App) {}
+    fn build(&self, app: &mut App) {
+        app.add_event::<KomotoolcCommandEvent>();
+        let (sender, receiver) = unbounded();
+
+        let name = komotoolc_socket_display();
+        match create_komotoolc_listener() {
+            Ok(listener) => {
+                println!("Listening for komotoolc on {}", name);
+                thread::spawn(move || run_komotoolc_listener(listener, sender));
+            }
+            Err(e) => eprintln!("Failed to listen for komotoolc on {}: {}", name, e),
+        }
+
+        app.insert_non_send_resource(receiver)
+            .add_systems(
+                First,
+                (forward_komotoolc_requests, handle_script_group_commands).chain(),
+            )
+            .add_systems(Last, reject_unclaimed_commands);
+    }
+}
+
+/// The local socket komotool and komotoolc meet on.
+///
+/// A named pipe on Windows, whose default security only lets other users open it for reading.
+/// On unix a socket file in the user's runtime dir, created with mode 0600 so other users
+/// can't connect.
+#[cfg(windows)]
+fn komotoolc_socket_name() -> std::io::Result<Name<'static>> {
+    komotoolc_pipe_name().to_ns_name::<GenericNamespaced>()
+}
+
+#[cfg(unix)]
+fn komotoolc_socket_name() -> std::io::Result<Name<'static>> {
+    komotoolc_socket_path().to_fs_name::<GenericFilePath>()
+}
+
+#[cfg(windows)]
+fn komotoolc_socket_display() -> String {
+    format!("pipe {}", komotoolc_pipe_name())
+}
+
+#[cfg(unix)]
+fn komotoolc_socket_display() -> String {
+    format!("socket {}", komotoolc_socket_path().display())
+}
+
+#[cfg(windows)]
+fn create_komotoolc_listener() -> std::io::Result<Listener> {
+    ListenerOptions::new()
+        .name(komotoolc_socket_name()?)
+        .create_sync()
+}
+
+#[cfg(unix)]
+fn create_komotoolc_listener() -> std::io::Result<Listener> {
+    // A socket file left behind by a komotool that didn't shut down cleanly blocks the bind,
+    // one nobody answers on is stale
+    let path = komotoolc_socket_path();
+    if path.exists() && Stream::connect(komotoolc_socket_name()?).is_err() {
+        std::fs::remove_file(&path)?;
+    }
+    ListenerOptions::new()
+        .name(komotoolc_socket_name()?)
+        .mode(0o600)
+        .create_sync()
+}
+
+fn run_komotoolc_listener(listener: Listener, sender: Sender<KomotoolcRequest>) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    if let Err(e) = handle_komotoolc_connection(stream, &sender) {
+                        println!("komotoolc connection error: {}", e);
+                    }
+                });
+            }
+            Err(e) => println!("Failed to accept komotoolc connection: {}", e),
+        }
+    }
+}
+
+fn handle_komotoolc_connection(
+    stream: Stream,
+    sender: &Sender<KomotoolcRequest>,
+) -> std::io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    let mut line = String::new();
+    stream.read_line(&mut line)?;
+
+    let response = match serde_json::from_str::<KomotoolcMessage>(&line) {
+        Ok(message) => {
+            let (reply_sender, reply_receiver) = unbounded();
+            if sender
+                .send(KomotoolcRequest {
+                    message,
+                    reply_sender,
+                })
+                .is_err()
+            {
+                KomotoolcResponse::Error("komotool is shutting down".to_string())
+            } else {
+                reply_receiver
+                    .recv_timeout(REPLY_TIMEOUT)
+                    .unwrap_or_else(|_| {
+                        KomotoolcResponse::Error("komotool didn't answer in time".to_string())
+                    })
+            }
+        }
+        Err(e) => KomotoolcResponse::Error(format!("Malformed komotoolc message: {}", e)),
+    };
+
+    let mut payload = serde_json::to_string(&response)?;
+    payload.push('\n');
+    stream.get_mut().write_all(payload.as_bytes())
+}
+
+/// Sends a command to the running komotool and waits for its reply.
+pub fn send_komotoolc_message(message: &KomotoolcMessage) -> std::io::Result<KomotoolcResponse> {
+    let mut stream = BufReader::new(Stream::connect(komotoolc_socket_name()?)?);
+    let mut payload = serde_json::to_string(message)?;
+    payload.push('\n');
+    stream.get_mut().write_all(payload.as_bytes())?;
+
+    let mut line = String::new();
+    stream.read_line(&mut line)?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+pub fn forward_komotoolc_requests(
+    receiver: NonSend<Receiver<KomotoolcRequest>>,
+    mut events: EventWriter<KomotoolcCommandEvent>,
+) {
+    for request in receiver.try_iter() {
+        events.send(KomotoolcCommandEvent {
+            message: request.message,
+            reply_sender: request.reply_sender,
+            claimed: Arc::new(AtomicBool::new(false)),
+        });
+    }
+}
+
+pub fn handle_script_group_commands(
+    mut events: EventReader<KomotoolcCommandEvent>,
+    mut groups: ResMut<ScriptGroups>,
+    diagnostics: Res<ScriptDiagnostics>,
+) {
+    for event in events.read() {
+        let response = match &event.message {
+            KomotoolcMessage::ActivateGroup(group) => {
+                groups.activate_group(group);
+                KomotoolcResponse::Ok(format!("Activated group {}", group))
+            }
+            KomotoolcMessage::DeactivateGroup(group) => {
+                groups.deactivate_group(group);
+                KomotoolcResponse::Ok(format!("Deactivated group {}", group))
+            }
+            KomotoolcMessage::SwitchProfile(profile) => {
+                if groups.switch_profile(profile) {
+                    KomotoolcResponse::Ok(format!("Switched to profile {}", profile))
+                } else {
+                    KomotoolcResponse::Error(format!("Unknown profile {}", profile))
+                }
+            }
+            KomotoolcMessage::ClearProfile => {
+                groups.clear_profile();
+                KomotoolcResponse::Ok("Cleared profile".to_string())
+            }
+            KomotoolcMessage::ScriptGroups => {
+                let profile = groups.active_profile.as_deref().unwrap_or("none");
+                KomotoolcResponse::Ok(format!(
+                    "Profile: {}\nActive groups: {}",
+                    profile,
+                    groups.active_groups().join(", ")
+                ))
+            }
+            KomotoolcMessage::ScriptDiagnostics => {
+                let mut lines: Vec<String> = diagnostics
+                    .entries
+                    .values()
+                    .map(|diagnostic| diagnostic.to_string())
+                    .collect();
+                lines.sort();
+                KomotoolcResponse::Ok(lines.join("\n"))
+            }
+            _ => continue,
+        };
+        event.reply(response);
+    }
+}
+
+/// Answers the commands no system replied to or claimed this frame, instead of leaving
+/// komotoolc waiting for the reply timeout.
+pub fn reject_unclaimed_commands(mut events: EventReader<KomotoolcCommandEvent>) {
+    for event in events.read() {
+        if !event.is_claimed() {
+            event.reply(KomotoolcResponse::Error(format!(
+                "Unknown command: {:?}",
+                event.message
+            )));
+        }
+    }
 }
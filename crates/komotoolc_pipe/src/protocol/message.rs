@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+#[cfg(unix)]
+use std::path::PathBuf;
+
+/// Environment variable overriding the name of the pipe komotool listens on for komotoolc.
+pub const KOMOTOOLC_PIPE_ENV: &str = "KOMOTOOLC_PIPE";
+pub const DEFAULT_KOMOTOOLC_PIPE: &str = "komotoolc";
+
+/// Name of the local pipe komotool listens on and komotoolc connects to.
+///
+/// On Windows this names the pipe itself, on unix the socket file in
+/// [`komotoolc_socket_path`].
+pub fn komotoolc_pipe_name() -> String {
+    env::var(KOMOTOOLC_PIPE_ENV)
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| DEFAULT_KOMOTOOLC_PIPE.to_string())
+}
+
+/// The socket file komotool listens on on unix, `<pipe name>.sock` in the user's
+/// `XDG_RUNTIME_DIR`, or the temp dir when that isn't set.
+#[cfg(unix)]
+pub fn komotoolc_socket_path() -> PathBuf {
+    env::var_os("XDG_RUNTIME_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(env::temp_dir)
+        .join(format!("{}.sock", komotoolc_pipe_name()))
+}
+
+/// Session name komotoolc and scripts use when none is given.
+pub const DEFAULT_SESSION: &str = "default";
+
+/// A command sent from komotoolc to a running komotool, one JSON object per line.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", content = "content")]
+pub enum KomotoolcMessage {
+    ActivateGroup(String),
+    DeactivateGroup(String),
+    SwitchProfile(String),
+    ClearProfile,
+    ScriptGroups,
+    ScriptDiagnostics,
+    /// Dry run: the working static config's changes against komorebi.json
+    StaticConfigDiff,
+    /// Writes the working static config and reloads it in komorebi, or replaces komorebi's configuration
+    ApplyStaticConfig {
+        replace: bool,
+    },
+    ResetStaticConfig,
+    /// Snapshots the window arrangement to the named session file
+    SaveSession(String),
+    /// Moves windows back into the named session's arrangement
+    RestoreSession(String),
+    Sessions,
+    /// Moves windows back to where they were before komotool's last command
+    Undo,
+    Redo,
+    /// Whether messages to komorebi are sent, only logged, or simulated
+    OutgoingMode,
+    /// Switches to `live`, `dry-run` or `shadow`
+    SetOutgoingMode(String),
+}
+
+/// The reply komotool writes back, one JSON object per line.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "status", content = "content")]
+pub enum KomotoolcResponse {
+    Ok(String),
+    Error(String),
+}
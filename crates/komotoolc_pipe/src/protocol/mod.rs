@@ -0,0 +1,3 @@
+pub mod message;
+
+pub use message::*;
@@ -12,11 +12,27 @@ use komotool_lua::KomoToolLuaPlugin;
 use komotool_pipe::KomoToolPipePlugin;
 use komotool_rhai::KomoToolRhaiPlugin;
 use komotool_utils::KomoToolUtilsPlugin;
+use komotool_utils::komorebi_commands::{OutgoingMode, set_outgoing_mode};
 //use komotool_windows::KomoToolWindowsPlugin;
 use komotool_framepace::KomotoolFramepacePlugin;
 use komotoolc_pipe::KomoToolcPipePlugin;
 
+/// `--dry-run` logs messages to komorebi instead of sending them, `--shadow` also simulates them.
+fn outgoing_mode_from_args() -> OutgoingMode {
+    let mut mode = OutgoingMode::Live;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--dry-run" => mode = OutgoingMode::DryRun,
+            "--shadow" => mode = OutgoingMode::Shadow,
+            _ => {}
+        }
+    }
+    mode
+}
+
 fn main() -> AppExit {
+    set_outgoing_mode(outgoing_mode_from_args());
+
     App::new()
         .add_plugins(StatesPlugin)
         .add_plugins(TaskPoolPlugin::default())